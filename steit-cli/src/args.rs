@@ -0,0 +1,121 @@
+use std::{collections::HashMap, io};
+
+/// Positional arguments and `--name value` / `--flag` options, in the order they were given.
+#[derive(Default, Debug)]
+pub struct Args {
+    positionals: Vec<String>,
+    options: HashMap<String, Vec<String>>,
+    flags: Vec<String>,
+}
+
+impl Args {
+    /// Parses `args`, accepting the names in `options` as options taking a value
+    /// and those in `flags` as options without one.
+    pub fn parse(
+        args: impl IntoIterator<Item = String>,
+        options: &[&str],
+        flags: &[&str],
+    ) -> io::Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if flags.contains(&name) => parsed.flags.push(name.to_string()),
+
+                Some(name) if options.contains(&name) => {
+                    let value = args.next().ok_or_else(|| {
+                        invalid_input(format!("missing value for option `--{}`", name))
+                    })?;
+
+                    parsed
+                        .options
+                        .entry(name.to_string())
+                        .or_default()
+                        .push(value);
+                }
+
+                Some(name) => return Err(invalid_input(format!("unknown option `--{}`", name))),
+
+                None => parsed.positionals.push(arg),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    pub fn positional(&self, index: usize, name: &str) -> io::Result<&str> {
+        self.positionals
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| invalid_input(format!("missing argument <{}>", name)))
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .get(name)
+            .and_then(|values| values.last())
+            .map(String::as_str)
+    }
+
+    pub fn required_option(&self, name: &str) -> io::Result<&str> {
+        self.option(name)
+            .ok_or_else(|| invalid_input(format!("missing option `--{}`", name)))
+    }
+
+    pub fn options(&self, name: &str) -> &[String] {
+        self.options.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+}
+
+pub fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Args;
+
+    fn parse(args: &[&str]) -> Args {
+        let args = args.iter().map(|arg| arg.to_string());
+        Args::parse(args, &["schema", "using"], &["pretty"]).unwrap()
+    }
+
+    #[test]
+    fn positionals_and_options() {
+        let args = parse(&["log.bin", "--schema", "schema.json", "--pretty", "out"]);
+        assert_eq!(args.positional(0, "log").unwrap(), "log.bin");
+        assert_eq!(args.positional(1, "out").unwrap(), "out");
+        assert_eq!(args.option("schema"), Some("schema.json"));
+        assert!(args.flag("pretty"));
+    }
+
+    #[test]
+    fn repeated_options() {
+        let args = parse(&["--using", "A", "--using", "B"]);
+        assert_eq!(args.options("using"), &["A", "B"]);
+        assert_eq!(args.option("using"), Some("B"));
+    }
+
+    #[test]
+    #[should_panic(expected = "missing value for option `--schema`")]
+    fn missing_value() {
+        parse(&["--schema"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown option `--namspace`")]
+    fn unknown_option() {
+        parse(&["--namspace", "Game", "out"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "missing argument <snapshot>")]
+    fn missing_positional() {
+        parse(&[]).positional(0, "snapshot").unwrap();
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    env, fs,
+    io::{self, Write},
+    process,
+};
+
+use serde_json::Value as Json;
+use steit::{
+    de::{Deserialize, Reader},
+    dynamic::{self, Schema},
+    gen::{
+        generators::{CSharpGenerator, CSharpSetting},
+        Generator, Setting,
+    },
+    log::LogEntry,
+    wire_fmt::HasWireType,
+};
+
+use args::{invalid_input, Args};
+
+mod args;
+
+const USAGE: &str = "\
+Usage:
+    steit dump <LOG> [--schema <SCHEMA>]
+    steit decode <SNAPSHOT> --schema <SCHEMA>
+    steit replay <LOG> --schema <SCHEMA> [--snapshot <SNAPSHOT>] [--out <FILE>]
+    steit gen csharp <OUT_DIR> --schema <SCHEMA> --namespace <NAMESPACE>
+        [--using <NAMESPACE>]... [--namespace-for <MODULE>=<NAMESPACE>]...
+        [--single-file <NAME>] [--with-builtins] [--rust-names]
+        [--nullable] [--serializable] [--unity] [--readonly-properties]
+
+Logs and snapshots are raw steit bytes.
+Schemas are JSON files produced by `steit::dynamic::Schema::export`.
+
+Options:
+    --compact          Print JSON on a single line
+    --with-builtins    Also generate builtin types such as `LogEntry`
+    --rust-names       Name generated files and classes after Rust types
+    --namespace-for    Put types of a Rust module and its submodules under another namespace
+    --single-file      Generate all types into one file
+    --nullable         Enable C# 8 nullable reference types
+    --serializable     Mark classes as `[Serializable]`
+    --unity            Make classes serializable by Unity
+    --readonly-properties
+                       Back properties with private fields instead of private setters
+";
+
+const GEN_OPTIONS: &[&str] = &[
+    "schema",
+    "namespace",
+    "using",
+    "namespace-for",
+    "single-file",
+];
+
+const GEN_FLAGS: &[&str] = &[
+    "with-builtins",
+    "rust-names",
+    "nullable",
+    "serializable",
+    "unity",
+    "readonly-properties",
+];
+
+fn main() {
+    if let Err(error) = run(env::args().skip(1), &mut io::stdout().lock()) {
+        eprintln!("error: {}\n\n{}", error, USAGE);
+        process::exit(1);
+    }
+}
+
+fn run(mut args: impl Iterator<Item = String>, out: &mut impl Write) -> io::Result<()> {
+    let command = args.next();
+
+    match command.as_deref() {
+        Some("dump") => dump(&Args::parse(args, &["schema"], &["compact"])?, out),
+        Some("decode") => decode(&Args::parse(args, &["schema"], &["compact"])?, out),
+        Some("replay") => replay(
+            &Args::parse(args, &["schema", "snapshot", "out"], &["compact"])?,
+            out,
+        ),
+        Some("gen") => gen(&Args::parse(args, GEN_OPTIONS, GEN_FLAGS)?, out),
+
+        None | Some("help") | Some("--help") => write!(out, "{}", USAGE),
+
+        Some(command) => Err(invalid_input(format!("unknown command `{}`", command))),
+    }
+}
+
+fn dump(args: &Args, out: &mut impl Write) -> io::Result<()> {
+    let bytes = fs::read(args.positional(0, "LOG")?)?;
+    let schema = args.option("schema").map(load_schema).transpose()?;
+
+    for entry in read_log(&bytes)? {
+        let json = match &schema {
+            Some(schema) => schema.entry_to_json(&entry)?,
+            None => dynamic::to_json(&entry)?,
+        };
+
+        print_json(args, out, &json)?;
+    }
+
+    Ok(())
+}
+
+fn decode(args: &Args, out: &mut impl Write) -> io::Result<()> {
+    let schema = load_schema(args.required_option("schema")?)?;
+    let bytes = fs::read(args.positional(0, "SNAPSHOT")?)?;
+    let value = schema.decode(&mut Reader::new(&*bytes))?;
+    print_json(args, out, &schema.to_json(&value, schema.root())?)
+}
+
+fn replay(args: &Args, out: &mut impl Write) -> io::Result<()> {
+    let schema = load_schema(args.required_option("schema")?)?;
+    let log = fs::read(args.positional(0, "LOG")?)?;
+
+    let mut value = match args.option("snapshot") {
+        Some(path) => schema.decode(&mut Reader::new(&*fs::read(path)?))?,
+        None => schema.default_value(schema.root())?,
+    };
+
+    schema.replay(&mut value, &mut Reader::new(&*log))?;
+
+    if let Some(path) = args.option("out") {
+        fs::write(path, value.to_bytes())?;
+    }
+
+    print_json(args, out, &schema.to_json(&value, schema.root())?)
+}
+
+fn gen(args: &Args, out: &mut impl Write) -> io::Result<()> {
+    match args.positional(0, "LANGUAGE")? {
+        "csharp" => {
+            let schema = load_schema(args.required_option("schema")?)?;
+            let out_dir = args.positional(1, "OUT_DIR")?;
+            fs::create_dir_all(out_dir)?;
+
+            let mut csharp_setting = CSharpSetting::new(args.required_option("namespace")?)
+                .using_namespaces(args.options("using"))
+                .nullable(args.flag("nullable"))
+                .serializable(args.flag("serializable"))
+                .unity(args.flag("unity"))
+                .readonly_properties(args.flag("readonly-properties"));
+
+            for mapping in args.options("namespace-for") {
+                let (module, namespace) = mapping.split_once('=').ok_or_else(|| {
+                    invalid_input(format!(
+                        "expected <MODULE>=<NAMESPACE> for `--namespace-for`, got `{}`",
+                        mapping
+                    ))
+                })?;
+
+                csharp_setting = csharp_setting.namespace_for(module, namespace);
+            }
+
+            let mut setting =
+                Setting::new(&out_dir, csharp_setting).skip_builtins(!args.flag("with-builtins"));
+
+            if args.flag("rust-names") {
+                setting = setting.get_name_rust();
+            }
+
+            if let Some(name) = args.option("single-file") {
+                setting = setting.single_file(name);
+            }
+
+            for name in CSharpGenerator.generate_schema(&schema, &setting)? {
+                writeln!(out, "{}", name)?;
+            }
+
+            Ok(())
+        }
+
+        language => Err(invalid_input(format!(
+            "unsupported language `{}`",
+            language
+        ))),
+    }
+}
+
+fn load_schema(path: &str) -> io::Result<Schema> {
+    let json: Json = serde_json::from_slice(&fs::read(path)?)?;
+    Schema::import(&json)
+}
+
+fn read_log(bytes: &[u8]) -> io::Result<Vec<LogEntry>> {
+    let reader = &mut Reader::new(bytes);
+    let mut entries = Vec::new();
+
+    while !reader.eof()? {
+        entries.push(LogEntry::deserialize_nested(LogEntry::WIRE_TYPE, reader)?);
+    }
+
+    Ok(entries)
+}
+
+fn print_json(args: &Args, out: &mut impl Write, json: &Json) -> io::Result<()> {
+    let json = if args.flag("compact") {
+        serde_json::to_string(json)?
+    } else {
+        serde_json::to_string_pretty(json)?
+    };
+
+    writeln!(out, "{}", json)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use steit::{
+        dynamic::Schema, log::loggers::BufferLogger, rt::Runtime, ser::Serialize, steit_derive,
+        types::List,
+    };
+
+    use super::run;
+
+    #[steit_derive(Debug, State)]
+    struct Hero {
+        #[steit(tag = 0)]
+        hp: i32,
+        #[steit(tag = 1)]
+        items: List<u32>,
+    }
+
+    /// Writes `schema.json`, `log.bin` and `hero.bin` for a `Hero` into a fresh directory.
+    fn fixture(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("steit-cli-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let schema = Schema::of::<Hero>().unwrap().export();
+        fs::write(dir.join("schema.json"), schema.to_string()).unwrap();
+
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut hero = Hero::new(runtime);
+        hero.set_hp(42);
+        hero.items.push(3);
+        hero.items.push(5);
+
+        fs::write(dir.join("log.bin"), logger.lock().unwrap().bytes()).unwrap();
+        fs::write(dir.join("hero.bin"), hero.to_bytes()).unwrap();
+        dir
+    }
+
+    /// Runs a command, resolving file names such as `log.bin` against `dir`.
+    fn run_in(dir: &Path, args: &[&str]) -> String {
+        let args = args.iter().map(|arg| {
+            if arg.ends_with(".json") || arg.ends_with(".bin") {
+                dir.join(arg).to_str().unwrap().to_string()
+            } else {
+                arg.to_string()
+            }
+        });
+
+        let mut out = Vec::new();
+        run(args, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn dump() {
+        let dir = fixture("dump");
+        let out = run_in(
+            &dir,
+            &["dump", "log.bin", "--schema", "schema.json", "--compact"],
+        );
+
+        assert_eq!(
+            out,
+            "{\"op\":\"update\",\"path\":[\"hp\"],\"value\":42}\n\
+             {\"op\":\"list_push\",\"path\":[\"items\"],\"item\":3}\n\
+             {\"op\":\"list_push\",\"path\":[\"items\"],\"item\":5}\n"
+        );
+    }
+
+    #[test]
+    fn decode() {
+        let dir = fixture("decode");
+        let out = run_in(
+            &dir,
+            &["decode", "hero.bin", "--schema", "schema.json", "--compact"],
+        );
+        assert_eq!(out, "{\"hp\":42,\"items\":[3,5]}\n");
+    }
+
+    #[test]
+    fn replay() {
+        let dir = fixture("replay");
+        let out = run_in(
+            &dir,
+            &[
+                "replay",
+                "log.bin",
+                "--schema",
+                "schema.json",
+                "--out",
+                "out.bin",
+                "--compact",
+            ],
+        );
+
+        assert_eq!(out, "{\"hp\":42,\"items\":[3,5]}\n");
+        assert_eq!(
+            fs::read(dir.join("out.bin")).unwrap(),
+            fs::read(dir.join("hero.bin")).unwrap()
+        );
+    }
+
+    #[test]
+    fn replay_onto_snapshot() {
+        let dir = fixture("replay-snapshot");
+        let out = run_in(
+            &dir,
+            &[
+                "replay",
+                "log.bin",
+                "--schema",
+                "schema.json",
+                "--snapshot",
+                "hero.bin",
+                "--compact",
+            ],
+        );
+
+        assert_eq!(out, "{\"hp\":42,\"items\":[3,5,3,5]}\n");
+    }
+
+    #[test]
+    fn gen_csharp() {
+        let dir = fixture("gen");
+        let out_dir = dir.join("out");
+        let out = run_in(
+            &dir,
+            &[
+                "gen",
+                "csharp",
+                out_dir.to_str().unwrap(),
+                "--schema",
+                "schema.json",
+                "--namespace",
+                "Game",
+            ],
+        );
+
+        assert_eq!(out, "Hero\n");
+        let hero = fs::read_to_string(out_dir.join("Hero.cs")).unwrap();
+        assert!(hero.contains("namespace Game"));
+        assert!(hero.contains("public sealed partial class Hero"));
+    }
+
+    #[test]
+    fn unknown_option() {
+        let args = ["gen", "csharp", "out", "--namspace", "Game"];
+        let error = run(args.iter().map(|arg| arg.to_string()), &mut Vec::new()).unwrap_err();
+        assert_eq!(error.to_string(), "unknown option `--namspace`");
+    }
+}
@@ -19,17 +19,29 @@ namespace Steit.Builtins {
         public None NoneVariant { get { return this.Variant as None; } }
         public Some SomeVariant { get { return this.Variant as Some; } }
 
-        public Maybe(Path path = null) {
+        public Maybe(Path path = null) : this(path, 0) { }
+
+        public Maybe(Path path, UInt32 tag) {
             StateFactory.ValidateType(typeof(T));
             this.Path = path ?? Path.Root;
-            this.Tag = 0;
-            this.Variant = new None(this.Path.GetNested(0));
+            this.Tag = tag;
+
+            switch (tag) {
+                case 0: this.Variant = new None(this, this.Path.GetNested(0)); break;
+                case 1: this.Variant = new Some(this, this.Path.GetNested(1)); break;
+                default: this.Variant = new None(this, this.Path.GetNested(0)); break;
+            }
         }
 
+        public static Maybe NewNone(Path path = null) { return new Maybe(path, 0); }
+        public static Maybe NewSome(Path path = null) { return new Maybe(path, 1); }
+
         public static event EventHandler<VariantUpdateEventArgs<Maybe<T>>> OnUpdate;
+        public static event EventHandler<VariantFieldUpdateEventArgs<Maybe<T>>> OnFieldUpdate;
 
         public static void ClearUpdateHandlers() {
             OnUpdate = null;
+            OnFieldUpdate = null;
         }
 
         public static Maybe<T> Deserialize(IReader reader, Path path = null) {
@@ -52,8 +64,8 @@ namespace Steit.Builtins {
 
         public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
             switch (tag) {
-                case 0: this.UpdateAndNotify(0, None.Deserialize(reader, this.Path.GetNested(0)), shouldNotify); break;
-                case 1: this.UpdateAndNotify(1, Some.Deserialize(reader, this.Path.GetNested(1)), shouldNotify); break;
+                case 0: this.UpdateAndNotify(0, None.Deserialize(reader, this, this.Path.GetNested(0)), shouldNotify); break;
+                case 1: this.UpdateAndNotify(1, Some.Deserialize(reader, this, this.Path.GetNested(1)), shouldNotify); break;
                 default: reader.SkipToEnd(); break;
             }
         }
@@ -72,19 +84,26 @@ namespace Steit.Builtins {
             this.Variant = newVariant;
         }
 
+        internal void NotifyFieldUpdate(UInt32 fieldTag, Object newValue, Object oldValue, IState variant) {
+            var args = new VariantFieldUpdateEventArgs<Maybe<T>>(this.Tag, fieldTag, newValue, oldValue, variant, this);
+            Maybe<T>.OnFieldUpdate?.Invoke(this, args);
+        }
+
         // Variant (0): None
 
         public sealed partial class None : IState {
             public Path Path { get; }
+            public Maybe<T> Container { get; }
 
-            internal None(Path path = null) {
+            internal None(Maybe<T> container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
             }
 
             public static void ClearUpdateHandlers() { }
 
-            internal static None Deserialize(IReader reader, Path path = null) {
-                var none = new None(path);
+            internal static None Deserialize(IReader reader, Maybe<T> container, Path path = null) {
+                var none = new None(container, path);
                 none.Replace(reader, shouldNotify: false);
                 return none;
             }
@@ -121,6 +140,7 @@ namespace Steit.Builtins {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, None>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -131,10 +151,12 @@ namespace Steit.Builtins {
 
         public sealed partial class Some : IState {
             public Path Path { get; }
+            public Maybe<T> Container { get; }
             public T F0 { get; private set; }
 
-            internal Some(Path path = null) {
+            internal Some(Maybe<T> container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.F0 = StateFactory.Construct<T>(this.Path.GetNested(0));
             }
 
@@ -148,8 +170,8 @@ namespace Steit.Builtins {
                 OnF0Update = null;
             }
 
-            internal static Some Deserialize(IReader reader, Path path = null) {
-                var some = new Some(path);
+            internal static Some Deserialize(IReader reader, Maybe<T> container, Path path = null) {
+                var some = new Some(container, path);
                 some.Replace(reader, shouldNotify: false);
                 return some;
             }
@@ -189,6 +211,7 @@ namespace Steit.Builtins {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, Some>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
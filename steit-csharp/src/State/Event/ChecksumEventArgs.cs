@@ -0,0 +1,13 @@
+using System;
+
+namespace Steit.State.Event {
+    public sealed class ChecksumEventArgs : EventArgs {
+        public IState State { get; }
+        public UInt64 Hash { get; }
+
+        public ChecksumEventArgs(IState state, UInt64 hash) {
+            this.State = state;
+            this.Hash = hash;
+        }
+    }
+}
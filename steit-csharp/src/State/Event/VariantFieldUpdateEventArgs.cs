@@ -0,0 +1,32 @@
+using System;
+
+namespace Steit.State.Event {
+    public sealed class VariantFieldUpdateEventArgs<TContainer> : EventArgs where TContainer : IEnumState {
+        public UInt32 VariantTag { get; }
+        public UInt32 FieldTag { get; }
+
+        public Object NewValue { get; }
+        public Object OldValue { get; }
+
+        public IState Variant { get; }
+        public TContainer Container { get; }
+
+        public VariantFieldUpdateEventArgs(
+            UInt32 variantTag,
+            UInt32 fieldTag,
+            Object newValue,
+            Object oldValue,
+            IState variant,
+            TContainer container
+        ) {
+            this.VariantTag = variantTag;
+            this.FieldTag = fieldTag;
+
+            this.NewValue = newValue;
+            this.OldValue = oldValue;
+
+            this.Variant = variant;
+            this.Container = container;
+        }
+    }
+}
@@ -12,6 +12,7 @@ namespace Steit.State {
         public const UInt32 ListPushTag = 8;
         public const UInt32 ListPopTag = 9;
         public const UInt32 MapRemoveTag = 12;
+        public const UInt32 ChecksumTag = 16;
 
         public Path Path { get; }
 
@@ -22,17 +23,39 @@ namespace Steit.State {
         public ListPush ListPushVariant { get { return this.Variant as ListPush; } }
         public ListPop ListPopVariant { get { return this.Variant as ListPop; } }
         public MapRemove MapRemoveVariant { get { return this.Variant as MapRemove; } }
+        /// <summary>
+        /// Carries the hash of the state at `path` when logged, for replicas to check theirs against.
+        /// </summary>
+        public Checksum ChecksumVariant { get { return this.Variant as Checksum; } }
 
-        public LogEntry(Path path = null) {
+        public LogEntry(Path path = null) : this(path, 0) { }
+
+        public LogEntry(Path path, UInt32 tag) {
             this.Path = path ?? Path.Root;
-            this.Tag = 0;
-            this.Variant = new Update(this.Path.GetNested(0));
+            this.Tag = tag;
+
+            switch (tag) {
+                case 0: this.Variant = new Update(this, this.Path.GetNested(0)); break;
+                case 8: this.Variant = new ListPush(this, this.Path.GetNested(8)); break;
+                case 9: this.Variant = new ListPop(this, this.Path.GetNested(9)); break;
+                case 12: this.Variant = new MapRemove(this, this.Path.GetNested(12)); break;
+                case 16: this.Variant = new Checksum(this, this.Path.GetNested(16)); break;
+                default: this.Variant = new Update(this, this.Path.GetNested(0)); break;
+            }
         }
 
+        public static LogEntry NewUpdate(Path path = null) { return new LogEntry(path, 0); }
+        public static LogEntry NewListPush(Path path = null) { return new LogEntry(path, 8); }
+        public static LogEntry NewListPop(Path path = null) { return new LogEntry(path, 9); }
+        public static LogEntry NewMapRemove(Path path = null) { return new LogEntry(path, 12); }
+        public static LogEntry NewChecksum(Path path = null) { return new LogEntry(path, 16); }
+
         public static event EventHandler<VariantUpdateEventArgs<LogEntry>> OnUpdate;
+        public static event EventHandler<VariantFieldUpdateEventArgs<LogEntry>> OnFieldUpdate;
 
         public static void ClearUpdateHandlers() {
             OnUpdate = null;
+            OnFieldUpdate = null;
         }
 
         public static LogEntry Deserialize(IReader reader, Path path = null) {
@@ -47,6 +70,7 @@ namespace Steit.State {
                 case 8: return WireType.Sized;
                 case 9: return WireType.Sized;
                 case 12: return WireType.Sized;
+                case 16: return WireType.Sized;
                 default: return null;
             }
         }
@@ -57,10 +81,11 @@ namespace Steit.State {
 
         public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
             switch (tag) {
-                case 0: this.UpdateAndNotify(0, Update.Deserialize(reader, this.Path.GetNested(0)), shouldNotify); break;
-                case 8: this.UpdateAndNotify(8, ListPush.Deserialize(reader, this.Path.GetNested(8)), shouldNotify); break;
-                case 9: this.UpdateAndNotify(9, ListPop.Deserialize(reader, this.Path.GetNested(9)), shouldNotify); break;
-                case 12: this.UpdateAndNotify(12, MapRemove.Deserialize(reader, this.Path.GetNested(12)), shouldNotify); break;
+                case 0: this.UpdateAndNotify(0, Update.Deserialize(reader, this, this.Path.GetNested(0)), shouldNotify); break;
+                case 8: this.UpdateAndNotify(8, ListPush.Deserialize(reader, this, this.Path.GetNested(8)), shouldNotify); break;
+                case 9: this.UpdateAndNotify(9, ListPop.Deserialize(reader, this, this.Path.GetNested(9)), shouldNotify); break;
+                case 12: this.UpdateAndNotify(12, MapRemove.Deserialize(reader, this, this.Path.GetNested(12)), shouldNotify); break;
+                case 16: this.UpdateAndNotify(16, Checksum.Deserialize(reader, this, this.Path.GetNested(16)), shouldNotify); break;
                 default: reader.SkipToEnd(); break;
             }
         }
@@ -79,16 +104,23 @@ namespace Steit.State {
             this.Variant = newVariant;
         }
 
+        internal void NotifyFieldUpdate(UInt32 fieldTag, Object newValue, Object oldValue, IState variant) {
+            var args = new VariantFieldUpdateEventArgs<LogEntry>(this.Tag, fieldTag, newValue, oldValue, variant, this);
+            LogEntry.OnFieldUpdate?.Invoke(this, args);
+        }
+
         // Variant (0): Update
 
         public sealed partial class Update : IState {
             public Path Path { get; }
+            public LogEntry Container { get; }
 
             public Vector<UInt32> FlattenPath { get; private set; }
             public Bytes Value { get; private set; }
 
-            internal Update(Path path = null) {
+            internal Update(LogEntry container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.FlattenPath = new Vector<UInt32>(this.Path.GetNested(0));
                 this.Value = new Bytes(this.Path.GetNested(1));
             }
@@ -104,8 +136,8 @@ namespace Steit.State {
                 OnValueUpdate = null;
             }
 
-            internal static Update Deserialize(IReader reader, Path path = null) {
-                var update = new Update(path);
+            internal static Update Deserialize(IReader reader, LogEntry container, Path path = null) {
+                var update = new Update(container, path);
                 update.Replace(reader, shouldNotify: false);
                 return update;
             }
@@ -148,6 +180,7 @@ namespace Steit.State {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, Update>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -158,12 +191,14 @@ namespace Steit.State {
 
         public sealed partial class ListPush : IState {
             public Path Path { get; }
+            public LogEntry Container { get; }
 
             public Vector<UInt32> FlattenPath { get; private set; }
             public Bytes Item { get; private set; }
 
-            internal ListPush(Path path = null) {
+            internal ListPush(LogEntry container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.FlattenPath = new Vector<UInt32>(this.Path.GetNested(0));
                 this.Item = new Bytes(this.Path.GetNested(1));
             }
@@ -179,8 +214,8 @@ namespace Steit.State {
                 OnItemUpdate = null;
             }
 
-            internal static ListPush Deserialize(IReader reader, Path path = null) {
-                var listPush = new ListPush(path);
+            internal static ListPush Deserialize(IReader reader, LogEntry container, Path path = null) {
+                var listPush = new ListPush(container, path);
                 listPush.Replace(reader, shouldNotify: false);
                 return listPush;
             }
@@ -223,6 +258,7 @@ namespace Steit.State {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, ListPush>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -233,10 +269,12 @@ namespace Steit.State {
 
         public sealed partial class ListPop : IState {
             public Path Path { get; }
+            public LogEntry Container { get; }
             public Vector<UInt32> FlattenPath { get; private set; }
 
-            internal ListPop(Path path = null) {
+            internal ListPop(LogEntry container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.FlattenPath = new Vector<UInt32>(this.Path.GetNested(0));
             }
 
@@ -250,8 +288,8 @@ namespace Steit.State {
                 OnFlattenPathUpdate = null;
             }
 
-            internal static ListPop Deserialize(IReader reader, Path path = null) {
-                var listPop = new ListPop(path);
+            internal static ListPop Deserialize(IReader reader, LogEntry container, Path path = null) {
+                var listPop = new ListPop(container, path);
                 listPop.Replace(reader, shouldNotify: false);
                 return listPop;
             }
@@ -291,6 +329,7 @@ namespace Steit.State {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, ListPop>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -301,12 +340,14 @@ namespace Steit.State {
 
         public sealed partial class MapRemove : IState {
             public Path Path { get; }
+            public LogEntry Container { get; }
 
             public Vector<UInt32> FlattenPath { get; private set; }
             public UInt32 Key { get; private set; }
 
-            internal MapRemove(Path path = null) {
+            internal MapRemove(LogEntry container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.FlattenPath = new Vector<UInt32>(this.Path.GetNested(0));
             }
 
@@ -321,8 +362,8 @@ namespace Steit.State {
                 OnKeyUpdate = null;
             }
 
-            internal static MapRemove Deserialize(IReader reader, Path path = null) {
-                var mapRemove = new MapRemove(path);
+            internal static MapRemove Deserialize(IReader reader, LogEntry container, Path path = null) {
+                var mapRemove = new MapRemove(container, path);
                 mapRemove.Replace(reader, shouldNotify: false);
                 return mapRemove;
             }
@@ -364,6 +405,86 @@ namespace Steit.State {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, MapRemove>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
+                }
+
+                return newValue;
+            }
+        }
+
+        // Variant (16): Checksum
+
+        /// <summary>
+        /// Carries the hash of the state at `path` when logged, for replicas to check theirs against.
+        /// </summary>
+        public sealed partial class Checksum : IState {
+            public Path Path { get; }
+            public LogEntry Container { get; }
+
+            public Vector<UInt32> FlattenPath { get; private set; }
+            public UInt64 Hash { get; private set; }
+
+            internal Checksum(LogEntry container, Path path = null) {
+                this.Path = path ?? Path.Root;
+                this.Container = container;
+                this.FlattenPath = new Vector<UInt32>(this.Path.GetNested(0));
+            }
+
+            public static event EventHandler<FieldUpdateEventArgs<Vector<UInt32>, Checksum>> OnFlattenPathUpdate;
+            public static event EventHandler<FieldUpdateEventArgs<UInt64, Checksum>> OnHashUpdate;
+
+            public static void ClearFlattenPathUpdateHandlers() { OnFlattenPathUpdate = null; }
+            public static void ClearHashUpdateHandlers() { OnHashUpdate = null; }
+
+            public static void ClearUpdateHandlers() {
+                OnFlattenPathUpdate = null;
+                OnHashUpdate = null;
+            }
+
+            internal static Checksum Deserialize(IReader reader, LogEntry container, Path path = null) {
+                var checksum = new Checksum(container, path);
+                checksum.Replace(reader, shouldNotify: false);
+                return checksum;
+            }
+
+            public WireType? GetWireType(UInt32 tag) {
+                switch (tag) {
+                    case 0: return WireType.Sized;
+                    case 1: return WireType.Varint;
+                    default: return null;
+                }
+            }
+
+            public IState GetNested(UInt32 tag) {
+                switch (tag) {
+                    case 0: return this.FlattenPath;
+                    default: return null;
+                }
+            }
+
+            public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+                switch (tag) {
+                    case 0: this.FlattenPath = this.MaybeNotify(0, Vector<UInt32>.Deserialize(reader, this.Path.GetNested(0)), this.FlattenPath, OnFlattenPathUpdate, shouldNotify); break;
+                    case 1: this.Hash = this.MaybeNotify(1, reader.ReadUInt64(), this.Hash, OnHashUpdate, shouldNotify); break;
+                    default: reader.SkipField(wireType); break;
+                }
+            }
+
+            public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+            public void ReplayListPop() { throw new NotSupportedException(); }
+            public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+            private TValue MaybeNotify<TValue>(
+                UInt32 tag,
+                TValue newValue,
+                TValue oldValue,
+                EventHandler<FieldUpdateEventArgs<TValue, Checksum>> handler,
+                bool shouldNotify
+            ) {
+                if (shouldNotify) {
+                    var args = new FieldUpdateEventArgs<TValue, Checksum>(tag, newValue, oldValue, this);
+                    handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -28,9 +28,10 @@ namespace Steit.State {
             IsSized["System.String"] = true;
         }
 
+        // Enums generated for `repr = "varint"` are read as their variant tags, like primitives.
         public static bool IsPrimitiveType(Type type) {
-            // return Deserializers[type.FullName!] != null;
-            return Deserializers[type.FullName] != null;
+            // return type.IsEnum || Deserializers[type.FullName!] != null;
+            return type.IsEnum || Deserializers[type.FullName] != null;
         }
 
         public static bool IsStateType(Type type) {
@@ -81,6 +82,10 @@ namespace Steit.State {
         }
 
         private static T DeserializePrimitive<T>(IReader reader) {
+            if (typeof(T).IsEnum) {
+                return (T) Enum.ToObject(typeof(T), reader.ReadUInt32());
+            }
+
             // var deserializer = Deserializers[typeof(T).FullName!];
             var deserializer = Deserializers[typeof(T).FullName];
 
@@ -3,9 +3,15 @@ using System.Collections.Generic;
 
 using Steit.Codec;
 using Steit.Collections;
+using Steit.State.Event;
 
 namespace Steit.State {
     public static class StateReplayer {
+        // States can't be encoded here to hash them, so checksums aren't checked, and desyncs
+        // aren't detected, unless a handler does it. See the README.
+        // `State` is `null` when the path doesn't lead anywhere, which means a desync as well.
+        public static event EventHandler<ChecksumEventArgs> OnChecksum;
+
         public static void Replay<T>(ref T root, IReader reader) where T : IState {
             while (!reader.EndOfStream()) {
                 var entry = LogEntry.Deserialize(reader.GetNested());
@@ -31,6 +37,13 @@ namespace Steit.State {
 
             var container = root.GetNested(path);
 
+            if (entry.Tag == LogEntry.ChecksumTag) {
+                // var hash = entry.ChecksumVariant!.Hash;
+                var hash = entry.ChecksumVariant.Hash;
+                OnChecksum?.Invoke(null, new ChecksumEventArgs(container, hash));
+                return;
+            }
+
             if (container == null) {
                 return;
             }
@@ -78,6 +91,8 @@ namespace Steit.State {
                 case LogEntry.ListPopTag: return entry.ListPopVariant.FlattenPath;
                 // case LogEntry.MapRemoveTag: return entry.MapRemoveVariant!.FlattenPath;
                 case LogEntry.MapRemoveTag: return entry.MapRemoveVariant.FlattenPath;
+                // case LogEntry.ChecksumTag: return entry.ChecksumVariant!.FlattenPath;
+                case LogEntry.ChecksumTag: return entry.ChecksumVariant.FlattenPath;
                 default: throw new InvalidOperationException(String.Format("Unknown log entry tag {0}", entry.Tag));
             }
         }
@@ -25,16 +25,34 @@ namespace Just.To.Test {
         public Attack AttackVariant { get { return this.Variant as Attack; } }
         public Skill SkillVariant { get { return this.Variant as Skill; } }
 
-        public Action(Path path = null) {
+        public Action(Path path = null) : this(path, 0) { }
+
+        public Action(Path path, UInt32 tag) {
             this.Path = path ?? Path.Root;
-            this.Tag = 0;
-            this.Variant = new Raw(this.Path.GetNested(0));
+            this.Tag = tag;
+
+            switch (tag) {
+                case 0: this.Variant = new Raw(this, this.Path.GetNested(0)); break;
+                case 1: this.Variant = new CardDraw(this, this.Path.GetNested(1)); break;
+                case 2: this.Variant = new CardDiscard(this, this.Path.GetNested(2)); break;
+                case 3: this.Variant = new Attack(this, this.Path.GetNested(3)); break;
+                case 4: this.Variant = new Skill(this, this.Path.GetNested(4)); break;
+                default: this.Variant = new Raw(this, this.Path.GetNested(0)); break;
+            }
         }
 
+        public static Action NewRaw(Path path = null) { return new Action(path, 0); }
+        public static Action NewCardDraw(Path path = null) { return new Action(path, 1); }
+        public static Action NewCardDiscard(Path path = null) { return new Action(path, 2); }
+        public static Action NewAttack(Path path = null) { return new Action(path, 3); }
+        public static Action NewSkill(Path path = null) { return new Action(path, 4); }
+
         public static event EventHandler<VariantUpdateEventArgs<Action>> OnUpdate;
+        public static event EventHandler<VariantFieldUpdateEventArgs<Action>> OnFieldUpdate;
 
         public static void ClearUpdateHandlers() {
             OnUpdate = null;
+            OnFieldUpdate = null;
         }
 
         public static Action Deserialize(IReader reader, Path path = null) {
@@ -60,11 +78,11 @@ namespace Just.To.Test {
 
         public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
             switch (tag) {
-                case 0: this.UpdateAndNotify(0, Raw.Deserialize(reader, this.Path.GetNested(0)), shouldNotify); break;
-                case 1: this.UpdateAndNotify(1, CardDraw.Deserialize(reader, this.Path.GetNested(1)), shouldNotify); break;
-                case 2: this.UpdateAndNotify(2, CardDiscard.Deserialize(reader, this.Path.GetNested(2)), shouldNotify); break;
-                case 3: this.UpdateAndNotify(3, Attack.Deserialize(reader, this.Path.GetNested(3)), shouldNotify); break;
-                case 4: this.UpdateAndNotify(4, Skill.Deserialize(reader, this.Path.GetNested(4)), shouldNotify); break;
+                case 0: this.UpdateAndNotify(0, Raw.Deserialize(reader, this, this.Path.GetNested(0)), shouldNotify); break;
+                case 1: this.UpdateAndNotify(1, CardDraw.Deserialize(reader, this, this.Path.GetNested(1)), shouldNotify); break;
+                case 2: this.UpdateAndNotify(2, CardDiscard.Deserialize(reader, this, this.Path.GetNested(2)), shouldNotify); break;
+                case 3: this.UpdateAndNotify(3, Attack.Deserialize(reader, this, this.Path.GetNested(3)), shouldNotify); break;
+                case 4: this.UpdateAndNotify(4, Skill.Deserialize(reader, this, this.Path.GetNested(4)), shouldNotify); break;
                 default: reader.SkipToEnd(); break;
             }
         }
@@ -83,19 +101,26 @@ namespace Just.To.Test {
             this.Variant = newVariant;
         }
 
+        internal void NotifyFieldUpdate(UInt32 fieldTag, Object newValue, Object oldValue, IState variant) {
+            var args = new VariantFieldUpdateEventArgs<Action>(this.Tag, fieldTag, newValue, oldValue, variant, this);
+            Action.OnFieldUpdate?.Invoke(this, args);
+        }
+
         // Variant (0): Raw
 
         public sealed partial class Raw : IState {
             public Path Path { get; }
+            public Action Container { get; }
 
-            internal Raw(Path path = null) {
+            internal Raw(Action container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
             }
 
             public static void ClearUpdateHandlers() { }
 
-            internal static Raw Deserialize(IReader reader, Path path = null) {
-                var raw = new Raw(path);
+            internal static Raw Deserialize(IReader reader, Action container, Path path = null) {
+                var raw = new Raw(container, path);
                 raw.Replace(reader, shouldNotify: false);
                 return raw;
             }
@@ -132,6 +157,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, Raw>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -142,13 +168,15 @@ namespace Just.To.Test {
 
         public sealed partial class CardDraw : IState {
             public Path Path { get; }
+            public Action Container { get; }
 
             public UInt16 PlayerIndex { get; private set; }
             public Vector<Action> Draw { get; private set; }
             public Vector<Action> PostDraw { get; private set; }
 
-            internal CardDraw(Path path = null) {
+            internal CardDraw(Action container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.Draw = new Vector<Action>(this.Path.GetNested(1));
                 this.PostDraw = new Vector<Action>(this.Path.GetNested(2));
             }
@@ -167,8 +195,8 @@ namespace Just.To.Test {
                 OnPostDrawUpdate = null;
             }
 
-            internal static CardDraw Deserialize(IReader reader, Path path = null) {
-                var cardDraw = new CardDraw(path);
+            internal static CardDraw Deserialize(IReader reader, Action container, Path path = null) {
+                var cardDraw = new CardDraw(container, path);
                 cardDraw.Replace(reader, shouldNotify: false);
                 return cardDraw;
             }
@@ -213,6 +241,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, CardDraw>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -223,15 +252,17 @@ namespace Just.To.Test {
 
         public sealed partial class CardDiscard : IState {
             public Path Path { get; }
+            public Action Container { get; }
 
-            internal CardDiscard(Path path = null) {
+            internal CardDiscard(Action container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
             }
 
             public static void ClearUpdateHandlers() { }
 
-            internal static CardDiscard Deserialize(IReader reader, Path path = null) {
-                var cardDiscard = new CardDiscard(path);
+            internal static CardDiscard Deserialize(IReader reader, Action container, Path path = null) {
+                var cardDiscard = new CardDiscard(container, path);
                 cardDiscard.Replace(reader, shouldNotify: false);
                 return cardDiscard;
             }
@@ -268,6 +299,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, CardDiscard>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -278,6 +310,7 @@ namespace Just.To.Test {
 
         public sealed partial class Attack : IState {
             public Path Path { get; }
+            public Action Container { get; }
 
             public UInt16 AttackerIndex { get; private set; }
             public UInt32 CardId { get; private set; }
@@ -285,8 +318,9 @@ namespace Just.To.Test {
             public ActionsOr<Vector<ActionsOr<Attack>>> Attacks { get; private set; }
             public Vector<Action> AfterAttacks { get; private set; }
 
-            internal Attack(Path path = null) {
+            internal Attack(Action container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.BeforeAttacks = new Vector<Action>(this.Path.GetNested(2));
                 this.Attacks = new ActionsOr<Vector<ActionsOr<Attack>>>(this.Path.GetNested(3));
                 this.AfterAttacks = new Vector<Action>(this.Path.GetNested(4));
@@ -312,8 +346,8 @@ namespace Just.To.Test {
                 OnAfterAttacksUpdate = null;
             }
 
-            internal static Attack Deserialize(IReader reader, Path path = null) {
-                var attack = new Attack(path);
+            internal static Attack Deserialize(IReader reader, Action container, Path path = null) {
+                var attack = new Attack(container, path);
                 attack.Replace(reader, shouldNotify: false);
                 return attack;
             }
@@ -363,6 +397,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, Attack>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -373,6 +408,7 @@ namespace Just.To.Test {
 
         public sealed partial class Skill : IState {
             public Path Path { get; }
+            public Action Container { get; }
 
             public UInt16 CasterIndex { get; private set; }
             public UInt32 CardId { get; private set; }
@@ -380,8 +416,9 @@ namespace Just.To.Test {
             public ActionsOr<Vector<ActionsOr<Skill>>> Skills { get; private set; }
             public Vector<Action> AfterSkills { get; private set; }
 
-            internal Skill(Path path = null) {
+            internal Skill(Action container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.BeforeSkills = new Vector<Action>(this.Path.GetNested(2));
                 this.Skills = new ActionsOr<Vector<ActionsOr<Skill>>>(this.Path.GetNested(3));
                 this.AfterSkills = new Vector<Action>(this.Path.GetNested(4));
@@ -407,8 +444,8 @@ namespace Just.To.Test {
                 OnAfterSkillsUpdate = null;
             }
 
-            internal static Skill Deserialize(IReader reader, Path path = null) {
-                var skill = new Skill(path);
+            internal static Skill Deserialize(IReader reader, Action container, Path path = null) {
+                var skill = new Skill(container, path);
                 skill.Replace(reader, shouldNotify: false);
                 return skill;
             }
@@ -458,6 +495,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, Skill>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
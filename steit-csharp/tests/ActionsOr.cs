@@ -19,17 +19,29 @@ namespace Just.To.Test {
         public Actions ActionsVariant { get { return this.Variant as Actions; } }
         public Value ValueVariant { get { return this.Variant as Value; } }
 
-        public ActionsOr(Path path = null) {
+        public ActionsOr(Path path = null) : this(path, 0) { }
+
+        public ActionsOr(Path path, UInt32 tag) {
             StateFactory.ValidateType(typeof(T));
             this.Path = path ?? Path.Root;
-            this.Tag = 0;
-            this.Variant = new Actions(this.Path.GetNested(0));
+            this.Tag = tag;
+
+            switch (tag) {
+                case 0: this.Variant = new Actions(this, this.Path.GetNested(0)); break;
+                case 1: this.Variant = new Value(this, this.Path.GetNested(1)); break;
+                default: this.Variant = new Actions(this, this.Path.GetNested(0)); break;
+            }
         }
 
+        public static ActionsOr NewActions(Path path = null) { return new ActionsOr(path, 0); }
+        public static ActionsOr NewValue(Path path = null) { return new ActionsOr(path, 1); }
+
         public static event EventHandler<VariantUpdateEventArgs<ActionsOr<T>>> OnUpdate;
+        public static event EventHandler<VariantFieldUpdateEventArgs<ActionsOr<T>>> OnFieldUpdate;
 
         public static void ClearUpdateHandlers() {
             OnUpdate = null;
+            OnFieldUpdate = null;
         }
 
         public static ActionsOr<T> Deserialize(IReader reader, Path path = null) {
@@ -52,8 +64,8 @@ namespace Just.To.Test {
 
         public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
             switch (tag) {
-                case 0: this.UpdateAndNotify(0, Actions.Deserialize(reader, this.Path.GetNested(0)), shouldNotify); break;
-                case 1: this.UpdateAndNotify(1, Value.Deserialize(reader, this.Path.GetNested(1)), shouldNotify); break;
+                case 0: this.UpdateAndNotify(0, Actions.Deserialize(reader, this, this.Path.GetNested(0)), shouldNotify); break;
+                case 1: this.UpdateAndNotify(1, Value.Deserialize(reader, this, this.Path.GetNested(1)), shouldNotify); break;
                 default: reader.SkipToEnd(); break;
             }
         }
@@ -72,14 +84,21 @@ namespace Just.To.Test {
             this.Variant = newVariant;
         }
 
+        internal void NotifyFieldUpdate(UInt32 fieldTag, Object newValue, Object oldValue, IState variant) {
+            var args = new VariantFieldUpdateEventArgs<ActionsOr<T>>(this.Tag, fieldTag, newValue, oldValue, variant, this);
+            ActionsOr<T>.OnFieldUpdate?.Invoke(this, args);
+        }
+
         // Variant (0): Actions
 
         public sealed partial class Actions : IState {
             public Path Path { get; }
+            public ActionsOr<T> Container { get; }
             public Vector<Action> F0 { get; private set; }
 
-            internal Actions(Path path = null) {
+            internal Actions(ActionsOr<T> container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.F0 = new Vector<Action>(this.Path.GetNested(0));
             }
 
@@ -93,8 +112,8 @@ namespace Just.To.Test {
                 OnF0Update = null;
             }
 
-            internal static Actions Deserialize(IReader reader, Path path = null) {
-                var actions = new Actions(path);
+            internal static Actions Deserialize(IReader reader, ActionsOr<T> container, Path path = null) {
+                var actions = new Actions(container, path);
                 actions.Replace(reader, shouldNotify: false);
                 return actions;
             }
@@ -134,6 +153,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, Actions>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -144,10 +164,12 @@ namespace Just.To.Test {
 
         public sealed partial class Value : IState {
             public Path Path { get; }
+            public ActionsOr<T> Container { get; }
             public T F0 { get; private set; }
 
-            internal Value(Path path = null) {
+            internal Value(ActionsOr<T> container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.F0 = StateFactory.Construct<T>(this.Path.GetNested(0));
             }
 
@@ -161,8 +183,8 @@ namespace Just.To.Test {
                 OnF0Update = null;
             }
 
-            internal static Value Deserialize(IReader reader, Path path = null) {
-                var value = new Value(path);
+            internal static Value Deserialize(IReader reader, ActionsOr<T> container, Path path = null) {
+                var value = new Value(container, path);
                 value.Replace(reader, shouldNotify: false);
                 return value;
             }
@@ -202,6 +224,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, Value>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
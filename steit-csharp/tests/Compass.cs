@@ -0,0 +1,88 @@
+using System;
+
+using Steit.Builtins;
+using Steit.Codec;
+using Steit.Collections;
+using Steit.State;
+using Steit.State.Event;
+
+namespace Just.To.Test {
+    public sealed partial class Compass : IState {
+        public Path Path { get; }
+
+        public Direction Facing { get; private set; }
+        public StateList<Direction> Route { get; private set; }
+        public StateMap<Direction> Marks { get; private set; }
+
+        public Compass(Path path = null) {
+            this.Path = path ?? Path.Root;
+            this.Route = new StateList<Direction>(this.Path.GetNested(1));
+            this.Marks = new StateMap<Direction>(this.Path.GetNested(2));
+        }
+
+        public static event EventHandler<FieldUpdateEventArgs<Direction, Compass>> OnFacingUpdate;
+        public static event EventHandler<FieldUpdateEventArgs<StateList<Direction>, Compass>> OnRouteUpdate;
+        public static event EventHandler<FieldUpdateEventArgs<StateMap<Direction>, Compass>> OnMarksUpdate;
+
+        public static void ClearFacingUpdateHandlers() { OnFacingUpdate = null; }
+        public static void ClearRouteUpdateHandlers() { OnRouteUpdate = null; }
+        public static void ClearMarksUpdateHandlers() { OnMarksUpdate = null; }
+
+        public static void ClearUpdateHandlers() {
+            OnFacingUpdate = null;
+            OnRouteUpdate = null;
+            OnMarksUpdate = null;
+        }
+
+        public static Compass Deserialize(IReader reader, Path path = null) {
+            var compass = new Compass(path);
+            compass.Replace(reader, shouldNotify: false);
+            return compass;
+        }
+
+        public WireType? GetWireType(UInt32 tag) {
+            switch (tag) {
+                case 0: return WireType.Varint;
+                case 1: return WireType.Sized;
+                case 2: return WireType.Sized;
+                default: return null;
+            }
+        }
+
+        public IState GetNested(UInt32 tag) {
+            switch (tag) {
+                case 1: return this.Route;
+                case 2: return this.Marks;
+                default: return null;
+            }
+        }
+
+        public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
+            switch (tag) {
+                case 0: this.Facing = this.MaybeNotify(0, reader.ReadDirection(), this.Facing, OnFacingUpdate, shouldNotify); break;
+                case 1: this.Route = this.MaybeNotify(1, StateList<Direction>.Deserialize(reader, this.Path.GetNested(1)), this.Route, OnRouteUpdate, shouldNotify); break;
+                case 2: this.Marks = this.MaybeNotify(2, StateMap<Direction>.Deserialize(reader, this.Path.GetNested(2)), this.Marks, OnMarksUpdate, shouldNotify); break;
+                default: reader.SkipField(wireType); break;
+            }
+        }
+
+        public void ReplayListPush(IReader reader) { throw new NotSupportedException(); }
+        public void ReplayListPop() { throw new NotSupportedException(); }
+        public void ReplayMapRemove(UInt32 key) { throw new NotSupportedException(); }
+
+        private TValue MaybeNotify<TValue>(
+            UInt32 tag,
+            TValue newValue,
+            TValue oldValue,
+            EventHandler<FieldUpdateEventArgs<TValue, Compass>> handler,
+            bool shouldNotify
+        ) {
+            if (shouldNotify) {
+                var args = new FieldUpdateEventArgs<TValue, Compass>(tag, newValue, oldValue, this);
+                handler?.Invoke(this, args);
+            }
+
+            return newValue;
+        }
+    }
+}
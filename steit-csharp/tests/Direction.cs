@@ -0,0 +1,19 @@
+using System;
+
+using Steit.Builtins;
+using Steit.Codec;
+using Steit.Collections;
+using Steit.State;
+using Steit.State.Event;
+
+namespace Just.To.Test {
+    public enum Direction : UInt32 {
+        North = 0,
+        East = 1,
+        South = 2,
+    }
+
+    public static class DirectionReader {
+        public static Direction ReadDirection(this IReader reader) { return (Direction) reader.ReadUInt32(); }
+    }
+}
@@ -19,16 +19,28 @@ namespace Just.To.Test {
         public FirstCase FirstCaseVariant { get { return this.Variant as FirstCase; } }
         public SecondCase SecondCaseVariant { get { return this.Variant as SecondCase; } }
 
-        public Multicase(Path path = null) {
+        public Multicase(Path path = null) : this(path, 0) { }
+
+        public Multicase(Path path, UInt32 tag) {
             this.Path = path ?? Path.Root;
-            this.Tag = 0;
-            this.Variant = new FirstCase(this.Path.GetNested(0));
+            this.Tag = tag;
+
+            switch (tag) {
+                case 0: this.Variant = new FirstCase(this, this.Path.GetNested(0)); break;
+                case 1: this.Variant = new SecondCase(this, this.Path.GetNested(1)); break;
+                default: this.Variant = new FirstCase(this, this.Path.GetNested(0)); break;
+            }
         }
 
+        public static Multicase NewFirstCase(Path path = null) { return new Multicase(path, 0); }
+        public static Multicase NewSecondCase(Path path = null) { return new Multicase(path, 1); }
+
         public static event EventHandler<VariantUpdateEventArgs<Multicase>> OnUpdate;
+        public static event EventHandler<VariantFieldUpdateEventArgs<Multicase>> OnFieldUpdate;
 
         public static void ClearUpdateHandlers() {
             OnUpdate = null;
+            OnFieldUpdate = null;
         }
 
         public static Multicase Deserialize(IReader reader, Path path = null) {
@@ -51,8 +63,8 @@ namespace Just.To.Test {
 
         public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
             switch (tag) {
-                case 0: this.UpdateAndNotify(0, FirstCase.Deserialize(reader, this.Path.GetNested(0)), shouldNotify); break;
-                case 1: this.UpdateAndNotify(1, SecondCase.Deserialize(reader, this.Path.GetNested(1)), shouldNotify); break;
+                case 0: this.UpdateAndNotify(0, FirstCase.Deserialize(reader, this, this.Path.GetNested(0)), shouldNotify); break;
+                case 1: this.UpdateAndNotify(1, SecondCase.Deserialize(reader, this, this.Path.GetNested(1)), shouldNotify); break;
                 default: reader.SkipToEnd(); break;
             }
         }
@@ -71,16 +83,23 @@ namespace Just.To.Test {
             this.Variant = newVariant;
         }
 
+        internal void NotifyFieldUpdate(UInt32 fieldTag, Object newValue, Object oldValue, IState variant) {
+            var args = new VariantFieldUpdateEventArgs<Multicase>(this.Tag, fieldTag, newValue, oldValue, variant, this);
+            Multicase.OnFieldUpdate?.Invoke(this, args);
+        }
+
         // Variant (0): FirstCase
 
         public sealed partial class FirstCase : IState {
             public Path Path { get; }
+            public Multicase Container { get; }
 
             public Int32 Counter { get; private set; }
             public Boolean Enabled { get; private set; }
 
-            internal FirstCase(Path path = null) {
+            internal FirstCase(Multicase container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
             }
 
             public static event EventHandler<FieldUpdateEventArgs<Int32, FirstCase>> OnCounterUpdate;
@@ -94,8 +113,8 @@ namespace Just.To.Test {
                 OnEnabledUpdate = null;
             }
 
-            internal static FirstCase Deserialize(IReader reader, Path path = null) {
-                var firstCase = new FirstCase(path);
+            internal static FirstCase Deserialize(IReader reader, Multicase container, Path path = null) {
+                var firstCase = new FirstCase(container, path);
                 firstCase.Replace(reader, shouldNotify: false);
                 return firstCase;
             }
@@ -136,6 +155,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, FirstCase>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -146,12 +166,14 @@ namespace Just.To.Test {
 
         public sealed partial class SecondCase : IState {
             public Path Path { get; }
+            public Multicase Container { get; }
 
             public Int32 Counter { get; private set; }
             public Boolean Enabled { get; private set; }
 
-            internal SecondCase(Path path = null) {
+            internal SecondCase(Multicase container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
             }
 
             public static event EventHandler<FieldUpdateEventArgs<Int32, SecondCase>> OnCounterUpdate;
@@ -165,8 +187,8 @@ namespace Just.To.Test {
                 OnEnabledUpdate = null;
             }
 
-            internal static SecondCase Deserialize(IReader reader, Path path = null) {
-                var secondCase = new SecondCase(path);
+            internal static SecondCase Deserialize(IReader reader, Multicase container, Path path = null) {
+                var secondCase = new SecondCase(container, path);
                 secondCase.Replace(reader, shouldNotify: false);
                 return secondCase;
             }
@@ -207,6 +229,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, SecondCase>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
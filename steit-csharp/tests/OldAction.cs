@@ -19,16 +19,28 @@ namespace Just.To.Test {
         public Raw RawVariant { get { return this.Variant as Raw; } }
         public Attack AttackVariant { get { return this.Variant as Attack; } }
 
-        public OldAction(Path path = null) {
+        public OldAction(Path path = null) : this(path, 0) { }
+
+        public OldAction(Path path, UInt32 tag) {
             this.Path = path ?? Path.Root;
-            this.Tag = 0;
-            this.Variant = new Raw(this.Path.GetNested(0));
+            this.Tag = tag;
+
+            switch (tag) {
+                case 0: this.Variant = new Raw(this, this.Path.GetNested(0)); break;
+                case 1: this.Variant = new Attack(this, this.Path.GetNested(1)); break;
+                default: this.Variant = new Raw(this, this.Path.GetNested(0)); break;
+            }
         }
 
+        public static OldAction NewRaw(Path path = null) { return new OldAction(path, 0); }
+        public static OldAction NewAttack(Path path = null) { return new OldAction(path, 1); }
+
         public static event EventHandler<VariantUpdateEventArgs<OldAction>> OnUpdate;
+        public static event EventHandler<VariantFieldUpdateEventArgs<OldAction>> OnFieldUpdate;
 
         public static void ClearUpdateHandlers() {
             OnUpdate = null;
+            OnFieldUpdate = null;
         }
 
         public static OldAction Deserialize(IReader reader, Path path = null) {
@@ -51,8 +63,8 @@ namespace Just.To.Test {
 
         public void ReplaceAt(UInt32 tag, WireType wireType, IReader reader, bool shouldNotify) {
             switch (tag) {
-                case 0: this.UpdateAndNotify(0, Raw.Deserialize(reader, this.Path.GetNested(0)), shouldNotify); break;
-                case 1: this.UpdateAndNotify(1, Attack.Deserialize(reader, this.Path.GetNested(1)), shouldNotify); break;
+                case 0: this.UpdateAndNotify(0, Raw.Deserialize(reader, this, this.Path.GetNested(0)), shouldNotify); break;
+                case 1: this.UpdateAndNotify(1, Attack.Deserialize(reader, this, this.Path.GetNested(1)), shouldNotify); break;
                 default: reader.SkipToEnd(); break;
             }
         }
@@ -71,14 +83,21 @@ namespace Just.To.Test {
             this.Variant = newVariant;
         }
 
+        internal void NotifyFieldUpdate(UInt32 fieldTag, Object newValue, Object oldValue, IState variant) {
+            var args = new VariantFieldUpdateEventArgs<OldAction>(this.Tag, fieldTag, newValue, oldValue, variant, this);
+            OldAction.OnFieldUpdate?.Invoke(this, args);
+        }
+
         // Variant (0): Raw
 
         public sealed partial class Raw : IState {
             public Path Path { get; }
+            public OldAction Container { get; }
             public StateList<Byte> LogEntries { get; private set; }
 
-            internal Raw(Path path = null) {
+            internal Raw(OldAction container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.LogEntries = new StateList<Byte>(this.Path.GetNested(0));
             }
 
@@ -92,8 +111,8 @@ namespace Just.To.Test {
                 OnLogEntriesUpdate = null;
             }
 
-            internal static Raw Deserialize(IReader reader, Path path = null) {
-                var raw = new Raw(path);
+            internal static Raw Deserialize(IReader reader, OldAction container, Path path = null) {
+                var raw = new Raw(container, path);
                 raw.Replace(reader, shouldNotify: false);
                 return raw;
             }
@@ -133,6 +152,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, Raw>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -143,13 +163,15 @@ namespace Just.To.Test {
 
         public sealed partial class Attack : IState {
             public Path Path { get; }
+            public OldAction Container { get; }
 
             public Byte Attacker { get; private set; }
             public Byte Defender { get; private set; }
             public StateList<OldHit> Hits { get; private set; }
 
-            internal Attack(Path path = null) {
+            internal Attack(OldAction container, Path path = null) {
                 this.Path = path ?? Path.Root;
+                this.Container = container;
                 this.Hits = new StateList<OldHit>(this.Path.GetNested(2));
             }
 
@@ -167,8 +189,8 @@ namespace Just.To.Test {
                 OnHitsUpdate = null;
             }
 
-            internal static Attack Deserialize(IReader reader, Path path = null) {
-                var attack = new Attack(path);
+            internal static Attack Deserialize(IReader reader, OldAction container, Path path = null) {
+                var attack = new Attack(container, path);
                 attack.Replace(reader, shouldNotify: false);
                 return attack;
             }
@@ -212,6 +234,7 @@ namespace Just.To.Test {
                 if (shouldNotify) {
                     var args = new FieldUpdateEventArgs<TValue, Attack>(tag, newValue, oldValue, this);
                     handler?.Invoke(this, args);
+                    this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);
                 }
 
                 return newValue;
@@ -216,6 +216,43 @@ namespace Just.To.Test {
                     17, 2, 1, 0, 10, 1, 0, 18, 1, 0, 26, 1, 0, 34, 1, 0, 40, 18,
             }));
 
+            var compass = Compass.Deserialize(new ByteReader(new byte[] {
+                // Facing: South.
+                0, 2,
+                // Route: East, South.
+                10, 2, 1, 2,
+                // Marks: 3 => East, 7 => North.
+                18, 4, 24, 1, 56, 0,
+            }));
+
+            Console.WriteLine("Facing: {0}", compass.Facing);
+            Console.WriteLine("Route: {0}", String.Join(", ", compass.Route));
+            Console.WriteLine("Marks: {0}", String.Join(", ", compass.Marks));
+
+            var list3 = new StateList<Direction>();
+
+            list3.OnPush += (sender, e) => {
+                Console.WriteLine("StateList<Direction>, add #{0}: {1}", e.Tag, e.Item);
+            };
+
+            StateReplayer.Replay<StateList<Direction>>(ref list3, new ByteReader(new byte[] {
+                4, 8, 10, 1, 1,
+                4, 8, 10, 1, 2,
+            }));
+
+            var map3 = new StateMap<Direction>();
+
+            map3.OnInsert += (sender, e) => {
+                Console.WriteLine("StateMap<Direction>, insert #{0}: {1}", e.Tag, e.Value);
+            };
+
+            StateReplayer.Replay<StateMap<Direction>>(ref map3, new ByteReader(new byte[] {
+                7, 0, 2, 1, 3, 10, 1, 1,
+                7, 0, 2, 1, 7, 10, 1, 0,
+            }));
+
+            Console.WriteLine("{0}", Option<Direction>.Deserialize(new ByteReader(new byte[] { 2 })));
+
             Console.WriteLine("{0}", StateFactory.Deserialize<String>(new ByteReader(new byte[] { 2, 51, 51 })));
             Console.WriteLine("{0}", StateFactory.Deserialize<String>(new ByteReader(new byte[] { 2, 207, 128 })));
 
@@ -104,6 +104,29 @@ impl Attribute<'_, String> {
     }
 }
 
+impl Attribute<'_, syn::Path> {
+    pub fn parse_path_str(&mut self, meta: &syn::MetaNameValue) -> bool {
+        self.parse_name_value(meta, |lit| match lit {
+            syn::Lit::Str(lit) => lit.parse().map_err(|_| "a path string"),
+            _ => Err("a path string"),
+        })
+    }
+}
+
+impl Attribute<'_, syn::Expr> {
+    /// Accepts a literal such as `100`, or a string to be parsed as an expression,
+    /// e.g. `"Vec::with_capacity(8)"`, since other expressions are not valid attribute syntax.
+    pub fn parse_expr(&mut self, meta: &syn::MetaNameValue) -> bool {
+        self.parse_name_value(meta, |lit| match lit {
+            syn::Lit::Str(lit) => lit.parse().map_err(|_| "a valid expression"),
+            lit => Ok(syn::Expr::Lit(syn::ExprLit {
+                attrs: Vec::new(),
+                lit: lit.clone(),
+            })),
+        })
+    }
+}
+
 pub struct VecAttribute<'a, T> {
     ctx: &'a Context,
     name: &'static str,
@@ -170,6 +193,78 @@ where
     }
 }
 
+impl VecAttribute<'_, (String, String)> {
+    /// Accepts `name(key = "value", …)`, keys being unique.
+    pub fn parse_str_pairs(&mut self, meta: &syn::MetaList) -> bool {
+        if !meta.path.is_ident(self.name) {
+            return false;
+        }
+
+        for nested in &meta.nested {
+            match nested {
+                syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(value),
+                    ..
+                })) => {
+                    let key = path.to_token_stream().to_string().replace(' ', "");
+
+                    if self.values.iter().any(|(existing, _)| *existing == key) {
+                        self.ctx
+                            .error(path, format!("duplicate `{}` key `{}`", self.name, key));
+                    } else {
+                        self.insert((key, value.value()));
+                    }
+                }
+
+                _ => self.ctx.error(
+                    nested,
+                    format!(
+                        "expected `{}` attribute to be a list of `key = \"value\"`",
+                        self.name
+                    ),
+                ),
+            }
+        }
+
+        true
+    }
+}
+
+/// Joins the doc comments among `attrs`, which are kept on the item itself.
+pub fn parse_doc(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+
+    for attr in attrs {
+        if !attr.path.is_ident("doc") {
+            continue;
+        }
+
+        if let Ok(syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(lit),
+            ..
+        })) = attr.parse_meta()
+        {
+            let line = lit.value();
+            // `/// Text` is passed as `#[doc = " Text"]`
+            lines.push(
+                line.strip_prefix(' ')
+                    .unwrap_or(&line)
+                    .trim_end()
+                    .to_string(),
+            );
+        }
+    }
+
+    let doc = lines.join("\n").trim().to_string();
+
+    if doc.is_empty() {
+        None
+    } else {
+        Some(doc)
+    }
+}
+
 pub trait AttributeParse {
     fn parse(
         self,
@@ -262,7 +357,7 @@ fn parse_meta(
 
         syn::NestedMeta::Lit(lit) => {
             if error_on_unknown {
-                ctx.error(&lit, "unexpected literal in steit attributes");
+                ctx.error(lit, "unexpected literal in steit attributes");
             }
         }
     }
@@ -2,13 +2,12 @@ use proc_macro2::TokenStream;
 use quote::ToTokens;
 
 use crate::{
-    attr::{Attribute, AttributeParse},
+    attr::{self, Attribute, AttributeParse},
     ctx::Context,
     r#impl::Implementer,
-    str_util,
 };
 
-use super::{r#enum::Enum, r#struct::Struct};
+use super::{r#enum::Enum, r#struct::Struct, transparent::Transparent, varint_enum::VarintEnum};
 
 pub type Result<T> = std::result::Result<T, ()>;
 
@@ -24,6 +23,11 @@ pub struct DeriveSetting {
     pub derives: syn::AttributeArgs,
 
     pub steit_owned: bool,
+    pub transparent: bool,
+    // Fields are private, read through getters and changed through setters and `Mut` handles
+    pub accessors: bool,
+    // Fieldless enums put on the wire as their variant tags, `#[steit(repr = "varint")]`
+    pub repr_varint: bool,
 
     pub has_size_cache: bool,
     pub has_runtime: bool,
@@ -32,10 +36,16 @@ pub struct DeriveSetting {
     pub derive_setters: bool,
     pub derive_wire_type: bool,
     pub derive_meta: bool,
+    // With the `serde` feature, alongside `Serialize` and `Deserialize`,
+    // unless opted out with `#[steit(serde = false)]`
+    pub derive_serde: bool,
 
     pub ctor_prefix: String,
     pub size_cache_renamed: Option<(String, TokenStream)>,
     pub runtime_renamed: Option<(String, TokenStream)>,
+
+    // Doc comments of the type, carried into its metadata
+    pub doc: Option<String>,
 }
 
 impl DeriveSetting {
@@ -74,13 +84,19 @@ impl DeriveSetting {
 
         // Attributes
 
+        let doc = attr::parse_doc(attrs);
+
         let mut steit_owned = Attribute::new(ctx, "steit_owned");
+        let mut transparent = Attribute::new(ctx, "transparent");
+        let mut accessors = Attribute::new(ctx, "accessors");
+        let mut repr = Attribute::new(ctx, "repr");
 
         let mut no_size_cache = Attribute::new(ctx, "no_size_cache");
 
         let mut derive_ctors = Attribute::new(ctx, "derive_ctors");
         let mut derive_setters = Attribute::new(ctx, "derive_setters");
         let mut no_meta = Attribute::new(ctx, "no_meta");
+        let mut serde = Attribute::new(ctx, "serde");
 
         let mut ctor_prefix = Attribute::new(ctx, "ctor_prefix");
         let mut size_cache_renamed = Attribute::new(ctx, "size_cache_renamed");
@@ -90,6 +106,14 @@ impl DeriveSetting {
             syn::Meta::Path(path) if steit_owned.parse_path(path) => true,
             syn::Meta::NameValue(meta) if steit_owned.parse_bool(meta) => true,
 
+            syn::Meta::Path(path) if transparent.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if transparent.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if accessors.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if accessors.parse_bool(meta) => true,
+
+            syn::Meta::NameValue(meta) if repr.parse_str(meta) => true,
+
             syn::Meta::Path(path) if no_size_cache.parse_path(path) => true,
             syn::Meta::NameValue(meta) if no_size_cache.parse_bool(meta) => true,
 
@@ -102,6 +126,9 @@ impl DeriveSetting {
             syn::Meta::Path(path) if no_meta.parse_path(path) => true,
             syn::Meta::NameValue(meta) if no_meta.parse_bool(meta) => true,
 
+            syn::Meta::Path(path) if serde.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if serde.parse_bool(meta) => true,
+
             syn::Meta::NameValue(path) if ctor_prefix.parse_str(path) => true,
             syn::Meta::NameValue(meta) if size_cache_renamed.parse_str(meta) => true,
             syn::Meta::NameValue(meta) if runtime_renamed.parse_str(meta) => true,
@@ -109,13 +136,37 @@ impl DeriveSetting {
             _ => false,
         });
 
-        let has_size_cache = derive_serialize && !no_size_cache.get().unwrap_or_default();
-        let has_runtime = derive_state;
+        // Transparent types borrow the size cache and runtime of their inner values
+        let transparent = transparent.get().unwrap_or_default();
+
+        let repr_varint = match repr.get_with_tokens() {
+            Some((repr, _)) if repr == "varint" => true,
+            Some((repr, repr_tokens)) => {
+                ctx.error(
+                    repr_tokens,
+                    format!("unsupported repr `{}`, expected `varint`", repr),
+                );
+                false
+            }
+            None => false,
+        };
+
+        // Both are values without a size cache or runtime of their own
+        let value_like = transparent || repr_varint;
 
-        let derive_ctors = derive_deserialize || derive_ctors.get().unwrap_or_default();
-        let derive_setters = derive_state || derive_setters.get().unwrap_or_default();
+        let has_size_cache =
+            derive_serialize && !value_like && !no_size_cache.get().unwrap_or_default();
+        let has_runtime = derive_state && !value_like;
+
+        let derive_ctors =
+            !repr_varint && (derive_deserialize || derive_ctors.get().unwrap_or_default());
+        let derive_setters =
+            !value_like && (derive_state || derive_setters.get().unwrap_or_default());
         let derive_wire_type = derive_serialize || derive_deserialize;
         let derive_meta = derive_deserialize && !no_meta.get().unwrap_or_default();
+        let derive_serde = cfg!(feature = "serde")
+            && (derive_serialize || derive_deserialize)
+            && serde.get().unwrap_or(true);
 
         (
             Self {
@@ -130,6 +181,9 @@ impl DeriveSetting {
                 derives,
 
                 steit_owned: steit_owned.get().unwrap_or_default(),
+                transparent,
+                accessors: accessors.get().unwrap_or_default(),
+                repr_varint,
 
                 has_size_cache,
                 has_runtime,
@@ -138,10 +192,13 @@ impl DeriveSetting {
                 derive_setters,
                 derive_wire_type,
                 derive_meta,
+                derive_serde,
 
                 ctor_prefix: ctor_prefix.get().unwrap_or_else(|| "new".to_string()),
                 size_cache_renamed: size_cache_renamed.get_with_tokens(),
                 runtime_renamed: runtime_renamed.get_with_tokens(),
+
+                doc,
             },
             unknown_attrs,
         )
@@ -170,7 +227,40 @@ pub fn derive(args: syn::AttributeArgs, mut input: syn::DeriveInput) -> TokenStr
     let (setting, unknown_attrs) = DeriveSetting::parse(&ctx, args, &mut input.attrs);
     let type_params = parse_type_params(&ctx, &input.generics);
 
+    let vis = &input.vis;
+    let mut handle_trait = None;
+
     let output = match &mut input.data {
+        syn::Data::Struct(data) if setting.transparent => {
+            Transparent::parse(&ctx, &impler, &setting, unknown_attrs, &mut data.fields)
+                .ok()
+                .into_token_stream()
+        }
+
+        syn::Data::Enum(data) if setting.transparent => {
+            ctx.error(
+                data.enum_token,
+                "`transparent` is only supported on structs",
+            );
+            quote!()
+        }
+
+        syn::Data::Struct(data) if setting.repr_varint => {
+            ctx.error(data.struct_token, "`repr` is only supported on enums");
+            quote!()
+        }
+
+        syn::Data::Enum(data) if setting.repr_varint => {
+            VarintEnum::parse(&ctx, &impler, &setting, unknown_attrs, &mut data.variants)
+                .ok()
+                .into_token_stream()
+        }
+
+        syn::Data::Enum(data) if setting.accessors => {
+            ctx.error(data.enum_token, "`accessors` is only supported on structs");
+            quote!()
+        }
+
         syn::Data::Struct(data) => Struct::parse(
             &ctx,
             &impler,
@@ -180,8 +270,11 @@ pub fn derive(args: syn::AttributeArgs, mut input: syn::DeriveInput) -> TokenStr
             &mut data.fields,
             None,
         )
-        .ok()
-        .into_token_stream(),
+        .map(|r#struct| {
+            handle_trait = r#struct.handle_trait(vis);
+            r#struct.into_token_stream()
+        })
+        .unwrap_or_default(),
 
         syn::Data::Enum(data) => Enum::parse(
             &ctx,
@@ -200,13 +293,14 @@ pub fn derive(args: syn::AttributeArgs, mut input: syn::DeriveInput) -> TokenStr
         }
     };
 
-    let output = wrap_in_const(&setting, &input.ident, output);
+    let output = wrap_in_const(&setting, output);
     let derives = setting.derives;
     let errors = ctx.check().err().map(to_compile_errors);
 
     let derived = quote! {
         #[derive(#(#derives),*)]
         #input
+        #handle_trait
         #output
         #errors
     };
@@ -231,22 +325,56 @@ fn parse_type_params<'a>(ctx: &Context, generics: &'a syn::Generics) -> Vec<&'a
     type_params
 }
 
+/// Evaluates to the `NameMeta` of a declared type, field or variant.
+pub fn name_meta(
+    rust_name: &str,
+    csharp_name: Option<&String>,
+    doc: Option<&String>,
+    annotations: &[(String, String)],
+) -> TokenStream {
+    let csharp_name = match csharp_name {
+        Some(csharp_name) => quote!(Some(#csharp_name)),
+        None => quote!(None),
+    };
+
+    let doc = match doc {
+        Some(doc) => quote!(Some(#doc)),
+        None => quote!(None),
+    };
+
+    let annotations = annotations
+        .iter()
+        .map(|(key, value)| quote!((#key, #value)));
+
+    quote! {
+        NameMeta {
+            rust: #rust_name,
+            csharp: #csharp_name,
+            doc: #doc,
+            annotations: &[#(#annotations,)*],
+        }
+    }
+}
+
 fn to_compile_errors(errors: Vec<syn::Error>) -> TokenStream {
     let compile_errors = errors.iter().map(syn::Error::to_compile_error);
     quote!(#(#compile_errors)*)
 }
 
-fn wrap_in_const(setting: &DeriveSetting, name: &syn::Ident, tokens: TokenStream) -> TokenStream {
-    let dummy_const = format_ident!(
-        "_IMPL_STEIT_FOR_{}",
-        str_util::to_snake_case(name.to_string()).to_uppercase(),
-    );
-
+fn wrap_in_const(setting: &DeriveSetting, tokens: TokenStream) -> TokenStream {
     let extern_crate = setting.extern_crate();
     let krate = setting.krate();
 
+    let serde = if setting.derive_serde {
+        Some(quote! { use #krate::serde_bridge::{self, serde}; })
+    } else {
+        None
+    };
+
     quote! {
-        const #dummy_const: () = {
+        // Deprecated fields and variants are still handled here
+        #[allow(deprecated)]
+        const _: () = {
             #extern_crate
 
             use std::{
@@ -259,11 +387,13 @@ fn wrap_in_const(setting: &DeriveSetting, name: &syn::Ident, tokens: TokenStream
                 log::LogEntryKind,
                 meta::*,
                 rt::{Runtime, SizeCache},
-                ser::Serialize,
+                ser::{Serialize, Writer},
                 state::State,
                 wire_fmt::{HasWireType, WireType},
             };
 
+            #serde
+
             #tokens
         };
     }
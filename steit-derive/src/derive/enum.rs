@@ -15,25 +15,29 @@ use super::{
     variant::Variant,
 };
 
-struct EnumAttrs {
-    reserved_tags: Vec<u32>,
-    csharp_name: Option<String>,
+pub struct EnumAttrs {
+    pub reserved_tags: Vec<u32>,
+    pub csharp_name: Option<String>,
+    pub annotations: Vec<(String, String)>,
 }
 
 impl EnumAttrs {
     pub fn parse(ctx: &Context, attrs: impl AttributeParse) -> Self {
         let mut reserved_tags = VecAttribute::new(ctx, "reserved_tags");
         let mut csharp_name = Attribute::new(ctx, "csharp_name");
+        let mut annotations = VecAttribute::new(ctx, "meta");
 
         attrs.parse(ctx, true, |meta| match meta {
             syn::Meta::List(meta) if reserved_tags.parse_int_list(meta) => true,
             syn::Meta::NameValue(meta) if csharp_name.parse_str(meta) => true,
+            syn::Meta::List(meta) if annotations.parse_str_pairs(meta) => true,
             _ => false,
         });
 
         Self {
             reserved_tags: reserved_tags.get(),
             csharp_name: csharp_name.get(),
+            annotations: annotations.get(),
         }
     }
 }
@@ -121,9 +125,18 @@ impl<'a> Enum<'a> {
 
     fn impl_setters(&self) -> TokenStream {
         let setters = self.variants.iter().map(|r#struct| r#struct.setters());
+        let mut_getters = self
+            .variants
+            .iter()
+            .map(|r#struct| r#struct.variant_mut_getters());
 
-        self.impler
-            .impl_with(self.trait_bounds(&["Default"]), quote!(#(#setters)*))
+        self.impler.impl_with(
+            self.trait_bounds(&["Default"]),
+            quote! {
+                #(#setters)*
+                #(#mut_getters)*
+            },
+        )
     }
 
     fn impl_partial_eq(&self) -> TokenStream {
@@ -264,6 +277,18 @@ impl<'a> Enum<'a> {
             }
         });
 
+        let change_stamp = if self.setting.has_runtime {
+            let runtime = self.runtime();
+
+            Some(quote! {
+                fn change_stamp(&self) -> Option<u64> {
+                    Some((#runtime).change_stamp())
+                }
+            })
+        } else {
+            None
+        };
+
         self.impler.impl_for(
             "Serialize",
             quote! {
@@ -273,7 +298,7 @@ impl<'a> Enum<'a> {
                     size
                 }
 
-                fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+                fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
                     match self { #(#serializers)* }
                     Ok(())
                 }
@@ -281,6 +306,8 @@ impl<'a> Enum<'a> {
                 fn size_cache(&self) -> Option<&SizeCache> {
                     match self { #(#size_caches,)* }
                 }
+
+                #change_stamp
             },
         )
     }
@@ -317,11 +344,19 @@ impl<'a> Enum<'a> {
             }
         });
 
+        let touch = if self.setting.has_runtime {
+            let runtime = self.runtime();
+            Some(quote! { (#runtime).touch(); })
+        } else {
+            None
+        };
+
         self.impler.impl_for_with(
             "Deserialize",
             self.trait_bounds(&["Deserialize"]),
             quote! {
                 fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+                    #touch
                     let tag = u32::deserialize(reader)?;
 
                     match tag {
@@ -341,8 +376,176 @@ impl<'a> Enum<'a> {
         )
     }
 
-    fn impl_state(&self) -> TokenStream {
-        let ctor_name = format_ident!("{}", &self.setting.ctor_prefix);
+    /// Bounds of the serde impls, the usual ones plus `serde_bound`.
+    fn serde_bounds(&self, fallback: &'static [&str], serde_bound: &'static str) -> Vec<&str> {
+        let mut bounds = self.trait_bounds(fallback).to_vec();
+        bounds.push(serde_bound);
+        bounds
+    }
+
+    fn impl_serde_serialize(&self) -> TokenStream {
+        let name = self.impler.name();
+
+        let serializers = self.variants.iter().map(|r#struct| {
+            let variant = r#struct.variant().unwrap();
+            let qual = variant.qual();
+            let variant_name = variant.name().to_string();
+
+            let destructure = r#struct.destructure();
+            let serializer = r#struct.serde_serializer();
+
+            quote! {
+                #name #qual { #destructure .. } => {
+                    if in_variant {
+                        #serializer
+                    } else {
+                        serde_bridge::serialize_variant(serde_map, #variant_name, self)?;
+                    }
+                }
+            }
+        });
+
+        let serialize_fields = self.impler.impl_for_with(
+            "serde_bridge::SerializeFields",
+            &self.serde_bounds(&[], "serde::Serialize"),
+            quote! {
+                fn serialize_fields<SerdeMap: serde::ser::SerializeMap>(
+                    &self,
+                    in_variant: bool,
+                    serde_map: &mut SerdeMap,
+                ) -> Result<(), SerdeMap::Error> {
+                    match self { #(#serializers)* }
+                    Ok(())
+                }
+            },
+        );
+
+        let serialize = self.impler.impl_for_with(
+            "serde::Serialize",
+            &self.serde_bounds(&[], "serde::Serialize"),
+            quote! {
+                fn serialize<SerdeSerializer: serde::Serializer>(
+                    &self,
+                    serializer: SerdeSerializer,
+                ) -> Result<SerdeSerializer::Ok, SerdeSerializer::Error> {
+                    serde_bridge::serialize_fields(self, serializer)
+                }
+            },
+        );
+
+        quote! {
+            #serialize_fields
+            #serialize
+        }
+    }
+
+    fn impl_serde_deserialize(&self) -> TokenStream {
+        let name = self.impler.name();
+        let enum_name = name.to_string();
+
+        let variant_names: Vec<_> = self
+            .variants
+            .iter()
+            .map(|r#struct| r#struct.variant().unwrap().name().to_string())
+            .collect();
+
+        let field_mergers = self.variants.iter().map(|r#struct| {
+            let variant = r#struct.variant().unwrap();
+            let qual = variant.qual();
+
+            let destructure = r#struct.destructure();
+            let runtime_destructure = r#struct.runtime().map(|runtime| {
+                let destructure = runtime.destructure(format_ident!("self_runtime"));
+                quote!(#destructure,)
+            });
+            let merger = r#struct.serde_merger();
+
+            quote! {
+                #name #qual { #destructure #runtime_destructure .. } => {
+                    #merger
+                }
+            }
+        });
+
+        let variant_mergers =
+            self.variants
+                .iter()
+                .zip(&variant_names)
+                .map(|(r#struct, variant_name)| {
+                    let variant = r#struct.variant().unwrap();
+                    let qual = variant.qual();
+                    let ctor_name = variant.ctor_name();
+
+                    let args = if self.setting.derive_state {
+                        Some(quote!(self.runtime().parent()))
+                    } else {
+                        None
+                    };
+
+                    quote! {
+                        #variant_name => {
+                            if let #name #qual { .. } = self {
+                            } else {
+                                *self = Self::#ctor_name(#args);
+                            }
+
+                            serde_bridge::merge_variant(serde_map, self)?;
+                            Ok(true)
+                        }
+                    }
+                });
+
+        let merge_fields = self.impler.impl_for_with(
+            "serde_bridge::MergeFields",
+            &self.serde_bounds(&["Default"], "serde::de::DeserializeOwned"),
+            quote! {
+                const NAME: &'static str = #enum_name;
+
+                fn merge_field<'de, SerdeAccess: serde::de::MapAccess<'de>>(
+                    &mut self,
+                    in_variant: bool,
+                    field_name: &str,
+                    serde_map: &mut SerdeAccess,
+                ) -> Result<bool, SerdeAccess::Error> {
+                    if in_variant {
+                        match self { #(#field_mergers)* }
+                    } else {
+                        match field_name {
+                            #(#variant_mergers)*
+
+                            _ => Err(serde::de::Error::unknown_variant(
+                                field_name,
+                                &[#(#variant_names,)*],
+                            )),
+                        }
+                    }
+                }
+            },
+        );
+
+        let deserialize = self.impler.impl_for_with_lifetime(
+            Some("'de"),
+            "serde::Deserialize<'de>",
+            &self.serde_bounds(&["Default"], "serde::de::DeserializeOwned"),
+            quote! {
+                fn deserialize<SerdeDeserializer: serde::Deserializer<'de>>(
+                    deserializer: SerdeDeserializer,
+                ) -> Result<Self, SerdeDeserializer::Error> {
+                    let mut value = Self::default();
+                    serde_bridge::merge_fields(deserializer, &mut value)?;
+                    Ok(value)
+                }
+            },
+        );
+
+        quote! {
+            #merge_fields
+            #deserialize
+        }
+    }
+
+    /// Matches the runtime out of whichever variant `self` is.
+    fn runtime(&self) -> TokenStream {
         let name = self.impler.name();
 
         let runtimes = self.variants.iter().map(|r#struct| {
@@ -355,6 +558,14 @@ impl<'a> Enum<'a> {
             quote!(#name #qual { #destructure, .. } => runtime)
         });
 
+        quote!(match self { #(#runtimes,)* })
+    }
+
+    fn impl_state(&self) -> TokenStream {
+        let ctor_name = format_ident!("{}", &self.setting.ctor_prefix);
+        let name = self.impler.name();
+        let runtime = self.runtime();
+
         let runtime_setters = self.variants.iter().map(|r#struct| {
             let variant = r#struct.variant().unwrap();
             let qual = variant.qual();
@@ -405,7 +616,7 @@ impl<'a> Enum<'a> {
                 }
 
                 fn runtime(&self) -> &Runtime {
-                    match self { #(#runtimes,)* }
+                    #runtime
                 }
 
                 fn set_runtime(&mut self, runtime: Runtime) {
@@ -428,6 +639,8 @@ impl<'a> Enum<'a> {
                     key: Option<u32>,
                     reader: &mut Reader<impl io::Read>,
                 ) -> io::Result<()> {
+                    (#runtime).touch();
+
                     if let Some(tag) = path.next() {
                         match tag {
                             #(#replayers,)*
@@ -440,6 +653,7 @@ impl<'a> Enum<'a> {
                     } else {
                         match kind {
                             LogEntryKind::Update => self.handle_update(reader),
+                            LogEntryKind::Checksum => self.handle_checksum(reader),
 
                             _ => Err(io::Error::new(
                                 io::ErrorKind::InvalidData,
@@ -453,18 +667,19 @@ impl<'a> Enum<'a> {
     }
 
     fn impl_meta(&self) -> TokenStream {
-        let rust_name = self.impler.name().to_string();
-
-        let csharp_name = match &self.attrs.csharp_name {
-            Some(csharp_name) => quote!(Some(#csharp_name)),
-            None => quote!(None),
-        };
+        let name = derive::name_meta(
+            &self.impler.name().to_string(),
+            self.attrs.csharp_name.as_ref(),
+            self.setting.doc.as_ref(),
+            &self.attrs.annotations,
+        );
 
         let builtin = self.setting.steit_owned;
 
         let variants = self.variants.iter().map(|r#struct| {
             let variant = r#struct.variant().unwrap();
             let tag = variant.tag();
+            let deprecated = variant.deprecated();
 
             let meta = r#struct.meta();
 
@@ -472,6 +687,7 @@ impl<'a> Enum<'a> {
                 VariantMeta {
                     ty: #meta,
                     tag: #tag,
+                    deprecated: #deprecated,
                 }
             }
         });
@@ -487,14 +703,12 @@ impl<'a> Enum<'a> {
         });
 
         let links = self.variants.iter().map(|r#struct| r#struct.meta_links());
+        let reserved_tags = &self.attrs.reserved_tags;
 
         self.impler.impl_for(
             "HasMeta",
             quote! {
-                const NAME: &'static NameMeta = &NameMeta {
-                    rust: #rust_name,
-                    csharp: #csharp_name,
-                };
+                const NAME: &'static NameMeta = &#name;
 
                 const TYPE: &'static TypeMeta = &TypeMeta::Ref(Self::NAME, &[#(#param_meta_list,)*]);
 
@@ -502,8 +716,11 @@ impl<'a> Enum<'a> {
                     r#type: Self::TYPE,
                     msg: Some(MessageMeta::Enum(EnumMeta {
                         name: Self::NAME,
+                        module: module_path!(),
                         type_params: &[#(#type_params,)*],
                         variants: &[#(#variants,)*],
+                        reserved_tags: &[#(#reserved_tags,)*],
+                        repr: EnumRepr::Message,
                         builtin: #builtin,
                     })),
                     links: || &[#(#links)*],
@@ -601,6 +818,10 @@ impl<'a> ToTokens for Enum<'a> {
         }
 
         if self.setting.derive_serialize {
+            for r#struct in &self.variants {
+                tokens.extend(r#struct.with_adapters());
+            }
+
             tokens.extend(self.impl_serialize());
         }
 
@@ -612,6 +833,14 @@ impl<'a> ToTokens for Enum<'a> {
             tokens.extend(self.impl_state());
         }
 
+        if self.setting.derive_serde && self.setting.derive_serialize {
+            tokens.extend(self.impl_serde_serialize());
+        }
+
+        if self.setting.derive_serde && self.setting.derive_deserialize {
+            tokens.extend(self.impl_serde_deserialize());
+        }
+
         if self.setting.derive_meta {
             tokens.extend(self.impl_meta());
         }
@@ -4,7 +4,7 @@ use proc_macro2::TokenStream;
 use quote::ToTokens;
 
 use crate::{
-    attr::{Attribute, AttributeParse},
+    attr::{self, Attribute, AttributeParse, VecAttribute},
     ctx::Context,
 };
 
@@ -15,29 +15,65 @@ use super::{
 };
 
 struct FieldAttrs {
-    tag: u32,
-    tag_tokens: TokenStream,
+    // `None` for skipped fields
+    tag: Option<(u32, TokenStream)>,
+    default: Option<syn::Expr>,
+    // A module with `TYPE: &TypeMeta`, `compute_size`, `serialize` and `merge`,
+    // standing in for the `HasMeta`, `Serialize` and `Deserialize` impls of the field type
+    with: Option<syn::Path>,
+    // Setters warn when used, but the field is still decoded and replayed
+    deprecated: bool,
+    // Inlines the fields of a nested struct, which then take no tag or path segment of their own
+    flatten: bool,
+    // A function checking the field whenever it is decoded or replayed
+    validate: Option<syn::Path>,
 
     no_hash: bool,
     no_eq_hash: bool,
     no_state: bool,
 
     csharp_name: Option<String>,
+    doc: Option<String>,
+    annotations: Vec<(String, String)>,
 }
 
 impl FieldAttrs {
     pub fn parse(ctx: &Context, field: &mut syn::Field) -> derive::Result<Self> {
         let mut tag = Attribute::new(ctx, "tag");
 
+        let mut skip = Attribute::new(ctx, "skip");
+        let mut default = Attribute::new(ctx, "default");
+        let mut with = Attribute::new(ctx, "with");
+        let mut deprecated = Attribute::new(ctx, "deprecated");
+        let mut flatten = Attribute::new(ctx, "flatten");
+        let mut validate = Attribute::new(ctx, "validate");
+
         let mut no_hash = Attribute::new(ctx, "no_hash");
         let mut no_eq_hash = Attribute::new(ctx, "no_eq_hash");
         let mut no_state = Attribute::new(ctx, "no_state");
 
         let mut csharp_name = Attribute::new(ctx, "csharp_name");
+        let mut annotations = VecAttribute::new(ctx, "meta");
+
+        let doc = attr::parse_doc(&field.attrs);
 
         (&mut field.attrs).parse(ctx, true, |meta| match meta {
             syn::Meta::NameValue(meta) if tag.parse_int(meta) => true,
 
+            syn::Meta::Path(path) if skip.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if skip.parse_bool(meta) => true,
+
+            syn::Meta::NameValue(meta) if default.parse_expr(meta) => true,
+            syn::Meta::NameValue(meta) if with.parse_path_str(meta) => true,
+
+            syn::Meta::Path(path) if deprecated.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if deprecated.parse_bool(meta) => true,
+
+            syn::Meta::Path(path) if flatten.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if flatten.parse_bool(meta) => true,
+
+            syn::Meta::NameValue(meta) if validate.parse_path_str(meta) => true,
+
             syn::Meta::Path(path) if no_hash.parse_path(path) => true,
             syn::Meta::NameValue(meta) if no_hash.parse_bool(meta) => true,
 
@@ -48,27 +84,87 @@ impl FieldAttrs {
             syn::Meta::NameValue(meta) if no_state.parse_bool(meta) => true,
 
             syn::Meta::NameValue(meta) if csharp_name.parse_str(meta) => true,
+            syn::Meta::List(meta) if annotations.parse_str_pairs(meta) => true,
 
             _ => false,
         });
 
-        let (tag, tag_tokens) = tag
+        let skip = skip.get().unwrap_or_default();
+        let tag = tag.get_with_tokens();
+        let with = with.get_with_tokens();
+        let validate = validate.get_with_tokens();
+        let (flatten, flatten_tokens) = flatten
             .get_with_tokens()
-            .ok_or_else(|| ctx.error(field, "expected a valid tag `#[steit(tag = …)]`"))?;
+            .map_or((false, None), |(flatten, tokens)| (flatten, Some(tokens)));
+
+        if flatten {
+            let message = match (skip, &tag, &with, &validate) {
+                (true, _, _, _) => Some("skipped fields cannot be flattened"),
+                (_, Some(_), _, _) => Some("flattened fields take the tags of their own fields"),
+                (_, _, Some(_), _) => Some("flattened fields take no codec"),
+                (_, _, _, Some(_)) => Some("flattened fields are checked by their own `validate`"),
+                _ => None,
+            };
+
+            if let Some(message) = message {
+                ctx.error(flatten_tokens.unwrap(), message);
+                return Err(());
+            }
+        }
+
+        if let (true, Some((_, with_tokens))) = (skip, &with) {
+            ctx.error(
+                with_tokens,
+                "skipped fields are not on the wire and take no codec",
+            );
+            return Err(());
+        }
+
+        if let (true, Some((_, validate_tokens))) = (skip, &validate) {
+            ctx.error(
+                validate_tokens,
+                "skipped fields are never decoded and take no `validate`",
+            );
+            return Err(());
+        }
+
+        match &tag {
+            Some((_, tag_tokens)) if skip => {
+                ctx.error(
+                    tag_tokens,
+                    "skipped fields are not on the wire and take no tag",
+                );
+                return Err(());
+            }
 
-        tag::validate(tag).map_err(|message| {
-            ctx.error(&tag_tokens, message);
-        })?;
+            Some((tag, tag_tokens)) => {
+                tag::validate(*tag).map_err(|message| {
+                    ctx.error(tag_tokens, message);
+                })?;
+            }
+
+            None if skip || flatten => (),
+            None => {
+                ctx.error(field, "expected a valid tag `#[steit(tag = …)]`");
+                return Err(());
+            }
+        }
 
         Ok(Self {
             tag,
-            tag_tokens,
+            default: default.get(),
+            with: with.map(|(with, _)| with),
+            deprecated: deprecated.get().unwrap_or_default(),
+            flatten,
+            validate: validate.map(|(validate, _)| validate),
 
             no_hash: no_hash.get().unwrap_or_default(),
             no_eq_hash: no_eq_hash.get().unwrap_or_default(),
             no_state: no_state.get().unwrap_or_default(),
 
             csharp_name: csharp_name.get(),
+            doc,
+            annotations: annotations.get(),
         })
     }
 }
@@ -134,6 +230,14 @@ impl Field {
         }
     }
 
+    /// The key of the field in serde maps, its index for tuple structs.
+    pub fn serde_name(&self) -> String {
+        match &self.name {
+            Some(name) => syn::ext::IdentExt::unraw(name).to_string(),
+            None => self.index.to_string(),
+        }
+    }
+
     pub fn alias_prefixed(&self, prefix: impl Into<Option<syn::Ident>>) -> syn::Ident {
         let alias = match &self.name {
             Some(name) => format_ident!("{}", name),
@@ -183,11 +287,176 @@ impl Field {
     }
 }
 
+/// A field which is left out of the wire format, e.g. a server-side cache,
+/// rebuilt from its default whenever its parent is constructed.
+pub struct SkippedField {
+    field: Field,
+    default: Option<syn::Expr>,
+}
+
+impl Deref for SkippedField {
+    type Target = Field;
+
+    fn deref(&self) -> &Self::Target {
+        &self.field
+    }
+}
+
+impl SkippedField {
+    pub fn init_default(&self) -> TokenStream {
+        self.init(match &self.default {
+            Some(default) => default.to_token_stream(),
+            None => quote!(Default::default()),
+        })
+    }
+}
+
+/// A struct whose fields are inlined into its parent, sharing the runtime of the parent.
+pub struct FlattenedField {
+    field: Field,
+    krate: TokenStream,
+}
+
+impl Deref for FlattenedField {
+    type Target = Field;
+
+    fn deref(&self) -> &Self::Target {
+        &self.field
+    }
+}
+
+impl FlattenedField {
+    fn flatten(&self) -> TokenStream {
+        let krate = &self.krate;
+        let ty = &self.ty;
+        quote!(<#ty as #krate::state::Flatten>)
+    }
+
+    pub fn tags(&self) -> TokenStream {
+        let flatten = self.flatten();
+        quote!(#flatten::TAGS)
+    }
+
+    pub fn fields_meta(&self) -> TokenStream {
+        let flatten = self.flatten();
+        quote!(#flatten::FIELDS)
+    }
+
+    pub fn meta_links(&self) -> TokenStream {
+        let flatten = self.flatten();
+        quote!(#flatten::LINKS)
+    }
+
+    pub fn init_default(&self) -> TokenStream {
+        self.init(quote!(State::with_runtime(runtime.clone())))
+    }
+
+    pub fn eq(&self) -> TokenStream {
+        let field = self.field(false);
+        let other_field = self.field_other(format_ident!("other"), false);
+
+        quote! {
+            if #field != #other_field {
+                return false;
+            }
+        }
+    }
+
+    pub fn hash(&self) -> TokenStream {
+        let field = self.field(false);
+        quote! { #field.hash(state); }
+    }
+
+    pub fn sizer(&self) -> TokenStream {
+        let field = self.field(false);
+        quote! { size += #field.cache_size(); }
+    }
+
+    pub fn serializer(&self) -> TokenStream {
+        let field = self.field(false);
+        quote! { #field.serialize_cached(writer)?; }
+    }
+
+    /// Runs `on_serialized` if the field numbered `field_number` belongs to this struct,
+    /// after serializing it.
+    pub fn field_serializer(&self, on_serialized: &TokenStream) -> TokenStream {
+        let flatten = self.flatten();
+        let field = self.field(false);
+
+        quote! {
+            if #flatten::serialize_field(&#field, field_number, writer)? {
+                #on_serialized
+            }
+        }
+    }
+
+    /// Runs `on_merged` if the field numbered `field_number` belongs to this struct.
+    pub fn merger(&self, on_merged: &TokenStream) -> TokenStream {
+        let flatten = self.flatten();
+        let field = self.field(false);
+
+        quote! {
+            if #flatten::merge_field(&mut #field, field_number, wire_type, reader)? {
+                #on_merged
+            }
+        }
+    }
+
+    pub fn validator(&self) -> TokenStream {
+        let flatten = self.flatten();
+        let field = self.field(false);
+        quote! { #flatten::validate(&#field)?; }
+    }
+
+    pub fn runtime_setter(&self) -> TokenStream {
+        let field = self.field(false);
+        quote! { #field.set_runtime(runtime.clone()); }
+    }
+
+    pub fn serde_serializer(&self) -> TokenStream {
+        let field = self.field(false);
+        quote! { serde_bridge::SerializeFields::serialize_fields(&#field, false, serde_map)?; }
+    }
+
+    /// Merged in place, as the struct already shares the runtime of its parent.
+    pub fn serde_merger(&self) -> TokenStream {
+        let field = self.field(false);
+
+        quote! {
+            if serde_bridge::MergeFields::merge_field(&mut #field, false, field_name, serde_map)? {
+                return Ok(true);
+            }
+        }
+    }
+
+    pub fn replayer(&self) -> TokenStream {
+        let tags = self.tags();
+        let field = self.field(false);
+
+        quote! {
+            tag if #tags.contains(&tag) => {
+                #field.handle(std::iter::once(tag).chain(path), kind, key, reader)
+            }
+        }
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+pub enum ParsedField<'a> {
+    Derive(DeriveField<'a>),
+    Skipped(SkippedField),
+    Flattened(FlattenedField),
+}
+
 pub struct DeriveField<'a> {
     setting: &'a DeriveSetting,
     attrs: FieldAttrs,
+    tag: u32,
+    tag_tokens: TokenStream,
     field: Field,
     type_meta: Option<TokenStream>,
+    // Serializes the field through its `with` codec
+    with_adapter: Option<syn::Ident>,
 }
 
 impl<'a> Deref for DeriveField<'a> {
@@ -203,44 +472,180 @@ impl<'a> DeriveField<'a> {
         ctx: &Context,
         setting: &'a DeriveSetting,
         type_params: &'a [&'a syn::TypeParam],
-        field: &mut syn::Field,
+        raw_field: &mut syn::Field,
         index: usize,
-    ) -> derive::Result<Self> {
-        let attrs = FieldAttrs::parse(ctx, field)?;
-        let field = Field::from_field(field, index);
+        variant: Option<&Variant>,
+    ) -> derive::Result<ParsedField<'a>> {
+        let mut attrs = FieldAttrs::parse(ctx, raw_field)?;
+        let field = Field::from_field(raw_field, index);
+
+        if attrs.flatten {
+            let message = if variant.is_some() {
+                Some("`flatten` is not supported on variants")
+            } else if !type_params.is_empty() {
+                Some("`flatten` is not supported in generic structs")
+            } else if !setting.derive_state {
+                Some("`flatten` is only supported when deriving `State`")
+            } else if setting.accessors {
+                // Flattened fields would stay public, with no accessors logging under them
+                Some("`flatten` is not supported with `accessors`")
+            } else {
+                None
+            };
 
-        let type_meta = if setting.derive_meta {
-            Some(field_type_meta(ctx, &field.ty, type_params)?)
-        } else {
-            None
+            if let Some(message) = message {
+                ctx.error(&field.ty, message);
+                return Err(());
+            }
+
+            return Ok(ParsedField::Flattened(FlattenedField {
+                field,
+                krate: setting.krate(),
+            }));
+        }
+
+        let (tag, tag_tokens) = match attrs.tag.take() {
+            Some(tag) => tag,
+            None => {
+                return Ok(ParsedField::Skipped(SkippedField {
+                    field,
+                    default: attrs.default,
+                }))
+            }
         };
 
-        Ok(Self {
+        if let (Some(validate), Some(_)) = (&attrs.validate, variant) {
+            ctx.error(
+                validate,
+                "`validate` is not supported on fields of variants",
+            );
+            return Err(());
+        }
+
+        if setting.accessors {
+            raw_field.vis = syn::Visibility::Inherited;
+        }
+
+        let with_adapter = match &attrs.with {
+            Some(_) if mentions_type_params(field.ty.to_token_stream(), type_params) => {
+                ctx.error(
+                    &field.ty,
+                    "`with` is not supported on fields of generic types",
+                );
+                return Err(());
+            }
+
+            Some(_) => Some(format_ident!(
+                "__with_{}",
+                field.alias_prefixed(
+                    variant.map(|variant| { format_ident!("{}", variant.snake_case_name()) })
+                ),
+            )),
+
+            None => None,
+        };
+
+        let type_meta = match (&attrs.with, setting.derive_meta) {
+            (Some(with), true) => Some(quote!(FieldTypeMeta::Type(#with::TYPE))),
+            (None, true) => Some(field_type_meta(ctx, &field.ty, type_params)?),
+            (_, false) => None,
+        };
+
+        Ok(ParsedField::Derive(Self {
             setting,
             attrs,
+            tag,
+            tag_tokens,
             field,
             type_meta,
-        })
+            with_adapter,
+        }))
     }
 
     pub fn tag(&self) -> u32 {
-        self.attrs.tag
+        self.tag
     }
 
     pub fn tag_with_tokens(&self) -> (u32, &TokenStream) {
-        (self.attrs.tag, &self.attrs.tag_tokens)
+        (self.tag, &self.tag_tokens)
+    }
+
+    /// Fields starting from a custom default are never omitted,
+    /// or their zero values would turn back into the default when deserialized.
+    fn omissible(&self) -> bool {
+        self.attrs.default.is_none()
     }
 
     pub fn is_state(&self) -> bool {
-        self.setting.derive_state && !self.attrs.no_state
+        self.setting.derive_state && !self.attrs.no_state && self.attrs.with.is_none()
     }
 
-    pub fn init_default(&self) -> TokenStream {
-        self.init(if self.is_state() {
-            let tag = self.tag();
-            quote!(State::with_runtime(runtime.nested(#tag)))
+    /// Fields of variants are bound by mutable references already.
+    fn field_mut(&self, is_variant: bool) -> TokenStream {
+        let field = self.field(is_variant);
+
+        if is_variant {
+            field
         } else {
-            quote!(Default::default())
+            quote!(&mut #field)
+        }
+    }
+
+    fn default_value(&self) -> TokenStream {
+        match &self.attrs.default {
+            Some(default) => default.to_token_stream(),
+            None => quote!(Default::default()),
+        }
+    }
+
+    /// Declares a wrapper which serializes the field through its `with` codec,
+    /// so it can be nested and logged like any other value.
+    pub fn with_adapter(&self) -> Option<TokenStream> {
+        let adapter = self.with_adapter.as_ref()?;
+        let with = self.attrs.with.as_ref()?;
+        let ty = &self.ty;
+
+        Some(quote! {
+            #[allow(non_camel_case_types)]
+            struct #adapter<'a>(&'a #ty);
+
+            impl HasWireType for #adapter<'_> {
+                const WIRE_TYPE: WireType = #with::TYPE.wire_type();
+            }
+
+            impl Serialize for #adapter<'_> {
+                fn compute_size(&self) -> u32 {
+                    #with::compute_size(self.0)
+                }
+
+                fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
+                    #with::serialize(self.0, writer)
+                }
+
+                fn size_cache(&self) -> Option<&SizeCache> {
+                    None
+                }
+            }
+        })
+    }
+
+    pub fn init_default(&self) -> TokenStream {
+        let tag = self.tag();
+
+        self.init(match (&self.attrs.default, self.is_state()) {
+            (Some(default), true) => {
+                let ty = &self.ty;
+
+                quote! {{
+                    let mut value: #ty = #default;
+                    value.set_runtime(runtime.nested(#tag));
+                    value
+                }}
+            }
+
+            (Some(default), false) => default.to_token_stream(),
+            (None, true) => quote!(State::with_runtime(runtime.nested(#tag))),
+            (None, false) => quote!(Default::default()),
         })
     }
 
@@ -252,6 +657,14 @@ impl<'a> DeriveField<'a> {
 
         let setter_with_name = format_ident!("{}_with", setter_name);
 
+        let variant_deprecated = matches!(variant, Some(variant) if variant.deprecated());
+
+        let deprecated = if self.attrs.deprecated || variant_deprecated {
+            Some(quote!(#[deprecated]))
+        } else {
+            None
+        };
+
         let ty = &self.ty;
         let tag = self.tag();
 
@@ -296,6 +709,7 @@ impl<'a> DeriveField<'a> {
 
             (
                 quote! {
+                    #deprecated
                     pub fn #setter_name(&mut self, mut value: #ty) -> &mut Self {
                         #reset_variant
                         #declare_runtime
@@ -306,6 +720,7 @@ impl<'a> DeriveField<'a> {
                     }
                 },
                 Some(quote! {
+                    #deprecated
                     pub fn #setter_with_name(&mut self, get_value: impl FnOnce(Runtime) -> #ty) -> &mut Self {
                         #reset_variant
                         #declare_runtime
@@ -319,10 +734,19 @@ impl<'a> DeriveField<'a> {
                 }),
             )
         } else {
+            let log_update = match &self.with_adapter {
+                Some(adapter) if self.setting.derive_state => Some(quote! {
+                    self.runtime().log_update_child(#tag, &#adapter(&value)).unwrap();
+                }),
+                _ => None,
+            };
+
             (
                 quote! {
+                    #deprecated
                     pub fn #setter_name(&mut self, value: #ty) -> &mut Self {
                         #reset_variant
+                        #log_update
                         #set_value
                         self
                     }
@@ -337,6 +761,144 @@ impl<'a> DeriveField<'a> {
         }
     }
 
+    pub fn getter(&self) -> TokenStream {
+        let getter_name = self.alias();
+        let ty = &self.ty;
+        let access = self.access();
+
+        quote! {
+            pub fn #getter_name(&self) -> &#ty {
+                &self.#access
+            }
+        }
+    }
+
+    /// Hands out the field behind a handle which logs whatever is changed through it.
+    pub fn mut_getter(&self, runtime: &Field) -> Option<TokenStream> {
+        if !self.is_state() {
+            return None;
+        }
+
+        let krate = self.setting.krate();
+        let mut_getter_name = format_ident!("{}_mut", self.alias());
+        let ty = &self.ty;
+        let access = self.access();
+        let runtime = runtime.access();
+        let tag = self.tag();
+
+        Some(quote! {
+            pub fn #mut_getter_name(&mut self) -> #krate::state::Mut<'_, #ty> {
+                #krate::state::Mut::new(&mut self.#access, &self.#runtime, #tag)
+            }
+        })
+    }
+
+    /// The setters and `*_mut` getter of the field, forwarded from `Mut` handles of the struct.
+    /// Returns their declarations in the handle trait and their implementations.
+    pub fn handle_methods(&self) -> (TokenStream, TokenStream) {
+        let krate = self.setting.krate();
+        let setter_name = self.alias_prefixed(format_ident!("set"));
+        let setter_with_name = format_ident!("{}_with", setter_name);
+        let mut_getter_name = format_ident!("{}_mut", self.alias());
+        let ty = &self.ty;
+        let guarded_mut = quote!(#krate::state::__private::GuardedMut::guarded_mut(self));
+
+        let deprecated = if self.attrs.deprecated {
+            Some(quote!(#[deprecated]))
+        } else {
+            None
+        };
+
+        let setter = quote!(fn #setter_name(&mut self, value: #ty) -> &mut Self);
+
+        if !self.is_state() {
+            return (
+                quote!(#deprecated #setter;),
+                quote! {
+                    #setter {
+                        #guarded_mut.#setter_name(value);
+                        self
+                    }
+                },
+            );
+        }
+
+        let setter_with = quote! {
+            fn #setter_with_name(
+                &mut self,
+                get_value: impl FnOnce(#krate::rt::Runtime) -> #ty,
+            ) -> &mut Self
+        };
+
+        let mut_getter = quote!(fn #mut_getter_name(&mut self) -> #krate::state::Mut<'_, #ty>);
+
+        (
+            quote! {
+                #deprecated #setter;
+                #deprecated #setter_with;
+                #mut_getter;
+            },
+            quote! {
+                #setter {
+                    #guarded_mut.#setter_name(value);
+                    self
+                }
+
+                #setter_with {
+                    #guarded_mut.#setter_with_name(get_value);
+                    self
+                }
+
+                #mut_getter {
+                    #guarded_mut.#mut_getter_name()
+                }
+            },
+        )
+    }
+
+    /// Hands out the field of `variant` behind a logged handle, if `variant` is the active one.
+    /// Unlike setters, this never switches variants, so only the field itself gets logged.
+    pub fn variant_mut_getter(
+        &self,
+        struct_name: &syn::Ident,
+        variant: &Variant,
+        runtime: &Field,
+    ) -> Option<TokenStream> {
+        if !self.is_state() {
+            return None;
+        }
+
+        let krate = self.setting.krate();
+        let mut_getter_name = format_ident!(
+            "{}_mut",
+            self.alias_prefixed(format_ident!("{}", variant.snake_case_name())),
+        );
+
+        let deprecated = if self.attrs.deprecated || variant.deprecated() {
+            Some(quote!(#[deprecated]))
+        } else {
+            None
+        };
+
+        let qual = variant.qual();
+        let ty = &self.ty;
+        let tag = self.tag();
+
+        let destructure = self.destructure(format_ident!("value"));
+        let runtime_destructure = runtime.destructure(format_ident!("runtime"));
+
+        Some(quote! {
+            #deprecated
+            pub fn #mut_getter_name(&mut self) -> Option<#krate::state::Mut<'_, #ty>> {
+                if let #struct_name #qual { #destructure, #runtime_destructure, .. } = self {
+                    Some(#krate::state::Mut::new(value, runtime, #tag))
+                } else {
+                    None
+                }
+            }
+        })
+    }
+
     pub fn eq(&self, is_variant: bool) -> Option<TokenStream> {
         if !self.attrs.no_eq_hash {
             let field = self.field(is_variant);
@@ -364,19 +926,61 @@ impl<'a> DeriveField<'a> {
     pub fn sizer(&self, is_variant: bool) -> TokenStream {
         let tag = self.tag();
         let field = self.field(is_variant);
-        quote! { size += #field.compute_size_nested(#tag, true).unwrap(); }
+        let omissible = self.omissible();
+
+        match &self.with_adapter {
+            Some(adapter) => quote! {
+                size += #adapter(&#field).compute_size_nested(#tag, #omissible).unwrap();
+            },
+
+            None => quote! { size += #field.compute_size_nested(#tag, #omissible).unwrap(); },
+        }
     }
 
     pub fn serializer(&self, is_variant: bool) -> TokenStream {
         let tag = self.tag();
         let field = self.field(is_variant);
-        quote! { #field.serialize_nested(#tag, true, writer)?; }
+        let omissible = self.omissible();
+
+        match &self.with_adapter {
+            Some(adapter) => {
+                quote! { #adapter(&#field).serialize_nested(#tag, #omissible, writer)?; }
+            }
+            None => quote! { #field.serialize_nested(#tag, #omissible, writer)?; },
+        }
     }
 
     pub fn merger(&self, is_variant: bool) -> TokenStream {
         let tag = self.tag();
         let field = self.field(is_variant);
-        quote! { #tag => #field.merge_nested(wire_type, reader)? }
+        let field_mut = self.field_mut(is_variant);
+
+        match &self.attrs.with {
+            Some(with) => quote! {
+                #tag => if wire_type == #with::TYPE.wire_type() {
+                    match wire_type {
+                        WireType::Varint => #with::merge(#field_mut, reader)?,
+                        WireType::Sized => #with::merge(#field_mut, &mut reader.nested()?)?,
+                    }
+                } else {
+                    reader.skip_field(wire_type)?
+                }
+            },
+
+            None => quote! { #tag => #field.merge_nested(wire_type, reader)? },
+        }
+    }
+
+    /// Fails with a `ValidationError` naming `struct_name.field` if the check of the field does.
+    pub fn validator(&self, struct_name: &syn::Ident) -> Option<TokenStream> {
+        let validate = self.attrs.validate.as_ref()?;
+        let krate = self.setting.krate();
+        let target = format!("{}.{}", struct_name, self.alias());
+        let field = self.field(false);
+
+        Some(quote! {
+            #krate::de::validate(#target, #validate(&#field))?;
+        })
     }
 
     pub fn runtime_setter(&self, is_variant: bool) -> Option<TokenStream> {
@@ -389,39 +993,136 @@ impl<'a> DeriveField<'a> {
         }
     }
 
+    pub fn serde_serializer(&self, is_variant: bool) -> TokenStream {
+        let name = self.serde_name();
+        let field = self.field(is_variant);
+
+        if is_variant {
+            quote! { serde_map.serialize_entry(#name, #field)?; }
+        } else {
+            quote! { serde_map.serialize_entry(#name, &#field)?; }
+        }
+    }
+
+    /// Deserialized values are placed under `runtime`, the one of the struct or variant.
+    pub fn serde_merger(&self, is_variant: bool, runtime: Option<&TokenStream>) -> TokenStream {
+        let name = self.serde_name();
+        let ty = &self.ty;
+        let field_mut = self.field_mut(is_variant);
+
+        let value = match runtime {
+            Some(runtime) if self.is_state() => {
+                let tag = self.tag();
+
+                quote! {{
+                    let mut value: #ty = serde_map.next_value()?;
+                    value.set_runtime(#runtime.nested(#tag));
+                    value
+                }}
+            }
+
+            _ => quote!(serde_map.next_value::<#ty>()?),
+        };
+
+        quote! {
+            #name => {
+                *#field_mut = #value;
+                Ok(true)
+            }
+        }
+    }
+
     pub fn replayer(&self, is_variant: bool) -> TokenStream {
-        let tag = self.attrs.tag;
+        let tag = self.tag();
         let field = self.field(is_variant);
 
         if self.is_state() {
             quote!(#tag => #field.handle(path, kind, key, reader))
+        } else if let (Some(with), Some(adapter)) = (&self.attrs.with, &self.with_adapter) {
+            let krate = self.setting.krate();
+            let ty = &self.ty;
+            let field_mut = self.field_mut(is_variant);
+            let default_value = self.default_value();
+
+            quote! {
+                #tag => match (path.next(), kind) {
+                    (None, LogEntryKind::Update) => {
+                        let target: &mut #ty = #field_mut;
+                        let mut value = #default_value;
+                        #with::merge(&mut value, reader)?;
+                        *target = value;
+                        Ok(())
+                    }
+
+                    (None, LogEntryKind::Checksum) => {
+                        let hash = #krate::state::state_hash(&#adapter(&#field));
+                        #krate::state::check_hash(hash, reader)
+                    }
+
+                    (None, kind) => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{:?} is not supported on fields with a codec", kind),
+                    )),
+
+                    (Some(tag), _) => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unexpected tag {} in a field with a codec", tag),
+                    )),
+                }
+            }
         } else {
             quote!(#tag => Ok(()))
         }
     }
 
     pub fn meta(&self) -> TokenStream {
-        let rust_name = self.alias().to_string();
-
-        let csharp_name = match &self.attrs.csharp_name {
-            Some(csharp_name) => quote!(Some(#csharp_name)),
-            None => quote!(None),
-        };
+        let name = derive::name_meta(
+            &self.alias().to_string(),
+            self.attrs.csharp_name.as_ref(),
+            self.attrs.doc.as_ref(),
+            &self.attrs.annotations,
+        );
 
         let type_meta = self.type_meta.as_ref().unwrap();
         let tag = self.tag();
+        let deprecated = self.attrs.deprecated;
 
         quote! {
             FieldMeta {
-                name: &NameMeta {
-                    rust: #rust_name,
-                    csharp: #csharp_name,
-                },
+                name: &#name,
                 ty: &#type_meta,
                 tag: #tag,
+                deprecated: #deprecated,
             }
         }
     }
+
+    pub fn meta_link(&self) -> TokenStream {
+        match &self.attrs.with {
+            Some(with) => quote! {
+                &MetaLink {
+                    r#type: #with::TYPE,
+                    msg: None,
+                    links: || &[],
+                }
+            },
+
+            None => {
+                let ty = self.ty();
+                quote!(<#ty>::LINK)
+            }
+        }
+    }
+}
+
+fn mentions_type_params(tokens: TokenStream, type_params: &[&syn::TypeParam]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => type_params
+            .iter()
+            .any(|type_param| type_param.ident == ident),
+        proc_macro2::TokenTree::Group(group) => mentions_type_params(group.stream(), type_params),
+        _ => false,
+    })
 }
 
 fn field_type_meta(
@@ -457,7 +1158,7 @@ fn field_type_meta(
     match ty {
         syn::Type::Paren(syn::TypeParen { elem, .. })
         | syn::Type::Reference(syn::TypeReference { elem, .. }) => {
-            field_type_meta(&ctx, &*elem, type_params)
+            field_type_meta(ctx, elem, type_params)
         }
 
         syn::Type::Array(syn::TypeArray { elem, .. }) => {
@@ -4,6 +4,8 @@ mod r#enum;
 mod field;
 mod r#struct;
 mod tag;
+mod transparent;
 mod variant;
+mod varint_enum;
 
 pub use derive::derive as do_it;
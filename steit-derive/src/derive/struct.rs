@@ -11,7 +11,7 @@ use crate::{
 
 use super::{
     derive::{self, DeriveSetting},
-    field::{DeriveField, Field},
+    field::{DeriveField, Field, FlattenedField, ParsedField, SkippedField},
     variant::Variant,
 };
 
@@ -23,7 +23,11 @@ struct StructAttrs {
     size_cache_renamed: Option<(String, TokenStream)>,
     runtime_renamed: Option<(String, TokenStream)>,
 
+    // A function checking the whole struct whenever it is decoded or replayed
+    validate: Option<(syn::Path, TokenStream)>,
+
     csharp_name: Option<String>,
+    annotations: Vec<(String, String)>,
 }
 
 impl StructAttrs {
@@ -35,7 +39,10 @@ impl StructAttrs {
         let mut size_cache_renamed = Attribute::new(ctx, "size_cache_renamed");
         let mut runtime_renamed = Attribute::new(ctx, "runtime_renamed");
 
+        let mut validate = Attribute::new(ctx, "validate");
+
         let mut csharp_name = Attribute::new(ctx, "csharp_name");
+        let mut annotations = VecAttribute::new(ctx, "meta");
 
         attrs.parse(ctx, true, |meta| match meta {
             syn::Meta::List(meta) if reserved_tags.parse_int_list(meta) => true,
@@ -46,7 +53,10 @@ impl StructAttrs {
             syn::Meta::NameValue(meta) if size_cache_renamed.parse_str(meta) => true,
             syn::Meta::NameValue(meta) if runtime_renamed.parse_str(meta) => true,
 
+            syn::Meta::NameValue(meta) if validate.parse_path_str(meta) => true,
+
             syn::Meta::NameValue(meta) if csharp_name.parse_str(meta) => true,
+            syn::Meta::List(meta) if annotations.parse_str_pairs(meta) => true,
 
             _ => false,
         });
@@ -59,7 +69,10 @@ impl StructAttrs {
             size_cache_renamed: size_cache_renamed.get_with_tokens(),
             runtime_renamed: runtime_renamed.get_with_tokens(),
 
+            validate: validate.get_with_tokens(),
+
             csharp_name: csharp_name.get(),
+            annotations: annotations.get(),
         }
     }
 }
@@ -70,6 +83,8 @@ pub struct Struct<'a> {
     attrs: StructAttrs,
     type_params: &'a [&'a syn::TypeParam],
     fields: Vec<DeriveField<'a>>,
+    skipped_fields: Vec<SkippedField>,
+    flattened_fields: Vec<FlattenedField>,
     size_cache: Option<Field>,
     runtime: Option<Field>,
     variant: Option<Variant<'a>>,
@@ -92,10 +107,17 @@ impl<'a> Struct<'a> {
         variant: Option<Variant<'a>>,
     ) -> derive::Result<Self> {
         let attrs = StructAttrs::parse(ctx, attrs);
-        let parsed_fields = parse_fields(ctx, setting, &attrs, type_params, fields)?;
+
+        if let (Some((_, validate_tokens)), Some(_)) = (&attrs.validate, &variant) {
+            ctx.error(validate_tokens, "`validate` is not supported on variants");
+            return Err(());
+        }
+
+        let (parsed_fields, skipped_fields, flattened_fields) =
+            parse_fields(ctx, setting, &attrs, type_params, fields, variant.as_ref())?;
 
         let krate = setting.krate();
-        let mut field_index = parsed_fields.len();
+        let mut field_index = fields.iter().len();
 
         let size_cache = if setting.has_size_cache && !attrs.no_size_cache {
             Some(add_field(
@@ -139,13 +161,15 @@ impl<'a> Struct<'a> {
             attrs,
             type_params,
             fields: parsed_fields,
+            skipped_fields,
+            flattened_fields,
             size_cache,
             runtime,
             variant,
         })
     }
 
-    pub fn variant(&self) -> Option<&Variant> {
+    pub fn variant(&self) -> Option<&Variant<'_>> {
         self.variant.as_ref()
     }
 
@@ -188,6 +212,12 @@ impl<'a> Struct<'a> {
         let name = self.impler.name();
         let qual = self.variant().map(|variant| variant.qual());
         let mut inits: Vec<_> = map_fields!(self, _.init_default()).collect();
+        inits.extend(self.skipped_fields.iter().map(SkippedField::init_default));
+        inits.extend(
+            self.flattened_fields
+                .iter()
+                .map(FlattenedField::init_default),
+        );
 
         if let Some(size_cache) = self.size_cache() {
             inits.push(size_cache.init(quote!(SizeCache::new())));
@@ -207,7 +237,13 @@ impl<'a> Struct<'a> {
             Default::default()
         };
 
+        let deprecated = match self.variant() {
+            Some(variant) if variant.deprecated() => Some(quote!(#[deprecated])),
+            _ => None,
+        };
+
         quote! {
+            #deprecated
             pub fn #ctor_name(#params) -> Self {
                 #set_variant_runtime
                 #name #qual { #(#inits,)* }
@@ -226,6 +262,20 @@ impl<'a> Struct<'a> {
         quote!(#(#setters)*)
     }
 
+    /// Only for variants, see `DeriveField::variant_mut_getter`.
+    pub fn variant_mut_getters(&self) -> TokenStream {
+        let name = self.impler.name();
+
+        let mut_getters: Vec<_> = match (self.variant(), self.runtime()) {
+            (Some(variant), Some(runtime)) => {
+                map_fields!(self, _.variant_mut_getter(name, variant, runtime)).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        quote!(#(#mut_getters)*)
+    }
+
     fn impl_setters(&self) -> TokenStream {
         self.impler.impl_with(
             self.trait_bounds(if self.variant.is_some() {
@@ -237,12 +287,102 @@ impl<'a> Struct<'a> {
         )
     }
 
+    fn impl_accessors(&self) -> TokenStream {
+        let getters = map_fields!(self, _.getter());
+        let mut_getters: Vec<_> = match self.runtime() {
+            Some(runtime) => map_fields!(self, _.mut_getter(runtime)).collect(),
+            None => Vec::new(),
+        };
+
+        let accessors = self.impler.impl_with(
+            self.trait_bounds(&[]),
+            quote! {
+                #(#getters)*
+                #(#mut_getters)*
+            },
+        );
+
+        // Every change to a guarded state is logged, so `Mut` can forward to its methods
+        let guarded = if self.setting.derive_state {
+            let krate = self.setting.krate();
+            let guarded = self
+                .impler
+                .impl_for_with("Guarded", self.trait_bounds(&[]), quote!());
+            Some(quote! {
+                use #krate::state::Guarded;
+                #guarded
+            })
+        } else {
+            None
+        };
+
+        let handle = self.handle_trait_name().map(|trait_name| {
+            let krate = self.setting.krate();
+            let name = self.impler.name();
+            let impls = map_fields!(self, _.handle_methods().1);
+
+            let mut generics = self.impler.bounded_generics(self.trait_bounds(&[]));
+            generics.params.insert(0, syn::parse_str("'a").unwrap());
+            let (impl_generics, _, where_clause) = generics.split_for_impl();
+            let (_, ty_generics, _) = self.impler.generics().split_for_impl();
+
+            quote! {
+                impl #impl_generics #trait_name #ty_generics
+                    for #krate::state::Mut<'a, #name #ty_generics> #where_clause
+                {
+                    #(#impls)*
+                }
+            }
+        });
+
+        quote! {
+            #accessors
+            #guarded
+            #handle
+        }
+    }
+
+    /// Only for guarded states with a runtime, see `handle_trait`.
+    fn handle_trait_name(&self) -> Option<syn::Ident> {
+        if self.setting.accessors && self.setting.derive_state && self.runtime().is_some() {
+            Some(format_ident!("{}Mut", self.impler.name()))
+        } else {
+            None
+        }
+    }
+
+    /// `Mut` doesn't hand out guarded states mutably, or whole values could be swapped in
+    /// unlogged, so their setters and `*_mut` getters are forwarded to the handle instead.
+    /// The trait carrying them goes next to the struct, where it can be imported.
+    pub fn handle_trait(&self, vis: &syn::Visibility) -> Option<TokenStream> {
+        let trait_name = self.handle_trait_name()?;
+        let decls = map_fields!(self, _.handle_methods().0);
+        let doc = format!(
+            "Logged changes to [`{}`] through its `Mut` handles.",
+            self.impler.name()
+        );
+
+        // Declared outside of the block the rest is derived in, so nothing is imported yet
+        let state = format!("{}::state::State", self.setting.krate());
+        let generics = self.impler.bounded_generics(&[&state]);
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        Some(quote! {
+            #[doc = #doc]
+            #vis trait #trait_name #impl_generics #where_clause {
+                #(#decls)*
+            }
+        })
+    }
+
     pub fn eq(&self) -> TokenStream {
         let is_variant = self.variant.is_some();
         let eqs = map_fields!(self, _.eq(is_variant));
+        let flattened_eqs = self.flattened_fields.iter().map(FlattenedField::eq);
 
         quote! {
             #(#eqs)*
+            #(#flattened_eqs)*
             true
         }
     }
@@ -283,7 +423,8 @@ impl<'a> Struct<'a> {
     pub fn hasher(&self) -> TokenStream {
         let is_variant = self.variant.is_some();
         let hashes = map_fields!(self, _.hash(is_variant));
-        quote!(#(#hashes)*)
+        let flattened_hashes = self.flattened_fields.iter().map(FlattenedField::hash);
+        quote!(#(#hashes)* #(#flattened_hashes)*)
     }
 
     fn impl_hash(&self) -> TokenStream {
@@ -312,13 +453,80 @@ impl<'a> Struct<'a> {
     pub fn sizer(&self) -> TokenStream {
         let is_variant = self.variant.is_some();
         let sizers = map_fields!(self, _.sizer(is_variant));
-        quote!(#(#sizers)*)
+        let flattened_sizers = self.flattened_fields.iter().map(FlattenedField::sizer);
+        quote!(#(#sizers)* #(#flattened_sizers)*)
     }
 
     pub fn serializer(&self) -> TokenStream {
         let is_variant = self.variant.is_some();
         let serializers = map_fields!(self, _.serializer(is_variant));
-        quote!(#(#serializers)*)
+        let flattened_serializers = self.flattened_fields.iter().map(FlattenedField::serializer);
+        let serializer = quote!(#(#serializers)* #(#flattened_serializers)*);
+
+        match self.canonical_serializer() {
+            Some(canonical_serializer) => quote! {
+                if writer.is_canonical() {
+                    #canonical_serializer
+                } else {
+                    #serializer
+                }
+            },
+
+            None => serializer,
+        }
+    }
+
+    /// Puts fields in ascending tag order for the canonical encoding, if they aren't already.
+    ///
+    /// Flattened fields may take any tags in between, so with them the order is only known
+    /// at runtime, when serializing field by field.
+    fn canonical_serializer(&self) -> Option<TokenStream> {
+        let is_variant = self.variant.is_some();
+        let mut serializers: Vec<_> = map_fields!(self, _.tag())
+            .zip(map_fields!(self, _.serializer(is_variant)))
+            .collect();
+
+        if self.flattened_fields.is_empty() {
+            if serializers.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+                return None;
+            }
+
+            serializers.sort_by_key(|(tag, _)| *tag);
+            let serializers = serializers.into_iter().map(|(_, serializer)| serializer);
+            return Some(quote!(#(#serializers)*));
+        }
+
+        let tags = self.tags();
+        let field_serializers = self.field_serializers(quote!(continue;));
+        let serializers = serializers
+            .into_iter()
+            .map(|(tag, serializer)| quote!(#tag => { #serializer }));
+
+        Some(quote! {
+            let mut field_numbers = #tags.to_vec();
+            field_numbers.sort_unstable();
+
+            for field_number in field_numbers {
+                match field_number {
+                    #(#serializers)*
+                    _ => { #field_serializers }
+                }
+            }
+        })
+    }
+
+    fn field_serializers(&self, on_serialized: TokenStream) -> TokenStream {
+        let field_serializers = self
+            .flattened_fields
+            .iter()
+            .map(|field| field.field_serializer(&on_serialized));
+
+        quote!(#(#field_serializers)*)
+    }
+
+    pub fn with_adapters(&self) -> TokenStream {
+        let with_adapters = map_fields!(self, _.with_adapter());
+        quote!(#(#with_adapters)*)
     }
 
     fn impl_serialize(&self) -> TokenStream {
@@ -332,6 +540,16 @@ impl<'a> Struct<'a> {
             quote!(None)
         };
 
+        let change_stamp = self.runtime().map(|runtime| {
+            let runtime = runtime.field(false);
+
+            quote! {
+                fn change_stamp(&self) -> Option<u64> {
+                    Some(#runtime.change_stamp())
+                }
+            }
+        });
+
         self.impler.impl_for(
             "Serialize",
             quote! {
@@ -341,7 +559,7 @@ impl<'a> Struct<'a> {
                     size
                 }
 
-                fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+                fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
                     #serializer
                     Ok(())
                 }
@@ -349,6 +567,8 @@ impl<'a> Struct<'a> {
                 fn size_cache(&self) -> Option<&SizeCache> {
                     #size_cache
                 }
+
+                #change_stamp
             },
         )
     }
@@ -356,6 +576,7 @@ impl<'a> Struct<'a> {
     pub fn merger(&self) -> TokenStream {
         let is_variant = self.variant.is_some();
         let mergers = map_fields!(self, _.merger(is_variant));
+        let flattened_mergers = self.flattened_mergers(quote!(continue;));
 
         quote! {
             while !reader.eof()? {
@@ -363,41 +584,230 @@ impl<'a> Struct<'a> {
 
                 match field_number {
                     #(#mergers,)*
-                    _ => reader.skip_field(wire_type)?,
+
+                    _ => {
+                        #flattened_mergers
+                        reader.skip_field(wire_type)?
+                    }
                 }
             }
         }
     }
 
+    fn flattened_mergers(&self, on_merged: TokenStream) -> TokenStream {
+        let mergers = self
+            .flattened_fields
+            .iter()
+            .map(|field| field.merger(&on_merged));
+
+        quote!(#(#mergers)*)
+    }
+
+    /// Runs the checks of the fields, of flattened structs and then of the struct itself.
+    fn validator(&self) -> Option<TokenStream> {
+        let name = self.impler.name();
+        let mut validators: Vec<_> = map_fields!(self, _.validator(name)).flatten().collect();
+        validators.extend(self.flattened_fields.iter().map(FlattenedField::validator));
+
+        if let Some((validate, _)) = &self.attrs.validate {
+            let krate = self.setting.krate();
+            let target = name.to_string();
+            validators.push(quote! { #krate::de::validate(#target, #validate(self))?; });
+        }
+
+        if validators.is_empty() {
+            None
+        } else {
+            Some(quote!(#(#validators)*))
+        }
+    }
+
     fn impl_deserialize(&self) -> TokenStream {
         let merger = self.merger();
+        let validator = self.validator();
+
+        let touch = self.runtime().map(|runtime| {
+            let runtime = runtime.field(false);
+            quote! { #runtime.touch(); }
+        });
 
         self.impler.impl_for(
             "Deserialize",
             quote! {
                 fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+                    #touch
                     #merger
+                    #validator
                     Ok(())
                 }
             },
         )
     }
 
+    /// Bounds of the serde impls, the usual ones plus `serde_bound`.
+    fn serde_bounds(&self, fallback: &'static [&str], serde_bound: &'static str) -> Vec<&str> {
+        let mut bounds = self.trait_bounds(fallback).to_vec();
+        bounds.push(serde_bound);
+        bounds
+    }
+
+    pub fn serde_serializer(&self) -> TokenStream {
+        let is_variant = self.variant.is_some();
+        let serializers = map_fields!(self, _.serde_serializer(is_variant));
+        let flattened_serializers = self
+            .flattened_fields
+            .iter()
+            .map(FlattenedField::serde_serializer);
+
+        quote!(#(#serializers)* #(#flattened_serializers)*)
+    }
+
+    /// Evaluates to whether the field called `name` has been read.
+    pub fn serde_merger(&self) -> TokenStream {
+        let is_variant = self.variant.is_some();
+
+        let runtime = self.runtime().map(|runtime| {
+            if is_variant {
+                quote!(self_runtime)
+            } else {
+                runtime.field(false)
+            }
+        });
+
+        let mergers = map_fields!(self, _.serde_merger(is_variant, runtime.as_ref()));
+        let flattened_mergers = self
+            .flattened_fields
+            .iter()
+            .map(FlattenedField::serde_merger);
+
+        quote! {
+            match field_name {
+                #(#mergers)*
+
+                _ => {
+                    #(#flattened_mergers)*
+                    Ok(false)
+                }
+            }
+        }
+    }
+
+    fn impl_serde_serialize(&self) -> TokenStream {
+        let serializer = self.serde_serializer();
+
+        let serialize_fields = self.impler.impl_for_with(
+            "serde_bridge::SerializeFields",
+            &self.serde_bounds(&[], "serde::Serialize"),
+            quote! {
+                fn serialize_fields<SerdeMap: serde::ser::SerializeMap>(
+                    &self,
+                    _in_variant: bool,
+                    serde_map: &mut SerdeMap,
+                ) -> Result<(), SerdeMap::Error> {
+                    #serializer
+                    Ok(())
+                }
+            },
+        );
+
+        let serialize = self.impler.impl_for_with(
+            "serde::Serialize",
+            &self.serde_bounds(&[], "serde::Serialize"),
+            quote! {
+                fn serialize<SerdeSerializer: serde::Serializer>(
+                    &self,
+                    serializer: SerdeSerializer,
+                ) -> Result<SerdeSerializer::Ok, SerdeSerializer::Error> {
+                    serde_bridge::serialize_fields(self, serializer)
+                }
+            },
+        );
+
+        quote! {
+            #serialize_fields
+            #serialize
+        }
+    }
+
+    fn impl_serde_deserialize(&self) -> TokenStream {
+        let name = self.impler.name().to_string();
+        let merger = self.serde_merger();
+
+        let validate_fields = self.validator().map(|validator| {
+            quote! {
+                fn validate_fields(&self) -> io::Result<()> {
+                    #validator
+                    Ok(())
+                }
+            }
+        });
+
+        let merge_fields = self.impler.impl_for_with(
+            "serde_bridge::MergeFields",
+            &self.serde_bounds(&[], "serde::de::DeserializeOwned"),
+            quote! {
+                const NAME: &'static str = #name;
+
+                fn merge_field<'de, SerdeAccess: serde::de::MapAccess<'de>>(
+                    &mut self,
+                    _in_variant: bool,
+                    field_name: &str,
+                    serde_map: &mut SerdeAccess,
+                ) -> Result<bool, SerdeAccess::Error> {
+                    #merger
+                }
+
+                #validate_fields
+            },
+        );
+
+        let deserialize = self.impler.impl_for_with_lifetime(
+            Some("'de"),
+            "serde::Deserialize<'de>",
+            &self.serde_bounds(&["Default"], "serde::de::DeserializeOwned"),
+            quote! {
+                fn deserialize<SerdeDeserializer: serde::Deserializer<'de>>(
+                    deserializer: SerdeDeserializer,
+                ) -> Result<Self, SerdeDeserializer::Error> {
+                    let mut value = Self::default();
+                    serde_bridge::merge_fields(deserializer, &mut value)?;
+                    Ok(value)
+                }
+            },
+        );
+
+        quote! {
+            #merge_fields
+            #deserialize
+        }
+    }
+
     pub fn runtime_setter(&self) -> TokenStream {
         let is_variant = self.variant.is_some();
-        let runtime_setters = map_fields!(self, _.runtime_setter(is_variant));
+        let mut runtime_setters: Vec<_> = map_fields!(self, _.runtime_setter(is_variant))
+            .flatten()
+            .collect();
+        runtime_setters.extend(
+            self.flattened_fields
+                .iter()
+                .map(FlattenedField::runtime_setter),
+        );
 
         if is_variant {
             quote! {
-                #(#runtime_setters)*
-                *self_runtime = runtime;
+                if let Err(runtime) = self_runtime.relocate(runtime) {
+                    #(#runtime_setters)*
+                    *self_runtime = runtime;
+                }
             }
         } else {
             let runtime = self.runtime().unwrap().access();
 
             quote! {
-                #(#runtime_setters)*
-                self.#runtime = runtime;
+                if let Err(runtime) = self.#runtime.relocate(runtime) {
+                    #(#runtime_setters)*
+                    self.#runtime = runtime;
+                }
             }
         }
     }
@@ -405,17 +815,23 @@ impl<'a> Struct<'a> {
     pub fn replayer(&self) -> TokenStream {
         let name = self.impler.name().to_token_stream().to_string();
         let is_variant = self.variant.is_some();
-        let replayers = map_fields!(self, _.replayer(is_variant));
+        let mut replayers: Vec<_> = map_fields!(self, _.replayer(is_variant)).collect();
+        replayers.extend(self.flattened_fields.iter().map(FlattenedField::replayer));
 
-        let update = if is_variant {
-            quote! {
+        let (update, checksum) = if is_variant {
+            let error = quote! {
                 Err(io::Error::new(
                     io::ErrorKind::InvalidData,
-                    "`LogEntryKind::Update` is not supported on variants but their enums",
+                    format!("{:?} is not supported on variants but their enums", kind),
                 ))
-            }
+            };
+
+            (error.clone(), error)
         } else {
-            quote!(self.handle_update(reader))
+            (
+                quote!(self.handle_update(reader)),
+                quote!(self.handle_checksum(reader)),
+            )
         };
 
         quote! {
@@ -431,6 +847,7 @@ impl<'a> Struct<'a> {
             } else {
                 match kind {
                     LogEntryKind::Update => #update,
+                    LogEntryKind::Checksum => #checksum,
 
                     _ => Err(io::Error::new(
                         io::ErrorKind::InvalidData,
@@ -445,7 +862,23 @@ impl<'a> Struct<'a> {
         let ctor_name = self.ctor_name();
         let runtime = self.runtime().unwrap().field(false);
         let runtime_setter = self.runtime_setter();
-        let replayer = self.replayer();
+
+        // Changes anywhere below may break invariants of the struct as a whole.
+        // They are checked once applied and stay so on failure, see `ValidationError`.
+        let replayer = match self.validator() {
+            Some(validator) => {
+                let replayer = self.replayer();
+
+                quote! {
+                    let result: io::Result<()> = #replayer;
+                    result?;
+                    #validator
+                    Ok(())
+                }
+            }
+
+            None => self.replayer(),
+        };
 
         self.impler.impl_for(
             "State",
@@ -469,24 +902,35 @@ impl<'a> Struct<'a> {
                     key: Option<u32>,
                     reader: &mut Reader<impl io::Read>,
                 ) -> io::Result<()> {
+                    #runtime.touch();
                     #replayer
                 }
             },
         )
     }
 
-    pub fn meta(&self) -> TokenStream {
-        let rust_name = match &self.variant {
-            Some(variant) => variant.name().to_string(),
-            None => self.impler.name().to_string(),
-        };
-
-        let csharp_name = match &self.attrs.csharp_name {
-            Some(csharp_name) => quote!(Some(#csharp_name)),
-            None => quote!(None),
-        };
+    fn name_meta(&self) -> TokenStream {
+        match &self.variant {
+            Some(variant) => derive::name_meta(
+                &variant.name().to_string(),
+                self.attrs.csharp_name.as_ref(),
+                variant.doc(),
+                variant.annotations(),
+            ),
+
+            None => derive::name_meta(
+                &self.impler.name().to_string(),
+                self.attrs.csharp_name.as_ref(),
+                self.setting.doc.as_ref(),
+                &self.attrs.annotations,
+            ),
+        }
+    }
 
-        let fields = map_fields!(self, _.meta());
+    pub fn meta(&self) -> TokenStream {
+        let name = self.name_meta();
+        let fields = self.fields_meta();
+        let reserved_tags = &self.attrs.reserved_tags;
         let builtin = self.setting.steit_owned;
 
         let type_params = if self.variant.is_none() {
@@ -502,56 +946,174 @@ impl<'a> Struct<'a> {
 
         quote! {
             StructMeta {
-                name: &NameMeta {
-                    rust: #rust_name,
-                    csharp: #csharp_name,
-                },
+                name: &#name,
+                module: module_path!(),
                 type_params: &[#type_params],
-                fields: &[#(#fields,)*],
+                fields: #fields,
+                reserved_tags: &[#(#reserved_tags,)*],
                 builtin: #builtin,
             }
         }
     }
 
-    pub fn meta_links(&self) -> TokenStream {
-        let links = self.fields.iter().map(|field| {
-            let ty = field.ty();
-            quote!(<#ty>::LINK)
-        });
+    /// Evaluates to a `&'static [#item_ty]` of `own` items,
+    /// followed by those which `flattened` gives for every flattened field.
+    fn with_flattened(
+        &self,
+        item_ty: TokenStream,
+        own: Vec<TokenStream>,
+        flattened: impl Fn(&FlattenedField) -> TokenStream,
+    ) -> TokenStream {
+        if self.flattened_fields.is_empty() {
+            return quote!(&[#(#own,)*]);
+        }
+
+        let krate = self.setting.krate();
+        let own_len = own.len();
+        let flattened: Vec<_> = self.flattened_fields.iter().map(flattened).collect();
+
+        quote! {{
+            const LEN: usize = #own_len #(+ #flattened.len())*;
+            const ITEMS: [#item_ty; LEN] =
+                #krate::state::concat(&[&[#(#own,)*], #(#flattened,)*]);
+            &ITEMS
+        }}
+    }
+
+    fn tags(&self) -> TokenStream {
+        let tags = map_fields!(self, _.tag()).map(|tag| quote!(#tag)).collect();
+
+        self.with_flattened(quote!(u32), tags, FlattenedField::tags)
+    }
+
+    fn fields_meta(&self) -> TokenStream {
+        self.with_flattened(
+            quote!(FieldMeta),
+            map_fields!(self, _.meta()).collect(),
+            FlattenedField::fields_meta,
+        )
+    }
+
+    fn links(&self) -> TokenStream {
+        self.with_flattened(
+            quote!(&'static MetaLink),
+            map_fields!(self, _.meta_link()).collect(),
+            FlattenedField::meta_links,
+        )
+    }
+
+    /// Flattened fields are checked against each other and the rest
+    /// only once the tags of their own fields are known, i.e. at compile time.
+    fn check_flattened_tags(&self) -> Option<TokenStream> {
+        if self.flattened_fields.is_empty() {
+            return None;
+        }
+
+        let krate = self.setting.krate();
+        let tags = self.tags();
+        let reserved_tags = &self.attrs.reserved_tags;
+
+        Some(quote! {
+            const _: () = #krate::state::check_flattened_tags(#tags, &[#(#reserved_tags,)*]);
+        })
+    }
+
+    fn impl_flatten(&self) -> TokenStream {
+        let krate = self.setting.krate();
+        let tags = self.tags();
+        let fields = self.fields_meta();
+        let links = self.links();
+        let mergers = map_fields!(self, _.merger(false));
+        let flattened_mergers = self.flattened_mergers(quote!(return Ok(true);));
+        let field_serializers = map_fields!(self, _.tag())
+            .zip(map_fields!(self, _.serializer(false)))
+            .map(|(tag, serializer)| quote!(#tag => { #serializer }));
+        let flattened_field_serializers = self.field_serializers(quote!(return Ok(true);));
+        let validator = self.validator();
+
+        let flatten = self.impler.impl_for_with(
+            "Flatten",
+            self.trait_bounds(&[]),
+            quote! {
+                const TAGS: &'static [u32] = #tags;
+                const FIELDS: &'static [FieldMeta] = #fields;
+                const LINKS: &'static [&'static MetaLink] = #links;
+
+                fn merge_field(
+                    &mut self,
+                    field_number: u32,
+                    wire_type: WireType,
+                    reader: &mut Reader<impl io::Read>,
+                ) -> io::Result<bool> {
+                    match field_number {
+                        #(#mergers,)*
+
+                        _ => {
+                            #flattened_mergers
+                            return Ok(false);
+                        }
+                    }
+
+                    Ok(true)
+                }
 
+                fn serialize_field(
+                    &self,
+                    field_number: u32,
+                    writer: &mut Writer<impl io::Write>,
+                ) -> io::Result<bool> {
+                    match field_number {
+                        #(#field_serializers)*
+
+                        _ => {
+                            #flattened_field_serializers
+                            return Ok(false);
+                        }
+                    }
+
+                    Ok(true)
+                }
+
+                fn validate(&self) -> io::Result<()> {
+                    #validator
+                    Ok(())
+                }
+            },
+        );
+
+        quote! {
+            use #krate::state::Flatten;
+            #flatten
+        }
+    }
+
+    pub fn meta_links(&self) -> TokenStream {
+        let links = map_fields!(self, _.meta_link());
         quote!(#(#links,)*)
     }
 
     fn impl_meta(&self) -> TokenStream {
         let meta = self.meta();
-        let rust_name = self.impler.name().to_string();
-
-        let csharp_name = match &self.attrs.csharp_name {
-            Some(csharp_name) => quote!(Some(#csharp_name)),
-            None => quote!(None),
-        };
+        let name = self.name_meta();
 
         let param_meta_list = self.type_params.iter().map(|param| {
             let name = &param.ident;
             quote!(FieldTypeMeta::Type(#name::TYPE))
         });
 
-        let links = self.meta_links();
+        let links = self.links();
 
         self.impler.impl_for(
             "HasMeta",
             quote! {
-                const NAME: &'static NameMeta = &NameMeta {
-                    rust: #rust_name,
-                    csharp: #csharp_name,
-                };
+                const NAME: &'static NameMeta = &#name;
 
                 const TYPE: &'static TypeMeta = &TypeMeta::Ref(Self::NAME, &[#(#param_meta_list,)*]);
 
                 const LINK: &'static MetaLink = &MetaLink {
                     r#type: Self::TYPE,
                     msg: Some(MessageMeta::Struct(#meta)),
-                    links: || &[#links],
+                    links: || #links,
                 };
             },
         )
@@ -564,32 +1126,44 @@ fn parse_fields<'a>(
     attrs: &StructAttrs,
     type_params: &'a [&'a syn::TypeParam],
     fields: &mut syn::Fields,
-) -> derive::Result<Vec<DeriveField<'a>>> {
-    let mut parsed_fields = Vec::with_capacity(fields.iter().len());
+    variant: Option<&Variant>,
+) -> derive::Result<(Vec<DeriveField<'a>>, Vec<SkippedField>, Vec<FlattenedField>)> {
+    let field_count = fields.iter().len();
+    let mut parsed_fields = Vec::with_capacity(field_count);
+    let mut skipped_fields = Vec::new();
+    let mut flattened_fields = Vec::new();
 
     let reserved_tags: HashSet<_> = attrs.reserved_tags.iter().collect();
     let mut tags = HashSet::new();
     let mut unique_tags = true;
 
     for (index, field) in fields.iter_mut().enumerate() {
-        if let Ok(parsed_field) = DeriveField::parse(ctx, setting, type_params, field, index) {
-            let (tag, tag_tokens) = parsed_field.tag_with_tokens();
+        match DeriveField::parse(ctx, setting, type_params, field, index, variant) {
+            Ok(ParsedField::Derive(parsed_field)) => {
+                let (tag, tag_tokens) = parsed_field.tag_with_tokens();
 
-            if reserved_tags.contains(&tag) {
-                ctx.error(tag_tokens, format!("tag {} has been reserved", tag));
-            }
+                if reserved_tags.contains(&tag) {
+                    ctx.error(tag_tokens, format!("tag {} has been reserved", tag));
+                }
+
+                if !tags.insert(tag) {
+                    ctx.error(tag_tokens, format!("duplicate tag {}", tag));
+                    unique_tags = false;
+                }
 
-            if !tags.insert(tag) {
-                ctx.error(tag_tokens, format!("duplicate tag {}", tag));
-                unique_tags = false;
+                parsed_fields.push(parsed_field);
             }
 
-            parsed_fields.push(parsed_field);
+            Ok(ParsedField::Skipped(skipped_field)) => skipped_fields.push(skipped_field),
+            Ok(ParsedField::Flattened(flattened_field)) => flattened_fields.push(flattened_field),
+            Err(()) => (),
         }
     }
 
-    if parsed_fields.len() == parsed_fields.capacity() && unique_tags {
-        Ok(parsed_fields)
+    let parsed_count = parsed_fields.len() + skipped_fields.len() + flattened_fields.len();
+
+    if parsed_count == field_count && unique_tags {
+        Ok((parsed_fields, skipped_fields, flattened_fields))
     } else {
         Err(())
     }
@@ -627,6 +1201,10 @@ impl<'a> ToTokens for Struct<'a> {
             tokens.extend(self.impl_setters());
         }
 
+        if self.setting.accessors {
+            tokens.extend(self.impl_accessors());
+        }
+
         if self.setting.derive_partial_eq {
             tokens.extend(self.impl_partial_eq());
         }
@@ -644,6 +1222,7 @@ impl<'a> ToTokens for Struct<'a> {
         }
 
         if self.setting.derive_serialize {
+            tokens.extend(self.with_adapters());
             tokens.extend(self.impl_serialize());
         }
 
@@ -655,8 +1234,22 @@ impl<'a> ToTokens for Struct<'a> {
             tokens.extend(self.impl_state());
         }
 
+        if self.setting.derive_serde && self.setting.derive_serialize {
+            tokens.extend(self.impl_serde_serialize());
+        }
+
+        if self.setting.derive_serde && self.setting.derive_deserialize {
+            tokens.extend(self.impl_serde_deserialize());
+        }
+
         if self.setting.derive_meta {
             tokens.extend(self.impl_meta());
         }
+
+        if self.setting.derive_state && self.setting.derive_meta {
+            tokens.extend(self.impl_flatten());
+        }
+
+        tokens.extend(self.check_flattened_tags());
     }
 }
@@ -0,0 +1,315 @@
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+
+use crate::{attr::AttributeParse, ctx::Context, r#impl::Implementer};
+
+use super::{
+    derive::{self, DeriveSetting},
+    field::Field,
+};
+
+/// A single-field struct which is encoded, replayed and described exactly like its inner value.
+pub struct Transparent<'a> {
+    impler: &'a Implementer<'a>,
+    setting: &'a DeriveSetting,
+    field: Field,
+}
+
+impl<'a> Transparent<'a> {
+    pub fn parse(
+        ctx: &'a Context,
+        impler: &'a Implementer,
+        setting: &'a DeriveSetting,
+        attrs: impl AttributeParse,
+        fields: &mut syn::Fields,
+    ) -> derive::Result<Self> {
+        attrs.parse(ctx, true, |_| false);
+
+        if fields.iter().len() != 1 {
+            ctx.error(
+                &*fields,
+                "`transparent` requires a struct with exactly one field",
+            );
+
+            return Err(());
+        }
+
+        let field = fields.iter_mut().next().unwrap();
+        (&mut field.attrs).parse(ctx, true, |_| false);
+
+        Ok(Self {
+            impler,
+            setting,
+            field: Field::from_field(field, 0),
+        })
+    }
+
+    fn trait_bounds(&self, fallback: &'static [&str]) -> &[&str] {
+        if self.setting.derive_state {
+            &["State"]
+        } else {
+            fallback
+        }
+    }
+
+    fn impl_ctor(&self) -> TokenStream {
+        let ctor_name = format_ident!("{}", &self.setting.ctor_prefix);
+        let name = self.impler.name();
+
+        let (params, init) = if self.setting.derive_state {
+            (
+                Some(quote!(runtime: Runtime)),
+                self.field.init(quote!(State::with_runtime(runtime))),
+            )
+        } else {
+            (None, self.field.init(quote!(Default::default())))
+        };
+
+        self.impler.impl_with(
+            self.trait_bounds(&["Default"]),
+            quote! {
+                pub fn #ctor_name(#params) -> Self {
+                    #name { #init }
+                }
+            },
+        )
+    }
+
+    fn impl_partial_eq(&self) -> TokenStream {
+        let access = self.field.access();
+
+        self.impler.impl_for(
+            "PartialEq",
+            quote! {
+                fn eq(&self, other: &Self) -> bool {
+                    self.#access.eq(&other.#access)
+                }
+            },
+        )
+    }
+
+    fn impl_default(&self) -> TokenStream {
+        let ctor_name = format_ident!("{}", &self.setting.ctor_prefix);
+
+        let args = if self.setting.derive_state {
+            Some(quote!(Runtime::default()))
+        } else {
+            None
+        };
+
+        self.impler.impl_for_with(
+            "Default",
+            self.trait_bounds(&["Default"]),
+            quote! {
+                fn default() -> Self {
+                    Self::#ctor_name(#args)
+                }
+            },
+        )
+    }
+
+    fn impl_hash(&self) -> TokenStream {
+        let access = self.field.access();
+
+        self.impler.impl_for(
+            "Hash",
+            quote! {
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    self.#access.hash(state);
+                }
+            },
+        )
+    }
+
+    fn impl_wire_type(&self) -> TokenStream {
+        let ty = self.field.ty();
+
+        self.impler.impl_for(
+            "HasWireType",
+            quote! {
+                const WIRE_TYPE: WireType = <#ty as HasWireType>::WIRE_TYPE;
+            },
+        )
+    }
+
+    fn impl_serialize(&self) -> TokenStream {
+        let access = self.field.access();
+
+        self.impler.impl_for(
+            "Serialize",
+            quote! {
+                fn compute_size(&self) -> u32 {
+                    self.#access.compute_size()
+                }
+
+                fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
+                    self.#access.serialize_cached(writer)
+                }
+
+                fn size_cache(&self) -> Option<&SizeCache> {
+                    self.#access.size_cache()
+                }
+
+                fn change_stamp(&self) -> Option<u64> {
+                    self.#access.change_stamp()
+                }
+
+                fn is_omissible(&self) -> bool {
+                    self.#access.is_omissible()
+                }
+            },
+        )
+    }
+
+    fn impl_deserialize(&self) -> TokenStream {
+        let access = self.field.access();
+
+        self.impler.impl_for(
+            "Deserialize",
+            quote! {
+                fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+                    self.#access.merge(reader)
+                }
+            },
+        )
+    }
+
+    fn impl_state(&self) -> TokenStream {
+        let ctor_name = format_ident!("{}", &self.setting.ctor_prefix);
+        let access = self.field.access();
+
+        self.impler.impl_for(
+            "State",
+            quote! {
+                fn with_runtime(runtime: Runtime) -> Self {
+                    Self::#ctor_name(runtime)
+                }
+
+                fn runtime(&self) -> &Runtime {
+                    self.#access.runtime()
+                }
+
+                fn set_runtime(&mut self, runtime: Runtime) {
+                    self.#access.set_runtime(runtime);
+                }
+
+                fn handle(
+                    &mut self,
+                    path: impl Iterator<Item = u32>,
+                    kind: LogEntryKind,
+                    key: Option<u32>,
+                    reader: &mut Reader<impl io::Read>,
+                ) -> io::Result<()> {
+                    self.#access.handle(path, kind, key, reader)
+                }
+
+                fn handle_update(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+                    self.#access.handle_update(reader)
+                }
+            },
+        )
+    }
+
+    fn impl_serde_serialize(&self) -> TokenStream {
+        let access = self.field.access();
+        let mut bounds = self.trait_bounds(&[]).to_vec();
+        bounds.push("serde::Serialize");
+
+        self.impler.impl_for_with(
+            "serde::Serialize",
+            &bounds,
+            quote! {
+                fn serialize<SerdeSerializer: serde::Serializer>(
+                    &self,
+                    serializer: SerdeSerializer,
+                ) -> Result<SerdeSerializer::Ok, SerdeSerializer::Error> {
+                    serde::Serialize::serialize(&self.#access, serializer)
+                }
+            },
+        )
+    }
+
+    fn impl_serde_deserialize(&self) -> TokenStream {
+        let name = self.impler.name();
+        let ty = self.field.ty();
+        let init = self.field.init(quote!(inner));
+        let mut bounds = self.trait_bounds(&[]).to_vec();
+        bounds.push("serde::de::DeserializeOwned");
+
+        self.impler.impl_for_with_lifetime(
+            Some("'de"),
+            "serde::Deserialize<'de>",
+            &bounds,
+            quote! {
+                fn deserialize<SerdeDeserializer: serde::Deserializer<'de>>(
+                    deserializer: SerdeDeserializer,
+                ) -> Result<Self, SerdeDeserializer::Error> {
+                    let inner = <#ty as serde::Deserialize>::deserialize(deserializer)?;
+                    Ok(#name { #init })
+                }
+            },
+        )
+    }
+
+    fn impl_meta(&self) -> TokenStream {
+        let ty = self.field.ty();
+
+        // Generated code sees the inner type only, so there is no message of our own
+        self.impler.impl_for(
+            "HasMeta",
+            quote! {
+                const NAME: &'static NameMeta = <#ty as HasMeta>::NAME;
+                const TYPE: &'static TypeMeta = <#ty as HasMeta>::TYPE;
+                const LINK: &'static MetaLink = <#ty as HasMeta>::LINK;
+            },
+        )
+    }
+}
+
+impl<'a> ToTokens for Transparent<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        if self.setting.derive_ctors {
+            tokens.extend(self.impl_ctor());
+        }
+
+        if self.setting.derive_partial_eq {
+            tokens.extend(self.impl_partial_eq());
+        }
+
+        if self.setting.derive_default {
+            tokens.extend(self.impl_default());
+        }
+
+        if self.setting.derive_hash {
+            tokens.extend(self.impl_hash());
+        }
+
+        if self.setting.derive_wire_type {
+            tokens.extend(self.impl_wire_type());
+        }
+
+        if self.setting.derive_serialize {
+            tokens.extend(self.impl_serialize());
+        }
+
+        if self.setting.derive_deserialize {
+            tokens.extend(self.impl_deserialize());
+        }
+
+        if self.setting.derive_state {
+            tokens.extend(self.impl_state());
+        }
+
+        if self.setting.derive_serde && self.setting.derive_serialize {
+            tokens.extend(self.impl_serde_serialize());
+        }
+
+        if self.setting.derive_serde && self.setting.derive_deserialize {
+            tokens.extend(self.impl_serde_deserialize());
+        }
+
+        if self.setting.derive_meta {
+            tokens.extend(self.impl_meta());
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use proc_macro2::TokenStream;
 
 use crate::{
-    attr::{Attribute, AttributeParse},
+    attr::{self, Attribute, AttributeParse, VecAttribute},
     ctx::Context,
     str_util,
 };
@@ -11,6 +11,9 @@ use super::{derive, derive::DeriveSetting, tag};
 struct VariantAttrs {
     tag: u32,
     tag_tokens: TokenStream,
+    deprecated: bool,
+    doc: Option<String>,
+    annotations: Vec<(String, String)>,
 }
 
 impl VariantAttrs {
@@ -19,9 +22,19 @@ impl VariantAttrs {
         variant: &mut syn::Variant,
     ) -> derive::Result<(Self, syn::AttributeArgs)> {
         let mut tag = Attribute::new(ctx, "tag");
+        let mut deprecated = Attribute::new(ctx, "deprecated");
+        let mut annotations = VecAttribute::new(ctx, "meta");
+
+        let doc = attr::parse_doc(&variant.attrs);
 
         let unknown_attrs = (&mut variant.attrs).parse(ctx, false, |meta| match meta {
             syn::Meta::NameValue(meta) if tag.parse_int(meta) => true,
+
+            syn::Meta::Path(path) if deprecated.parse_path(path) => true,
+            syn::Meta::NameValue(meta) if deprecated.parse_bool(meta) => true,
+
+            syn::Meta::List(meta) if annotations.parse_str_pairs(meta) => true,
+
             _ => false,
         });
 
@@ -33,7 +46,16 @@ impl VariantAttrs {
             ctx.error(&tag_tokens, message);
         })?;
 
-        Ok((Self { tag, tag_tokens }, unknown_attrs))
+        Ok((
+            Self {
+                tag,
+                tag_tokens,
+                deprecated: deprecated.get().unwrap_or_default(),
+                doc,
+                annotations: annotations.get(),
+            },
+            unknown_attrs,
+        ))
     }
 }
 
@@ -73,6 +95,18 @@ impl<'a> Variant<'a> {
         (self.attrs.tag, &self.attrs.tag_tokens)
     }
 
+    pub fn deprecated(&self) -> bool {
+        self.attrs.deprecated
+    }
+
+    pub fn doc(&self) -> Option<&String> {
+        self.attrs.doc.as_ref()
+    }
+
+    pub fn annotations(&self) -> &[(String, String)] {
+        &self.attrs.annotations
+    }
+
     pub fn snake_case_name(&self) -> String {
         str_util::to_snake_case(self.name.to_string())
     }
@@ -0,0 +1,481 @@
+use std::collections::HashSet;
+
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+
+use crate::{attr::AttributeParse, ctx::Context, r#impl::Implementer};
+
+use super::{
+    derive::{self, DeriveSetting},
+    r#enum::EnumAttrs,
+    variant::Variant,
+};
+
+/// A fieldless enum, put on the wire as the tag of its variant alone.
+pub struct VarintEnum<'a> {
+    impler: &'a Implementer<'a>,
+    setting: &'a DeriveSetting,
+    attrs: EnumAttrs,
+    variants: Vec<Variant<'a>>,
+}
+
+impl<'a> VarintEnum<'a> {
+    pub fn parse(
+        ctx: &'a Context,
+        impler: &'a Implementer,
+        setting: &'a DeriveSetting,
+        attrs: impl AttributeParse,
+        variants: &mut syn::punctuated::Punctuated<syn::Variant, syn::Token![,]>,
+    ) -> derive::Result<Self> {
+        if variants.is_empty() {
+            ctx.error(variants, "cannot derive for enums with zero variants");
+            return Err(());
+        }
+
+        let attrs = EnumAttrs::parse(ctx, attrs);
+        let variants = parse_variants(ctx, impler, setting, &attrs, variants)?;
+
+        Ok(Self {
+            impler,
+            setting,
+            attrs,
+            variants,
+        })
+    }
+
+    fn default_variant(&self) -> &Variant<'a> {
+        self.variants
+            .iter()
+            .find(|variant| variant.tag() == 0)
+            .unwrap()
+    }
+
+    /// Evaluates to the tag of the variant of `value`.
+    fn tag_of(&self, value: TokenStream) -> TokenStream {
+        let name = self.impler.name();
+
+        let arms = self.variants.iter().map(|variant| {
+            let qual = variant.qual();
+            let tag = variant.tag();
+            quote!(#name #qual => #tag)
+        });
+
+        quote! {
+            match #value {
+                #(#arms,)*
+            }
+        }
+    }
+
+    fn impl_partial_eq(&self) -> TokenStream {
+        let tag = self.tag_of(quote!(self));
+        let other_tag = self.tag_of(quote!(other));
+
+        self.impler.impl_for(
+            "PartialEq",
+            quote! {
+                fn eq(&self, other: &Self) -> bool {
+                    let tag: u32 = #tag;
+                    let other_tag: u32 = #other_tag;
+                    tag == other_tag
+                }
+            },
+        )
+    }
+
+    fn impl_default(&self) -> TokenStream {
+        let name = self.impler.name();
+        let qual = self.default_variant().qual();
+
+        self.impler.impl_for_with(
+            "Default",
+            &[],
+            quote! {
+                fn default() -> Self {
+                    #name #qual
+                }
+            },
+        )
+    }
+
+    fn impl_hash(&self) -> TokenStream {
+        let tag = self.tag_of(quote!(self));
+
+        self.impler.impl_for(
+            "Hash",
+            quote! {
+                fn hash<H: Hasher>(&self, state: &mut H) {
+                    let tag: u32 = #tag;
+                    tag.hash(state);
+                }
+            },
+        )
+    }
+
+    fn impl_map_key(&self) -> TokenStream {
+        let krate = self.setting.krate();
+        let name = self.impler.name();
+        let tag = self.tag_of(quote!(self));
+
+        let arms = self.variants.iter().map(|variant| {
+            let qual = variant.qual();
+            let tag = variant.tag();
+            quote!(#tag => Ok(#name #qual))
+        });
+
+        let map_key = self.impler.impl_for_with(
+            "MapKey",
+            &[],
+            quote! {
+                fn try_from_field_number(field_number: u32) -> io::Result<Self> {
+                    match field_number {
+                        #(#arms,)*
+
+                        _ => Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("unknown variant tag {}", field_number),
+                        )),
+                    }
+                }
+
+                fn as_field_number(&self) -> u32 {
+                    #tag
+                }
+            },
+        );
+
+        quote! {
+            use #krate::types::MapKey;
+            #map_key
+        }
+    }
+
+    fn impl_serde_serialize(&self) -> TokenStream {
+        let name = self.impler.name();
+
+        let arms = self.variants.iter().map(|variant| {
+            let qual = variant.qual();
+            let variant_name = variant.name().to_string();
+            quote!(#name #qual => #variant_name)
+        });
+
+        self.impler.impl_for_with(
+            "serde::Serialize",
+            &[],
+            quote! {
+                fn serialize<SerdeSerializer: serde::Serializer>(
+                    &self,
+                    serializer: SerdeSerializer,
+                ) -> Result<SerdeSerializer::Ok, SerdeSerializer::Error> {
+                    serializer.serialize_str(match self {
+                        #(#arms,)*
+                    })
+                }
+            },
+        )
+    }
+
+    fn impl_serde_deserialize(&self) -> TokenStream {
+        let name = self.impler.name();
+
+        let variant_names: Vec<_> = self
+            .variants
+            .iter()
+            .map(|variant| variant.name().to_string())
+            .collect();
+
+        let arms = self
+            .variants
+            .iter()
+            .zip(&variant_names)
+            .map(|(variant, variant_name)| {
+                let qual = variant.qual();
+                quote!(#variant_name => Ok(#name #qual))
+            });
+
+        self.impler.impl_for_with_lifetime(
+            Some("'de"),
+            "serde::Deserialize<'de>",
+            &[],
+            quote! {
+                fn deserialize<SerdeDeserializer: serde::Deserializer<'de>>(
+                    deserializer: SerdeDeserializer,
+                ) -> Result<Self, SerdeDeserializer::Error> {
+                    let variant_name = <String as serde::Deserialize>::deserialize(deserializer)?;
+
+                    match variant_name.as_str() {
+                        #(#arms,)*
+
+                        _ => Err(serde::de::Error::unknown_variant(
+                            &variant_name,
+                            &[#(#variant_names,)*],
+                        )),
+                    }
+                }
+            },
+        )
+    }
+
+    fn impl_wire_type(&self) -> TokenStream {
+        self.impler.impl_for_with(
+            "HasWireType",
+            &[],
+            quote! {
+                const WIRE_TYPE: WireType = WireType::Varint;
+            },
+        )
+    }
+
+    fn impl_serialize(&self) -> TokenStream {
+        let tag = self.tag_of(quote!(self));
+
+        self.impler.impl_for(
+            "Serialize",
+            quote! {
+                fn compute_size(&self) -> u32 {
+                    let tag: u32 = #tag;
+                    tag.compute_size()
+                }
+
+                fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
+                    let tag: u32 = #tag;
+                    tag.serialize_cached(writer)
+                }
+
+                fn size_cache(&self) -> Option<&SizeCache> {
+                    None
+                }
+
+                fn is_omissible(&self) -> bool {
+                    let tag: u32 = #tag;
+                    tag.is_omissible()
+                }
+            },
+        )
+    }
+
+    fn impl_deserialize(&self) -> TokenStream {
+        self.impler.impl_for(
+            "Deserialize",
+            quote! {
+                fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+                    *self = Self::try_from_field_number(u32::deserialize(reader)?)?;
+                    Ok(())
+                }
+            },
+        )
+    }
+
+    fn impl_state(&self) -> TokenStream {
+        let name = self.impler.name().to_string();
+
+        self.impler.impl_for(
+            "State",
+            quote! {
+                fn with_runtime(_runtime: Runtime) -> Self {
+                    Self::default()
+                }
+
+                fn runtime(&self) -> &Runtime {
+                    panic!("cannot get `Runtime` from `{}`", #name)
+                }
+
+                fn set_runtime(&mut self, _runtime: Runtime) {}
+
+                fn handle_update(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+                    *self = Self::deserialize(reader)?;
+                    Ok(())
+                }
+
+                fn handle(
+                    &mut self,
+                    mut path: impl Iterator<Item = u32>,
+                    kind: LogEntryKind,
+                    _key: Option<u32>,
+                    reader: &mut Reader<impl io::Read>,
+                ) -> io::Result<()> {
+                    match (path.next(), kind) {
+                        (None, LogEntryKind::Update) => self.handle_update(reader),
+                        (None, LogEntryKind::Checksum) => self.handle_checksum(reader),
+
+                        (None, kind) => Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("{:?} is not supported on `{}`", kind, #name),
+                        )),
+
+                        (Some(tag), _) => Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("`{}` expected end-of-path but got tag {}", #name, tag),
+                        )),
+                    }
+                }
+            },
+        )
+    }
+
+    fn impl_meta(&self) -> TokenStream {
+        let rust_name = self.impler.name().to_string();
+
+        let name = derive::name_meta(
+            &rust_name,
+            Some(self.attrs.csharp_name.as_ref().unwrap_or(&rust_name)),
+            self.setting.doc.as_ref(),
+            &self.attrs.annotations,
+        );
+        let reserved_tags = &self.attrs.reserved_tags;
+        let builtin = self.setting.steit_owned;
+
+        let variants = self.variants.iter().map(|variant| {
+            let name = derive::name_meta(
+                &variant.name().to_string(),
+                None,
+                variant.doc(),
+                variant.annotations(),
+            );
+
+            let tag = variant.tag();
+            let deprecated = variant.deprecated();
+
+            quote! {
+                VariantMeta {
+                    ty: StructMeta {
+                        name: &#name,
+                        module: module_path!(),
+                        type_params: &[],
+                        fields: &[],
+                        reserved_tags: &[],
+                        builtin: #builtin,
+                    },
+                    tag: #tag,
+                    deprecated: #deprecated,
+                }
+            }
+        });
+
+        // Primitive as far as the wire is concerned, the message only lists the variants
+        self.impler.impl_for(
+            "HasMeta",
+            quote! {
+                const NAME: &'static NameMeta = &#name;
+
+                const TYPE: &'static TypeMeta = &TypeMeta::Primitive(Self::NAME, WireType::Varint);
+
+                const LINK: &'static MetaLink = &MetaLink {
+                    r#type: Self::TYPE,
+                    msg: Some(MessageMeta::Enum(EnumMeta {
+                        name: Self::NAME,
+                        module: module_path!(),
+                        type_params: &[],
+                        variants: &[#(#variants,)*],
+                        reserved_tags: &[#(#reserved_tags,)*],
+                        repr: EnumRepr::Varint,
+                        builtin: #builtin,
+                    })),
+                    links: || &[],
+                };
+            },
+        )
+    }
+}
+
+fn parse_variants<'a>(
+    ctx: &'a Context,
+    impler: &'a Implementer,
+    setting: &'a DeriveSetting,
+    attrs: &EnumAttrs,
+    variants: &mut syn::punctuated::Punctuated<syn::Variant, syn::Token![,]>,
+) -> derive::Result<Vec<Variant<'a>>> {
+    let mut parsed_variants = Vec::with_capacity(variants.iter().len());
+
+    let reserved_tags: HashSet<_> = attrs.reserved_tags.iter().collect();
+    let mut tags = HashSet::new();
+    let mut unique_tags = true;
+
+    for variant in variants.iter_mut() {
+        if let Ok((parsed_variant, unknown_attrs)) = Variant::parse(ctx, setting, variant) {
+            unknown_attrs.parse(ctx, true, |_| false);
+
+            if !matches!(variant.fields, syn::Fields::Unit) {
+                ctx.error(
+                    &variant.fields,
+                    "variants of `repr = \"varint\"` enums cannot have fields",
+                );
+                continue;
+            }
+
+            let (tag, tag_tokens) = parsed_variant.tag_with_tokens();
+
+            if reserved_tags.contains(&tag) {
+                ctx.error(tag_tokens, format!("tag {} has been reserved", tag));
+            }
+
+            if !tags.insert(tag) {
+                ctx.error(tag_tokens, format!("duplicate tag {}", tag));
+                unique_tags = false;
+            }
+
+            parsed_variants.push(parsed_variant);
+        }
+    }
+
+    if !tags.contains(&0) {
+        ctx.error(
+            impler.name(),
+            "expected a variant with tag 0 as the default variant `#[steit(tag = 0)]`",
+        );
+        return Err(());
+    }
+
+    if parsed_variants.len() == parsed_variants.capacity() && unique_tags {
+        Ok(parsed_variants)
+    } else {
+        Err(())
+    }
+}
+
+impl<'a> ToTokens for VarintEnum<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        // Needed by `Deserialize` and `State` as well
+        tokens.extend(self.impl_map_key());
+
+        if self.setting.derive_partial_eq {
+            tokens.extend(self.impl_partial_eq());
+        }
+
+        if self.setting.derive_default {
+            tokens.extend(self.impl_default());
+        }
+
+        if self.setting.derive_hash {
+            tokens.extend(self.impl_hash());
+        }
+
+        if self.setting.derive_wire_type {
+            tokens.extend(self.impl_wire_type());
+        }
+
+        if self.setting.derive_serialize {
+            tokens.extend(self.impl_serialize());
+        }
+
+        if self.setting.derive_deserialize {
+            tokens.extend(self.impl_deserialize());
+        }
+
+        if self.setting.derive_state {
+            tokens.extend(self.impl_state());
+        }
+
+        if self.setting.derive_serde && self.setting.derive_serialize {
+            tokens.extend(self.impl_serde_serialize());
+        }
+
+        if self.setting.derive_serde && self.setting.derive_deserialize {
+            tokens.extend(self.impl_serde_deserialize());
+        }
+
+        if self.setting.derive_meta {
+            tokens.extend(self.impl_meta());
+        }
+    }
+}
@@ -14,6 +14,23 @@ impl<'a> Implementer<'a> {
         self.name
     }
 
+    pub fn generics(&self) -> &syn::Generics {
+        self.generics
+    }
+
+    /// The generics of the type, with `bounds` added to every type parameter.
+    pub fn bounded_generics(&self, bounds: &[&str]) -> syn::Generics {
+        let mut generics = self.generics.clone();
+
+        for type_param in &mut generics.type_params_mut() {
+            for bound in bounds {
+                type_param.bounds.push(syn::parse_str(bound).unwrap());
+            }
+        }
+
+        generics
+    }
+
     #[allow(dead_code)]
     pub fn r#impl(&self, tokens: TokenStream) -> TokenStream {
         self.impl_for(None, tokens)
@@ -28,20 +45,31 @@ impl<'a> Implementer<'a> {
         r#trait: impl Into<Option<&'b str>>,
         bounds: &[&str],
         tokens: TokenStream,
+    ) -> TokenStream {
+        self.impl_for_with_lifetime(None, r#trait, bounds, tokens)
+    }
+
+    /// Same as `impl_for_with`, but the impl may take a `lifetime` of its own,
+    /// for traits such as `Deserialize<'de>`.
+    pub fn impl_for_with_lifetime<'b>(
+        &self,
+        lifetime: Option<&str>,
+        r#trait: impl Into<Option<&'b str>>,
+        bounds: &[&str],
+        tokens: TokenStream,
     ) -> TokenStream {
         let r#trait = r#trait.into();
-        let mut generics = self.generics.clone();
+        let mut generics = self.bounded_generics(bounds);
 
-        for type_param in &mut generics.type_params_mut() {
-            for bound in bounds {
-                type_param.bounds.push(syn::parse_str(bound).unwrap());
-            }
+        if let Some(lifetime) = lifetime {
+            generics.params.insert(0, syn::parse_str(lifetime).unwrap());
         }
 
-        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let (_, ty_generics, _) = self.generics.split_for_impl();
 
         let r#for = r#trait.map(|r#trait| {
-            let r#trait = format_ident!("{}", r#trait);
+            let r#trait: syn::Path = syn::parse_str(r#trait).unwrap();
             quote!(#r#trait for)
         });
 
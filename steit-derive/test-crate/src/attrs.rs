@@ -0,0 +1,878 @@
+#[cfg(test)]
+mod tests {
+    use steit::{
+        de::{Deserialize, Reader, ValidationError},
+        log::loggers::BufferLogger,
+        meta::{EnumRepr, FieldTypeMeta, HasMeta, MessageMeta, TypeMeta},
+        rt::Runtime,
+        ser::Serialize,
+        state::{state_hash, Flatten, State},
+        steit_derive,
+        types::{List, Map},
+        wire_fmt::WireType,
+    };
+
+    #[steit_derive(Debug, State)]
+    struct Hero {
+        #[steit(tag = 0, default = 100)]
+        hp: i32,
+        #[steit(tag = 1, default = "String::from(\"nobody\")", no_state)]
+        name: String,
+        #[steit(tag = 2)]
+        items: List<u32>,
+        #[steit(skip)]
+        cache: Vec<u32>,
+        #[steit(skip, default = "new_handle()")]
+        handle: Handle,
+    }
+
+    #[derive(PartialEq, Debug)]
+    struct Handle(u32);
+
+    fn new_handle() -> Handle {
+        Handle(42)
+    }
+
+    #[steit_derive(Debug, Serialize, Deserialize)]
+    enum Shape {
+        #[steit(tag = 0)]
+        Circle {
+            #[steit(tag = 0, default = 1)]
+            radius: u32,
+            #[steit(skip)]
+            area_cache: Option<f64>,
+        },
+    }
+
+    /// A number with three decimal places, stored as a scaled integer.
+    #[derive(Clone, Copy, PartialEq, Default, Debug)]
+    struct Fixed(f64);
+
+    mod fixed {
+        use std::io;
+
+        use steit::{
+            de::{Deserialize, Reader},
+            meta::{NameMeta, TypeMeta},
+            ser::Serialize,
+            wire_fmt::WireType,
+        };
+
+        use super::Fixed;
+
+        pub const TYPE: &TypeMeta = &TypeMeta::Primitive(
+            &NameMeta {
+                rust: "Fixed",
+                csharp: Some("Int64"),
+                doc: None,
+                annotations: &[],
+            },
+            WireType::Varint,
+        );
+
+        fn raw(value: &Fixed) -> i64 {
+            (value.0 * 1000.0).round() as i64
+        }
+
+        pub fn compute_size(value: &Fixed) -> u32 {
+            raw(value).compute_size()
+        }
+
+        pub fn serialize(value: &Fixed, writer: &mut impl io::Write) -> io::Result<()> {
+            raw(value).serialize(writer)
+        }
+
+        pub fn merge(value: &mut Fixed, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+            value.0 = i64::deserialize(reader)? as f64 / 1000.0;
+            Ok(())
+        }
+    }
+
+    // `Fixed` has no serde impls
+    #[steit_derive(Debug, State)]
+    #[steit(serde = false)]
+    struct Unit {
+        #[steit(tag = 0, with = "fixed")]
+        speed: Fixed,
+        #[steit(tag = 1)]
+        level: i64,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(serde = false)]
+    enum Motion {
+        #[steit(tag = 0)]
+        Idle,
+        #[steit(tag = 1)]
+        Moving(#[steit(tag = 0, with = "fixed")] Fixed),
+    }
+
+    #[steit_derive(PartialEq, Debug, Serialize, Deserialize)]
+    #[steit(transparent)]
+    struct PlayerId(u32);
+
+    #[steit_derive(Debug, State)]
+    #[steit(transparent)]
+    struct Inventory(List<u32>);
+
+    #[steit_derive(Debug, State)]
+    struct Player {
+        #[steit(tag = 0)]
+        id: i32,
+        #[steit(tag = 1)]
+        inventory: Inventory,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(reserved_tags(1))]
+    struct Monster {
+        #[steit(tag = 0)]
+        hp: i32,
+        #[steit(tag = 2, deprecated)]
+        rage: i32,
+    }
+
+    mod guild {
+        use steit::{
+            steit_derive,
+            types::{List, Map},
+        };
+
+        #[steit_derive(Debug, State)]
+        #[steit(accessors)]
+        pub struct Guild {
+            #[steit(tag = 0)]
+            pub level: i32,
+            #[steit(tag = 1)]
+            leader: Member,
+            #[steit(tag = 2)]
+            members: List<Member>,
+            #[steit(tag = 3)]
+            ranks: Map<u32, i32>,
+        }
+
+        #[steit_derive(Debug, State)]
+        #[steit(accessors)]
+        pub struct Member {
+            #[steit(tag = 0)]
+            hp: i32,
+        }
+    }
+
+    #[steit_derive(Clone, Copy, PartialEq, Debug, State)]
+    #[steit(repr = "varint")]
+    enum Direction {
+        #[steit(tag = 0)]
+        North,
+        #[steit(tag = 1)]
+        East,
+        #[steit(tag = 5)]
+        South,
+    }
+
+    #[steit_derive(Debug, State)]
+    struct Compass {
+        #[steit(tag = 0)]
+        facing: Direction,
+        #[steit(tag = 1)]
+        visits: Map<Direction, u32>,
+    }
+
+    #[steit_derive(Debug, State)]
+    enum Quest {
+        #[steit(tag = 0)]
+        Idle,
+        #[steit(tag = 1)]
+        Hunting {
+            #[steit(tag = 0)]
+            kills: u32,
+            #[steit(tag = 1)]
+            loot: List<u32>,
+        },
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    struct Position {
+        #[steit(tag = 0)]
+        x: i32,
+        #[steit(tag = 1)]
+        y: i32,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    struct Stats {
+        #[steit(tag = 2)]
+        hp: u32,
+        #[steit(tag = 3)]
+        buffs: List<u32>,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    struct Soldier {
+        #[steit(tag = 4)]
+        kind: u32,
+        #[steit(flatten)]
+        position: Position,
+        #[steit(flatten)]
+        stats: Stats,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    struct FlatSoldier {
+        #[steit(tag = 4)]
+        kind: u32,
+        #[steit(tag = 0)]
+        x: i32,
+        #[steit(tag = 1)]
+        y: i32,
+        #[steit(tag = 2)]
+        hp: u32,
+        #[steit(tag = 3)]
+        buffs: List<u32>,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(validate = "check_fighter")]
+    struct Fighter {
+        #[steit(tag = 0)]
+        hp: u32,
+        #[steit(tag = 1)]
+        max_hp: u32,
+        #[steit(tag = 2, validate = "check_targets")]
+        targets: List<u32>,
+    }
+
+    fn check_fighter(fighter: &Fighter) -> Result<(), String> {
+        if fighter.hp > fighter.max_hp {
+            Err(format!(
+                "hp {} exceeds max_hp {}",
+                fighter.hp, fighter.max_hp
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_targets(targets: &List<u32>) -> Result<(), &'static str> {
+        if targets.len() > 2 {
+            Err("too many targets")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Restores some health when drunk.
+    ///
+    /// Stacks up to 10 in the inventory.
+    #[steit_derive(Debug, State)]
+    #[steit(meta(category = "consumable"))]
+    struct Potion {
+        /// Health restored.
+        #[steit(tag = 0, meta(unit = "hp", max = "100"))]
+        heal: u32,
+        #[steit(tag = 1)]
+        charges: u32,
+    }
+
+    /// How a spell is aimed.
+    #[steit_derive(Debug, State)]
+    enum Aim {
+        /// Aimed at the caster itself.
+        #[steit(tag = 0)]
+        Caster,
+        #[steit(tag = 1, meta(icon = "target"))]
+        Unit {
+            /// Id of the unit aimed at.
+            #[steit(tag = 0)]
+            id: u32,
+        },
+    }
+
+    fn to_bytes(value: &impl Serialize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn default_values() {
+        let hero = Hero::new(Runtime::new());
+        assert_eq!(hero.hp, 100);
+        assert_eq!(hero.name, "nobody");
+        assert!(hero.cache.is_empty());
+        assert_eq!(hero.handle, Handle(42));
+
+        let hero = Hero::default();
+        assert_eq!(hero.hp, 100);
+
+        let Shape::Circle {
+            radius, area_cache, ..
+        } = Shape::default();
+        assert_eq!(radius, 1);
+        assert_eq!(area_cache, None);
+    }
+
+    #[test]
+    fn zero_overrides_default() {
+        let mut hero = Hero::new(Runtime::new());
+        hero.set_hp(0).set_name(String::new());
+
+        let bytes = to_bytes(&hero);
+        let hero = Hero::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(hero.hp, 0);
+        assert_eq!(hero.name, "");
+    }
+
+    #[test]
+    fn skipped_fields() {
+        let mut hero = Hero::new(Runtime::new());
+        let bytes = to_bytes(&hero);
+
+        hero.cache.push(7);
+        hero.handle = Handle(1);
+        assert_eq!(to_bytes(&hero), bytes);
+
+        let hero = Hero::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert!(hero.cache.is_empty());
+        assert_eq!(hero.handle, Handle(42));
+
+        match Hero::LINK.msg.as_ref().unwrap() {
+            MessageMeta::Struct(r#struct) => {
+                let names: Vec<_> = r#struct
+                    .fields
+                    .iter()
+                    .map(|field| field.name.rust)
+                    .collect();
+                assert_eq!(names, vec!["hp", "name", "items"]);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn with_codec() {
+        let mut unit = Unit::new(Runtime::new());
+        unit.speed = Fixed(1.5);
+        unit.level = 1500;
+
+        // `speed` is on the wire as the same integer as `level`
+        let bytes = to_bytes(&unit);
+        assert_eq!(bytes[1..3], bytes[4..]);
+
+        let unit = Unit::deserialize(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(unit.speed, Fixed(1.5));
+    }
+
+    #[test]
+    fn with_codec_replay() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut unit = Unit::new(runtime);
+        unit.set_speed(Fixed(0.25));
+
+        let mut motion = Motion::new(Runtime::with_logger(BufferLogger::new()));
+        motion.set_moving_f0(Fixed(2.0));
+        assert!(matches!(motion, Motion::Moving(Fixed(speed), ..) if speed == 2.0));
+
+        let mut replayed = Unit::new(Runtime::new());
+        replayed
+            .replay(&mut Reader::new(&*logger.lock().unwrap().bytes()))
+            .unwrap();
+
+        assert_eq!(replayed.speed, Fixed(0.25));
+    }
+
+    #[test]
+    fn with_codec_checksum() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut unit = Unit::new(runtime);
+        unit.set_speed(Fixed(0.25));
+        let update = logger.lock().unwrap().pluck_bytes();
+
+        // Hashed as the integer its codec puts on the wire
+        let hash = state_hash(&250i64);
+        unit.runtime().nested(0).log_checksum(hash).unwrap();
+        let checksum = logger.lock().unwrap().pluck_bytes();
+
+        let mut replayed = Unit::new(Runtime::new());
+        replayed.replay(&mut Reader::new(&*update)).unwrap();
+        replayed.replay(&mut Reader::new(&*checksum)).unwrap();
+
+        let mut diverged = Unit::new(Runtime::new());
+        let error = diverged.replay(&mut Reader::new(&*checksum)).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("state diverged at path [0]: "));
+    }
+
+    #[test]
+    fn with_codec_meta() {
+        match Unit::LINK.msg.as_ref().unwrap() {
+            MessageMeta::Struct(r#struct) => match r#struct.fields[0].ty {
+                FieldTypeMeta::Type(TypeMeta::Primitive(name, _)) => {
+                    assert_eq!(name.csharp, Some("Int64"));
+                }
+
+                _ => unreachable!(),
+            },
+
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn transparent() {
+        let id = PlayerId(1337);
+        let bytes = to_bytes(&id);
+        assert_eq!(bytes, to_bytes(&1337u32));
+        assert_eq!(
+            PlayerId::deserialize(&mut Reader::new(&*bytes)).unwrap(),
+            id
+        );
+
+        assert_eq!(PlayerId::NAME.rust, "u32");
+        assert!(PlayerId::LINK.msg.is_none());
+        assert_eq!(Inventory::NAME.rust, "List");
+    }
+
+    #[test]
+    fn transparent_replay() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut player = Player::new(runtime);
+        player.set_id(7);
+        player.inventory.0.push(3);
+        player.inventory.0.push(5);
+
+        let mut replayed = Player::new(Runtime::new());
+        replayed
+            .replay(&mut Reader::new(&*logger.lock().unwrap().bytes()))
+            .unwrap();
+
+        assert_eq!(to_bytes(&replayed), to_bytes(&player));
+        assert_eq!(to_bytes(&replayed.inventory), to_bytes(&player.inventory.0));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_fields() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut monster = Monster::new(runtime);
+        monster.set_hp(10).set_rage(3);
+
+        let bytes = to_bytes(&monster);
+        assert_eq!(
+            Monster::deserialize(&mut Reader::new(&*bytes))
+                .unwrap()
+                .rage,
+            3
+        );
+
+        let mut replayed = Monster::new(Runtime::new());
+        replayed
+            .replay(&mut Reader::new(&*logger.lock().unwrap().bytes()))
+            .unwrap();
+        assert_eq!(replayed.rage, 3);
+
+        match Monster::LINK.msg.as_ref().unwrap() {
+            MessageMeta::Struct(r#struct) => {
+                assert_eq!(r#struct.reserved_tags, &[1]);
+                assert!(!r#struct.fields[0].deprecated);
+                assert!(r#struct.fields[1].deprecated);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn accessors() {
+        use guild::{Guild, Member, MemberMut};
+
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut guild = Guild::new(runtime);
+        guild.level_mut().set(3);
+        guild.leader_mut().set_hp(10);
+        guild.members_mut().push_with(Member::new);
+        guild.members_mut().push_with(Member::new);
+        guild.members_mut().get_mut(0).unwrap().set_hp(7);
+        guild.members_mut().get_mut(1).unwrap().hp_mut().set(8);
+        guild.ranks_mut().insert(1, 2);
+        guild.ranks_mut().get_mut(&1).unwrap().set(5);
+
+        let mut replayed = Guild::new(Runtime::new());
+        replayed
+            .replay(&mut Reader::new(&*logger.lock().unwrap().bytes()))
+            .unwrap();
+
+        assert_eq!(to_bytes(&replayed), to_bytes(&guild));
+        assert_eq!(*replayed.level(), 3);
+        assert_eq!(*replayed.leader().hp(), 10);
+        assert_eq!(*replayed.members()[0].hp(), 7);
+        assert_eq!(*replayed.members()[1].hp(), 8);
+        assert_eq!(replayed.ranks().get(&1), Some(&5));
+    }
+
+    #[test]
+    fn varint_enum() {
+        assert_eq!(to_bytes(&Direction::South), to_bytes(&5u32));
+        assert_eq!(Direction::default(), Direction::North);
+        assert_eq!(
+            Direction::deserialize(&mut Reader::new(&[1][..])).unwrap(),
+            Direction::East,
+        );
+        assert!(Direction::deserialize(&mut Reader::new(&[2][..])).is_err());
+
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut compass = Compass::new(runtime);
+        compass.set_facing(Direction::South);
+        compass.visits.insert(Direction::East, 3);
+
+        let mut replayed = Compass::new(Runtime::new());
+        replayed
+            .replay(&mut Reader::new(&*logger.lock().unwrap().bytes()))
+            .unwrap();
+
+        assert_eq!(replayed.facing, Direction::South);
+        assert_eq!(replayed.visits.get(&Direction::East), Some(&3));
+    }
+
+    #[test]
+    fn varint_enum_meta() {
+        assert!(matches!(
+            Direction::TYPE,
+            TypeMeta::Primitive(_, WireType::Varint)
+        ));
+
+        match Direction::LINK.msg.as_ref().unwrap() {
+            MessageMeta::Enum(r#enum) => {
+                assert_eq!(r#enum.repr, EnumRepr::Varint);
+                assert_eq!(r#enum.variants[2].ty.name.rust, "South");
+                assert_eq!(r#enum.variants[2].tag, 5);
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn variant_mut_getters() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut quest = Quest::new(runtime);
+
+        assert!(quest.hunting_kills_mut().is_none());
+
+        quest.set_hunting_kills(1);
+        quest.hunting_kills_mut().unwrap().set(7);
+        quest.hunting_loot_mut().unwrap().push(3);
+
+        let mut replayed = Quest::new(Runtime::new());
+        let bytes = logger.lock().unwrap().bytes();
+        replayed.replay(&mut Reader::new(&*bytes)).unwrap();
+
+        match replayed {
+            Quest::Hunting { kills, loot, .. } => {
+                assert_eq!(kills, 7);
+                assert_eq!(*loot, [3]);
+            }
+
+            _ => unreachable!(),
+        }
+
+        // Neither handle switches variants, so neither logs the whole enum again
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut quest = Quest::new_hunting(runtime);
+        quest.hunting_kills_mut().unwrap().set(2);
+
+        let expected = Runtime::with_logger_returned(BufferLogger::new());
+        expected.0.nested(1).log_update_child(0, &2u32).unwrap();
+        assert_eq!(
+            logger.lock().unwrap().bytes(),
+            expected.1.lock().unwrap().bytes(),
+        );
+    }
+
+    #[test]
+    fn flatten() {
+        let mut soldier = Soldier::new(Runtime::new());
+        soldier.set_kind(7);
+        soldier.position.set_x(-3).set_y(2);
+        soldier.stats.set_hp(100);
+        soldier.stats.buffs.push(9);
+
+        let mut flat = FlatSoldier::new(Runtime::new());
+        flat.set_kind(7).set_x(-3).set_y(2).set_hp(100);
+        flat.buffs.push(9);
+
+        let bytes = to_bytes(&soldier);
+        assert_eq!(bytes, to_bytes(&flat));
+        assert_eq!(
+            Soldier::deserialize(&mut Reader::new(&*bytes)).unwrap(),
+            soldier
+        );
+    }
+
+    #[test]
+    fn flatten_replay() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut soldier = Soldier::new(runtime);
+        soldier.position.set_y(4);
+        soldier.stats.buffs.push(1);
+        soldier.stats.buffs.push(2);
+
+        let (runtime, flat_logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut flat = FlatSoldier::new(runtime);
+        flat.set_y(4);
+        flat.buffs.push(1);
+        flat.buffs.push(2);
+
+        // No path segment for the flattened structs
+        let bytes = logger.lock().unwrap().bytes();
+        assert_eq!(bytes, flat_logger.lock().unwrap().bytes());
+
+        let mut replayed = Soldier::new(Runtime::new());
+        replayed.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(replayed, soldier);
+    }
+
+    #[test]
+    fn flatten_canonical() {
+        let mut soldier = Soldier::new(Runtime::new());
+        soldier.set_kind(7);
+        soldier.position.set_x(-3).set_y(2);
+        soldier.stats.set_hp(100);
+
+        let mut flat = FlatSoldier::new(Runtime::new());
+        flat.set_kind(7).set_x(-3).set_y(2).set_hp(100);
+
+        let bytes = soldier.to_canonical_bytes();
+        assert_eq!(bytes, &[0, 5, 8, 4, 16, 100, 32, 7]);
+        assert_eq!(bytes, flat.to_canonical_bytes());
+        assert_eq!(Soldier::from_canonical_bytes(&bytes).unwrap(), soldier);
+        assert!(Soldier::from_canonical_bytes(&to_bytes(&soldier)).is_err());
+    }
+
+    #[test]
+    fn flatten_meta() {
+        assert_eq!(<Soldier as Flatten>::TAGS, &[4, 0, 1, 2, 3]);
+
+        match Soldier::LINK.msg.as_ref().unwrap() {
+            MessageMeta::Struct(r#struct) => {
+                let names: Vec<_> = r#struct
+                    .fields
+                    .iter()
+                    .map(|field| field.name.rust)
+                    .collect();
+                assert_eq!(names, ["kind", "x", "y", "hp", "buffs"]);
+            }
+
+            _ => unreachable!(),
+        }
+
+        assert_eq!((Soldier::LINK.links)().len(), 5);
+    }
+
+    #[test]
+    fn validate_on_deserialize() {
+        let mut fighter = Fighter::new(Runtime::new());
+        fighter.set_max_hp(5).set_hp(5);
+        assert!(Fighter::deserialize(&mut Reader::new(&*to_bytes(&fighter))).is_ok());
+
+        fighter.set_hp(10);
+        let error = Fighter::deserialize(&mut Reader::new(&*to_bytes(&fighter))).unwrap_err();
+        let error = ValidationError::of(&error).unwrap();
+        assert_eq!(error.target(), "Fighter");
+        assert_eq!(error.message(), "hp 10 exceeds max_hp 5");
+    }
+
+    #[test]
+    fn validate_on_replay() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut fighter = Fighter::new(runtime);
+        fighter.set_max_hp(10).set_hp(5);
+        fighter.targets.push(1);
+        fighter.targets.push(2);
+
+        let bytes = logger.lock().unwrap().bytes();
+        let mut replayed = Fighter::new(Runtime::new());
+        replayed.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(replayed.hp, 5);
+
+        fighter.targets.push(3);
+
+        let bytes = logger.lock().unwrap().bytes();
+        let mut replayed = Fighter::new(Runtime::new());
+        let error = replayed.replay(&mut Reader::new(&*bytes)).unwrap_err();
+        assert_eq!(
+            ValidationError::of(&error).unwrap().target(),
+            "Fighter.targets"
+        );
+
+        // Not rolled back, the state is to be dropped
+        assert_eq!(replayed.targets.len(), 3);
+
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut fighter = Fighter::new(runtime);
+        fighter.set_max_hp(10).set_hp(11);
+
+        let bytes = logger.lock().unwrap().bytes();
+        let mut replayed = Fighter::new(Runtime::new());
+        let error = replayed.replay(&mut Reader::new(&*bytes)).unwrap_err();
+        assert_eq!(ValidationError::of(&error).unwrap().target(), "Fighter");
+    }
+
+    #[test]
+    fn doc_and_annotations() {
+        assert_eq!(
+            Potion::NAME.doc,
+            Some("Restores some health when drunk.\n\nStacks up to 10 in the inventory.")
+        );
+        assert_eq!(Potion::NAME.annotation("category"), Some("consumable"));
+
+        match Potion::LINK.msg.as_ref().unwrap() {
+            MessageMeta::Struct(r#struct) => {
+                assert_eq!(r#struct.doc(), Potion::NAME.doc);
+                assert_eq!(r#struct.fields[0].doc(), Some("Health restored."));
+                assert_eq!(
+                    r#struct.fields[0].name.annotations,
+                    &[("unit", "hp"), ("max", "100")]
+                );
+                assert_eq!(r#struct.fields[1].doc(), None);
+            }
+
+            _ => unreachable!(),
+        }
+
+        match Aim::LINK.msg.as_ref().unwrap() {
+            MessageMeta::Enum(r#enum) => {
+                assert_eq!(r#enum.name.doc, Some("How a spell is aimed."));
+                assert_eq!(
+                    r#enum.variants[0].doc(),
+                    Some("Aimed at the caster itself.")
+                );
+                assert_eq!(r#enum.variants[1].doc(), None);
+                assert_eq!(
+                    r#enum.variants[1].ty.name.annotation("icon"),
+                    Some("target")
+                );
+                assert_eq!(
+                    r#enum.variants[1].ty.fields[0].doc(),
+                    Some("Id of the unit aimed at.")
+                );
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_struct() {
+        let mut hero = Hero::new(Runtime::new());
+        hero.items.push(3);
+        let json = serde_json::to_string(&hero).unwrap();
+        assert_eq!(json, r#"{"hp":100,"name":"nobody","items":[3]}"#);
+
+        let hero: Hero = serde_json::from_str(r#"{"hp":5,"items":[1,2]}"#).unwrap();
+        assert_eq!((hero.hp, &*hero.name, hero.items.len()), (5, "nobody", 2));
+        assert_eq!(hero.handle, Handle(42));
+        assert_eq!(hero.items.runtime().path(), vec![2]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_tuple_struct() {
+        #[steit_derive(PartialEq, Debug, State)]
+        struct Pair(#[steit(tag = 0)] i32, #[steit(tag = 1)] List<u32>);
+
+        let mut pair = Pair::new(Runtime::new());
+        pair.set_f0(-1).1.push(2);
+        let json = serde_json::to_string(&pair).unwrap();
+        assert_eq!(json, r#"{"0":-1,"1":[2]}"#);
+        assert_eq!(serde_json::from_str::<Pair>(&json).unwrap(), pair);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_transparent() {
+        assert_eq!(serde_json::to_string(&PlayerId(7)).unwrap(), "7");
+        assert_eq!(serde_json::from_str::<PlayerId>("7").unwrap(), PlayerId(7));
+
+        let inventory: Inventory = serde_json::from_str("[1,2]").unwrap();
+        assert_eq!(inventory.0.len(), 2);
+        assert!(inventory.is_root());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_flatten() {
+        let mut soldier = Soldier::new(Runtime::new());
+        soldier.set_kind(7);
+        soldier.position.set_x(-3).set_y(2);
+        soldier.stats.set_hp(100);
+        soldier.stats.buffs.push(9);
+
+        let json = serde_json::to_string(&soldier).unwrap();
+        assert_eq!(json, r#"{"kind":7,"x":-3,"y":2,"hp":100,"buffs":[9]}"#);
+
+        let back: Soldier = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, soldier);
+        assert_eq!(back.stats.buffs.runtime().path(), vec![3]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_enum() {
+        let mut quest = Quest::new_hunting(Runtime::new());
+
+        if let Quest::Hunting { kills, loot, .. } = &mut quest {
+            *kills = 3;
+            loot.push(1);
+        }
+
+        let json = serde_json::to_string(&quest).unwrap();
+        assert_eq!(json, r#"{"Hunting":{"kills":3,"loot":[1]}}"#);
+
+        match serde_json::from_str(&json).unwrap() {
+            Quest::Hunting { kills, loot, .. } => {
+                assert_eq!(kills, 3);
+                assert_eq!(loot.runtime().path(), vec![1, 1]);
+            }
+
+            _ => unreachable!(),
+        }
+
+        let quest = Quest::new_idle(Runtime::new());
+        assert_eq!(serde_json::to_string(&quest).unwrap(), r#"{"Idle":{}}"#);
+
+        let error = serde_json::from_str::<Quest>(r#"{"Resting":{}}"#).unwrap_err();
+        assert!(error.to_string().contains("unknown variant `Resting`"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_varint_enum() {
+        let mut compass = Compass::new(Runtime::new());
+        compass.set_facing(Direction::East);
+        compass.visits.insert(Direction::South, 2);
+
+        let json = serde_json::to_string(&compass).unwrap();
+        assert_eq!(json, r#"{"facing":"East","visits":{"South":2}}"#);
+
+        let back: Compass = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.facing, Direction::East);
+        assert_eq!(back.visits.get(&Direction::South), Some(&2));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_validate() {
+        let fighter: Fighter = serde_json::from_str(r#"{"hp":5,"max_hp":5}"#).unwrap();
+        assert_eq!(fighter.hp, 5);
+
+        let error = serde_json::from_str::<Fighter>(r#"{"hp":10,"max_hp":5}"#).unwrap_err();
+        assert!(error.to_string().contains("hp 10 exceeds max_hp 5"));
+
+        let error = serde_json::from_str::<Fighter>(r#"{"targets":[1,2,3]}"#).unwrap_err();
+        assert!(error.to_string().contains("too many targets"));
+    }
+}
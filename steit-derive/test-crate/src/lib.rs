@@ -1,3 +1,5 @@
+mod attrs;
+
 #[cfg(test)]
 mod tests {
     use std::path::Path;
@@ -16,6 +18,7 @@ mod tests {
     };
 
     #[steit_derive(Debug, State)]
+    #[allow(dead_code)]
     enum Foo {
         #[steit(tag = 0)]
         First {
@@ -56,6 +59,7 @@ mod tests {
     }
 
     #[steit_derive(Debug, State)]
+    #[allow(dead_code)]
     enum Animal<A, B, C> {
         #[steit(tag = 0)]
         Alligator(#[steit(tag = 0)] Maybe<A>),
@@ -243,6 +247,27 @@ mod tests {
         map: Map<u16, i32>,
     }
 
+    #[steit_derive(Clone, Copy, Debug, State)]
+    #[steit(repr = "varint")]
+    enum Direction {
+        #[steit(tag = 0)]
+        North,
+        #[steit(tag = 1)]
+        East,
+        #[steit(tag = 2)]
+        South,
+    }
+
+    #[steit_derive(Debug, State)]
+    struct Compass {
+        #[steit(tag = 0)]
+        facing: Direction,
+        #[steit(tag = 1)]
+        route: List<Direction>,
+        #[steit(tag = 2)]
+        marks: Map<u16, Direction>,
+    }
+
     #[test]
     fn test() {
         let base_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../steit-csharp");
@@ -272,6 +297,7 @@ mod tests {
         generator.generate::<Outer>(&setting).unwrap();
         generator.generate::<Multicase>(&setting).unwrap();
         generator.generate::<Woof>(&setting).unwrap();
+        generator.generate::<Compass>(&setting).unwrap();
 
         println!("\nHELLO!");
 
@@ -292,6 +318,21 @@ mod tests {
         hello.serialize(&mut bytes).unwrap();
         println!("serialized: {:?}", bytes);
 
+        println!("\nCOMPASS");
+
+        let runtime = Runtime::with_logger(WriterLogger::stdout());
+        let mut compass = Compass::new(runtime);
+
+        compass.set_facing(Direction::South);
+        compass.route.push(Direction::East);
+        compass.route.push(Direction::South);
+        compass.marks.insert(3, Direction::East);
+        compass.marks.insert(7, Direction::North);
+
+        let mut bytes = Vec::new();
+        compass.serialize(&mut bytes).unwrap();
+        println!("serialized: {:?}", bytes);
+
         println!("\nOUTER");
 
         let runtime = Runtime::with_logger(WriterLogger::stdout());
@@ -0,0 +1,98 @@
+//! Costs which depend on how runtimes are shared.
+//! Compare `cargo bench` against `cargo bench --features unsync`.
+
+#[macro_use]
+extern crate bencher;
+
+use bencher::{black_box, Bencher};
+
+use steit::{
+    log::loggers::NoopLogger,
+    rt::Runtime,
+    state::State,
+    steit_derive,
+    types::{List, Map},
+};
+
+#[steit_derive(Debug, State)]
+struct Unit {
+    #[steit(tag = 0)]
+    hp: i32,
+    #[steit(tag = 1)]
+    buffs: List<u32>,
+}
+
+#[steit_derive(Debug, State)]
+struct Room {
+    #[steit(tag = 0)]
+    units: Map<u32, Unit>,
+    #[steit(tag = 1)]
+    turn: u32,
+}
+
+fn room(units: u32) -> Room {
+    let mut room = Room::new(Runtime::with_logger(NoopLogger::new()));
+
+    for id in 0..units {
+        room.units.insert_with(id, |runtime| {
+            let mut unit = Unit::new(runtime);
+            unit.buffs.push(id);
+            unit
+        });
+    }
+
+    room
+}
+
+fn set_field(b: &mut Bencher) {
+    let mut room = room(1);
+    let mut turn = 0;
+
+    b.iter(|| {
+        turn += 1;
+        room.set_turn(black_box(turn));
+    });
+}
+
+fn set_nested_field(b: &mut Bencher) {
+    let mut room = room(1);
+    let mut hp = 0;
+
+    b.iter(|| {
+        hp += 1;
+        room.units.get_mut(&0).unwrap().set_hp(black_box(hp));
+    });
+}
+
+fn list_push_pop(b: &mut Bencher) {
+    let mut room = room(1);
+
+    b.iter(|| {
+        let buffs = &mut room.units.get_mut(&0).unwrap().buffs;
+        buffs.push(black_box(7));
+        buffs.pop();
+    });
+}
+
+fn set_runtime(b: &mut Bencher) {
+    let mut room = room(100);
+
+    b.iter(|| {
+        room.set_runtime(Runtime::with_logger(NoopLogger::new()));
+    });
+}
+
+fn nested_runtime(b: &mut Bencher) {
+    let runtime = Runtime::with_logger(NoopLogger::new());
+    b.iter(|| black_box(runtime.nested(0).nested(1).nested(2)));
+}
+
+benchmark_group!(
+    benches,
+    set_field,
+    set_nested_field,
+    list_push_pop,
+    set_runtime,
+    nested_runtime
+);
+benchmark_main!(benches);
@@ -0,0 +1,342 @@
+//! A `tokio-util` codec framing values the way log entries are framed in a log,
+//! i.e. each prefixed with its length as a varint.
+//!
+//! A stream of [`LogEntry`] frames is thus the same as the bytes of a log,
+//! and a snapshot sent as a state can be received as [`Bytes`] to be merged later.
+//!
+//! [`LogEntry`]: ../log/enum.LogEntry.html
+//! [`Bytes`]: ../types/struct.Bytes.html
+
+use std::{fmt, io, marker::PhantomData};
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{de::Deserialize, ser::Serialize, wire_fmt::WireType};
+
+/// Frames longer than this are rejected by default, as `LengthDelimitedCodec` does.
+pub const DEFAULT_MAX_LENGTH: usize = 8 * 1024 * 1024;
+
+/// Encodes and decodes length-delimited frames of `T`.
+///
+/// ```
+/// # use bytes::BytesMut;
+/// # use tokio_util::codec::{Decoder, Encoder};
+/// # use steit::codec::Codec;
+/// let mut codec = Codec::<u32>::new();
+/// let mut bytes = BytesMut::new();
+/// codec.encode(1337, &mut bytes).unwrap();
+/// assert_eq!(&*bytes, &[2, 185, 10]);
+/// assert_eq!(codec.decode(&mut bytes).unwrap(), Some(1337));
+/// ```
+pub struct Codec<T> {
+    max_length: usize,
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Codec<T> {
+    pub fn new() -> Self {
+        Self {
+            max_length: DEFAULT_MAX_LENGTH,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Makes frames whose content is longer than `max_length` bytes fail to encode or decode.
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    fn check_length(&self, length: u64) -> io::Result<usize> {
+        if length > self.max_length as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "frame of {} bytes exceeds the maximum of {}",
+                    length, self.max_length,
+                ),
+            ));
+        }
+
+        Ok(length as usize)
+    }
+}
+
+impl<T> Default for Codec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Codec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            max_length: self.max_length,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Codec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Codec")
+            .field("max_length", &self.max_length)
+            .finish()
+    }
+}
+
+impl<T: Serialize> Encoder<&T> for Codec<T> {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &T, dst: &mut BytesMut) -> io::Result<()> {
+        let start = dst.len();
+
+        match T::WIRE_TYPE {
+            // Already prefixed with its length, with no size pass
+            WireType::Sized => {
+                item.serialize_nested_streaming(None, false, &mut BufMut::writer(&mut *dst))?
+            }
+
+            WireType::Varint => {
+                item.compute_size().serialize_buf(dst)?;
+                item.serialize_buf(dst)?;
+            }
+        }
+
+        // The length is only known once the frame is written
+        let frame = &dst[start..];
+        let (prefix, length) = read_length(frame)?.unwrap();
+
+        if let Err(error) = self.check_length(length) {
+            dst.truncate(start);
+            return Err(error);
+        }
+
+        debug_assert_eq!(prefix + length as usize, frame.len());
+        Ok(())
+    }
+}
+
+impl<T: Serialize> Encoder<T> for Codec<T> {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> io::Result<()> {
+        self.encode(&item, dst)
+    }
+}
+
+impl<T: Deserialize> Decoder for Codec<T> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<T>> {
+        let (prefix, length) = match read_length(src)? {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        let length = self.check_length(length)?;
+
+        if src.len() < prefix + length {
+            src.reserve(prefix + length - src.len());
+            return Ok(None);
+        }
+
+        src.advance(prefix);
+        let mut frame = src.split_to(length);
+        T::deserialize_buf(&mut frame).map(Some)
+    }
+}
+
+/// Reads the varint length prefix of the frame at the start of `src`, returning the size
+/// of the prefix along with it, or `None` if the prefix isn't complete yet.
+fn read_length(src: &[u8]) -> io::Result<Option<(usize, u64)>> {
+    let mut length = 0;
+
+    for (index, &byte) in src.iter().enumerate().take(10) {
+        length |= u64::from(byte & 0x7f) << (7 * index);
+
+        if byte & 0x80 == 0 {
+            return Ok(Some((index + 1, length)));
+        }
+    }
+
+    if src.len() >= 10 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame length is not a valid varint",
+        ));
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use bytes::{BufMut, BytesMut};
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite};
+
+    use crate::{
+        de::Deserialize,
+        log::{loggers::BufferLogger, LogEntry},
+        rt::{LoggerHandle, Runtime},
+        ser::Serialize,
+        state::State,
+        test_util::Point,
+        types::{Bytes, List},
+    };
+
+    use super::Codec;
+
+    fn points() -> (List<Point>, LoggerHandle<BufferLogger>) {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        (List::new(runtime), logger)
+    }
+
+    fn push(points: &mut List<Point>, x: i32) {
+        points.push_with(|runtime| Point::new(runtime, x, -x, 1000 * x));
+    }
+
+    #[test]
+    fn encode_as_log() {
+        let (mut points, logger) = points();
+
+        for x in 0..30 {
+            push(&mut points, x);
+        }
+
+        points.pop();
+        points[3].log_checksum().unwrap();
+
+        let mut logger = logger.lock().unwrap();
+        let mut codec = Codec::<LogEntry>::new();
+        let mut bytes = BytesMut::new();
+
+        for entry in logger.entries() {
+            codec.encode(entry, &mut bytes).unwrap();
+        }
+
+        assert_eq!(&*bytes, &*logger.pluck_bytes());
+    }
+
+    #[test]
+    fn decode_partial() {
+        let mut codec = Codec::<Point>::new();
+        let mut bytes = BytesMut::new();
+        codec
+            .encode(Point::new(Runtime::new(), 1, 2, 300), &mut bytes)
+            .unwrap();
+        codec
+            .encode(Point::empty(Runtime::new()), &mut bytes)
+            .unwrap();
+        assert_eq!(&*bytes, &[7, 0, 2, 8, 4, 16, 216, 4, 0]);
+
+        let mut src = BytesMut::new();
+
+        for &byte in &bytes[..7] {
+            src.put_u8(byte);
+            assert_eq!(codec.decode(&mut src).unwrap(), None);
+        }
+
+        src.put_u8(bytes[7]);
+        let point = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!((point.x, point.y, point.z), (1, 2, 300));
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.put_u8(bytes[8]);
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(Point::empty(Runtime::new()))
+        );
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn max_length() {
+        let mut codec = Codec::<Bytes>::new().max_length(2);
+        let mut bytes = BytesMut::from(&[1, 2][..]);
+
+        let error = codec
+            .encode(Bytes::from_raw(vec![1, 2, 3]), &mut bytes)
+            .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(&*bytes, &[1, 2]);
+
+        bytes.extend_from_slice(&[3, 1, 2, 3]);
+        assert_eq!(
+            codec.decode(&mut bytes).unwrap(),
+            Some(Bytes::from_raw(vec![2]))
+        );
+        assert!(codec.decode(&mut bytes).is_err());
+
+        let mut bytes = BytesMut::from(&[0xff; 10][..]);
+        assert!(Codec::<Bytes>::new().decode(&mut bytes).is_err());
+    }
+
+    #[tokio::test]
+    async fn log_over_duplex() {
+        let (server, client) = tokio::io::duplex(64);
+        let mut sink = FramedWrite::new(server, Codec::<LogEntry>::new());
+        let mut stream = FramedRead::new(client, Codec::<LogEntry>::new());
+
+        let (mut points, logger) = points();
+
+        for x in 0..20 {
+            push(&mut points, x);
+        }
+
+        points.log_checksum().unwrap();
+
+        let entries = logger.lock().unwrap().pluck();
+
+        let send = async {
+            for entry in entries {
+                sink.feed(entry).await?;
+            }
+
+            SinkExt::<LogEntry>::close(&mut sink).await
+        };
+
+        let receive = async {
+            let mut replica = List::new(Runtime::new());
+
+            while let Some(entry) = stream.next().await {
+                replica.replay_entry(entry?)?;
+            }
+
+            Ok::<_, io::Error>(replica)
+        };
+
+        let (sent, replica) = tokio::join!(send, receive);
+        sent.unwrap();
+        assert_eq!(replica.unwrap(), points);
+    }
+
+    #[tokio::test]
+    async fn snapshot_over_duplex() {
+        let (server, client) = tokio::io::duplex(64);
+        let mut sink = FramedWrite::new(server, Codec::<List<Point>>::new());
+        let mut stream = FramedRead::new(client, Codec::<Bytes>::new());
+
+        let (mut points, _logger) = points();
+
+        for x in 0..20 {
+            push(&mut points, x);
+        }
+
+        let (sent, snapshot) = tokio::join!(sink.send(&points), stream.next());
+        sent.unwrap();
+
+        let snapshot = snapshot.unwrap().unwrap();
+        assert_eq!(snapshot.as_raw(), &*points.to_bytes());
+
+        let mut replica = List::<Point>::new(Runtime::new());
+        replica.merge_buf(&mut snapshot.as_raw()).unwrap();
+        assert_eq!(replica, points);
+    }
+}
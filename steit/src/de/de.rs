@@ -1,6 +1,9 @@
 use std::io;
 
-use crate::wire_fmt::{HasWireType, WireType};
+use crate::{
+    ser::Serialize,
+    wire_fmt::{HasWireType, WireType},
+};
 
 use super::reader::Reader;
 
@@ -36,4 +39,37 @@ pub trait Deserialize: Default + HasWireType {
         value.merge_nested(wire_type, reader)?;
         Ok(value)
     }
+
+    /// Merges what remains of `buf`, which is consumed to the end.
+    #[cfg(feature = "bytes")]
+    fn merge_buf(&mut self, buf: &mut impl bytes::Buf) -> io::Result<()> {
+        self.merge(&mut Reader::new(bytes::Buf::reader(buf)))
+    }
+
+    #[cfg(feature = "bytes")]
+    fn deserialize_buf(buf: &mut impl bytes::Buf) -> io::Result<Self> {
+        let mut value = Self::default();
+        value.merge_buf(buf)?;
+        Ok(value)
+    }
+
+    /// Deserializes `bytes`, rejecting them unless they are in the [canonical encoding],
+    /// i.e. exactly what serializing the result canonically gives back.
+    ///
+    /// [canonical encoding]: ../ser/index.html
+    fn from_canonical_bytes(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Serialize,
+    {
+        let value = Self::deserialize(&mut Reader::new(bytes))?;
+
+        if value.to_canonical_bytes() == bytes {
+            Ok(value)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "input is not in the canonical encoding",
+            ))
+        }
+    }
 }
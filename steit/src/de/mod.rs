@@ -1,6 +1,8 @@
 #[allow(clippy::module_inception)]
 mod de;
 mod reader;
+mod validate;
 
 pub use de::*;
 pub use reader::*;
+pub use validate::*;
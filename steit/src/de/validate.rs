@@ -0,0 +1,87 @@
+use std::{error::Error, fmt, io};
+
+/// Raised when decoded or replayed data breaks an invariant
+/// checked through `#[steit(validate = "…")]`.
+///
+/// It reaches callers wrapped in an `io::Error` of kind `InvalidData`,
+/// which [`ValidationError::of`] unwraps again.
+///
+/// Replaying checks a struct only once an entry has been applied below it, and nothing
+/// is rolled back. A state which failed to replay still holds the offending entry,
+/// so it should be dropped, e.g. in favor of the last snapshot.
+///
+/// [`ValidationError::of`]: #method.of
+#[derive(Debug)]
+pub struct ValidationError {
+    target: &'static str,
+    message: String,
+}
+
+impl ValidationError {
+    pub fn new(target: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            target,
+            message: message.into(),
+        }
+    }
+
+    /// What failed the check, e.g. `Hero` for the struct or `Hero.hp` for one of its fields.
+    pub fn target(&self) -> &'static str {
+        self.target
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the validation error behind `error`, if that is what it was caused by.
+    pub fn of(error: &io::Error) -> Option<&Self> {
+        error.get_ref()?.downcast_ref()
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid `{}`: {}", self.target, self.message)
+    }
+}
+
+impl Error for ValidationError {}
+
+impl From<ValidationError> for io::Error {
+    fn from(error: ValidationError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+/// Turns the outcome of a user-provided check of `target` into that of a decoder.
+#[doc(hidden)]
+pub fn validate<E: fmt::Display>(target: &'static str, result: Result<(), E>) -> io::Result<()> {
+    result.map_err(|error| ValidationError::new(target, error.to_string()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{validate, ValidationError};
+
+    #[test]
+    fn wrapped_in_io_error() {
+        let error = validate("Hero.hp", Err("exceeds max_hp")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "invalid `Hero.hp`: exceeds max_hp");
+
+        let error = ValidationError::of(&error).unwrap();
+        assert_eq!(error.target(), "Hero.hp");
+        assert_eq!(error.message(), "exceeds max_hp");
+    }
+
+    #[test]
+    fn other_errors() {
+        assert!(validate::<String>("Hero", Ok(())).is_ok());
+
+        let error = io::Error::new(io::ErrorKind::InvalidData, "unknown variant tag 5");
+        assert!(ValidationError::of(&error).is_none());
+    }
+}
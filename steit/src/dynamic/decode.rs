@@ -0,0 +1,481 @@
+use std::io;
+
+use indexmap::IndexMap;
+
+use crate::{
+    de::{Deserialize, Reader},
+    meta::{EnumMeta, FieldMeta, MessageMeta, StructMeta, VariantMeta},
+    wire_fmt::WireType,
+};
+
+use super::{
+    schema::{Schema, Type},
+    value::Value,
+};
+
+type DynReader<'a> = Reader<&'a mut dyn io::Read>;
+
+impl Schema {
+    /// Builds the value a freshly constructed object of type `ty` would have.
+    pub fn default_value(&self, ty: &Type) -> io::Result<Value> {
+        match ty {
+            Type::Primitive(name, wire_type) => Ok(match name.rust {
+                "bool" => Value::Bool(false),
+                "i8" | "i16" | "i32" | "i64" => Value::Int(0),
+                "String" => Value::String(String::new()),
+                _ => match wire_type {
+                    WireType::Varint => Value::UInt(0),
+                    WireType::Sized => Value::Bytes(Vec::new()),
+                },
+            }),
+
+            Type::Ref(name, args) => match name.rust {
+                "Vec" | "List" => Ok(Value::List(Vec::new())),
+                "Map" => Ok(Value::Map(IndexMap::new())),
+                "Option" => Ok(Value::Option(None)),
+                "Bytes" => Ok(Value::Bytes(Vec::new())),
+
+                name => match self.expect_message(name)? {
+                    MessageMeta::Struct(r#struct) => Ok(Value::Struct {
+                        fields: self.default_fields(r#struct, &env(r#struct.type_params, args))?,
+                    }),
+
+                    MessageMeta::Enum(r#enum) => {
+                        let variant = r#enum
+                            .variants
+                            .iter()
+                            .find(|variant| variant.default())
+                            .ok_or_else(|| {
+                                io::Error::new(
+                                    io::ErrorKind::InvalidInput,
+                                    format!("enum `{}` has no default variant", name),
+                                )
+                            })?;
+
+                        Ok(Value::Enum {
+                            tag: variant.tag,
+                            fields: self
+                                .default_fields(&variant.ty, &env(r#enum.type_params, args))?,
+                        })
+                    }
+                },
+            },
+        }
+    }
+
+    /// Decodes a value of the root type.
+    pub fn decode(&self, reader: &mut Reader<impl io::Read>) -> io::Result<Value> {
+        self.decode_type(self.root(), reader)
+    }
+
+    pub fn decode_type(&self, ty: &Type, reader: &mut Reader<impl io::Read>) -> io::Result<Value> {
+        let mut value = self.default_value(ty)?;
+        self.merge(&mut value, ty, reader)?;
+        Ok(value)
+    }
+
+    /// Merges the content in `reader` into `value`, the same way `Deserialize::merge` does.
+    pub fn merge(
+        &self,
+        value: &mut Value,
+        ty: &Type,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<()> {
+        self.merge_dyn(value, ty, &mut Reader::new(reader))
+    }
+
+    // Nested readers are type-erased, otherwise recursing on `Reader::nested` would never end.
+    fn merge_dyn(&self, value: &mut Value, ty: &Type, reader: &mut DynReader) -> io::Result<()> {
+        match (value, ty) {
+            (Value::Bool(value), _) => *value = bool::deserialize(reader)?,
+            (Value::UInt(value), _) => *value = u64::deserialize(reader)?,
+            (Value::Int(value), _) => *value = i64::deserialize(reader)?,
+            (Value::String(value), _) => *value = String::deserialize(reader)?,
+
+            (Value::Bytes(bytes), _) => {
+                io::Read::read_to_end(reader, bytes)?;
+            }
+
+            (Value::List(items), Type::Ref(_, args)) => {
+                let item_ty = expect_arg(args, 0)?;
+
+                while !reader.eof()? {
+                    let mut item = self.default_value(item_ty)?;
+                    self.merge_nested(&mut item, item_ty, item_ty.wire_type(), reader)?;
+                    items.push(item);
+                }
+            }
+
+            (Value::Map(entries), Type::Ref(_, args)) => {
+                let value_ty = expect_arg(args, 0)?;
+
+                while !reader.eof()? {
+                    let (key, wire_type) = reader.read_tag()?;
+
+                    if let Some(value) = entries.get_mut(&key) {
+                        self.merge_nested(value, value_ty, wire_type, reader)?;
+                    } else {
+                        let mut value = self.default_value(value_ty)?;
+                        self.merge_nested(&mut value, value_ty, wire_type, reader)?;
+                        entries.insert(key, value);
+                    }
+                }
+            }
+
+            (Value::Option(value), Type::Ref(_, args)) => {
+                let value_ty = expect_arg(args, 0)?;
+
+                if !reader.eof()? {
+                    if value.is_none() {
+                        *value = Some(Box::new(self.default_value(value_ty)?));
+                    }
+
+                    if let Some(value) = value {
+                        self.merge_nested(value, value_ty, value_ty.wire_type(), reader)?;
+                    }
+                }
+            }
+
+            (Value::Struct { fields }, Type::Ref(name, args)) => {
+                let r#struct = self.expect_struct(name.rust)?;
+                let env = env(r#struct.type_params, args);
+                self.merge_fields(fields, r#struct.fields, &env, reader)?;
+            }
+
+            (value @ Value::Enum { .. }, Type::Ref(name, args)) => {
+                self.merge_enum(value, name.rust, args, reader)?
+            }
+
+            (value, ty) => return Err(mismatch(value, ty)),
+        }
+
+        Ok(())
+    }
+
+    fn merge_nested(
+        &self,
+        value: &mut Value,
+        ty: &Type,
+        wire_type: WireType,
+        reader: &mut DynReader,
+    ) -> io::Result<()> {
+        if wire_type != ty.wire_type() {
+            return reader.skip_field(wire_type);
+        }
+
+        match wire_type {
+            WireType::Varint => self.merge_dyn(value, ty, reader),
+            WireType::Sized => self.merge_dyn(value, ty, &mut Reader::new(&mut reader.nested()?)),
+        }
+    }
+
+    pub(super) fn expect_struct(&self, name: &str) -> io::Result<&'static StructMeta> {
+        match self.expect_message(name)? {
+            MessageMeta::Struct(r#struct) => Ok(r#struct),
+            MessageMeta::Enum(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("expected `{}` to be a struct", name),
+            )),
+        }
+    }
+
+    pub(super) fn expect_enum(&self, name: &str) -> io::Result<&'static EnumMeta> {
+        match self.expect_message(name)? {
+            MessageMeta::Enum(r#enum) => Ok(r#enum),
+            MessageMeta::Struct(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("expected `{}` to be an enum", name),
+            )),
+        }
+    }
+
+    pub(super) fn expect_variant(
+        &self,
+        variants: &'static [VariantMeta],
+        tag: u32,
+    ) -> io::Result<&'static VariantMeta> {
+        variants
+            .iter()
+            .find(|variant| variant.tag == tag)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown variant tag {}", tag),
+                )
+            })
+    }
+
+    pub(super) fn field_type(
+        &self,
+        fields: &'static [FieldMeta],
+        tag: u32,
+        env: &[(&str, Type)],
+    ) -> io::Result<Option<Type>> {
+        match fields.iter().find(|field| field.tag == tag) {
+            Some(field) => Type::resolve(field.ty, env).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn merge_enum(
+        &self,
+        value: &mut Value,
+        name: &str,
+        args: &[Type],
+        reader: &mut DynReader,
+    ) -> io::Result<()> {
+        let r#enum = self.expect_enum(name)?;
+        let env = env(r#enum.type_params, args);
+        let tag = u32::deserialize(reader)?;
+
+        let variant = self.expect_variant(r#enum.variants, tag)?;
+
+        let is_current = match value {
+            Value::Enum { tag: current, .. } => *current == tag,
+            _ => false,
+        };
+
+        if !is_current {
+            *value = Value::Enum {
+                tag,
+                fields: self.default_fields(&variant.ty, &env)?,
+            };
+        }
+
+        match value {
+            Value::Enum { fields, .. } => {
+                self.merge_fields(fields, variant.ty.fields, &env, reader)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub(super) fn default_fields(
+        &self,
+        r#struct: &'static StructMeta,
+        env: &[(&str, Type)],
+    ) -> io::Result<IndexMap<u32, Value>> {
+        let mut fields = IndexMap::with_capacity(r#struct.fields.len());
+
+        for field in r#struct.fields {
+            let ty = Type::resolve(field.ty, env)?;
+            fields.insert(field.tag, self.default_value(&ty)?);
+        }
+
+        Ok(fields)
+    }
+
+    fn merge_fields(
+        &self,
+        fields: &mut IndexMap<u32, Value>,
+        metas: &'static [FieldMeta],
+        env: &[(&str, Type)],
+        reader: &mut DynReader,
+    ) -> io::Result<()> {
+        while !reader.eof()? {
+            let (tag, wire_type) = reader.read_tag()?;
+
+            match (fields.get_mut(&tag), self.field_type(metas, tag, env)?) {
+                (Some(value), Some(ty)) => self.merge_nested(value, &ty, wire_type, reader)?,
+                _ => reader.skip_field(wire_type)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn env<'a>(type_params: &[&'a str], args: &[Type]) -> Vec<(&'a str, Type)> {
+    type_params
+        .iter()
+        .copied()
+        .zip(args.iter().cloned())
+        .collect()
+}
+
+pub(super) fn mismatch(value: &Value, ty: &Type) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("value {:?} does not match type `{}`", value, ty.name().rust),
+    )
+}
+
+pub(super) fn expect_arg(args: &[Type], index: usize) -> io::Result<&Type> {
+    args.get(index).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("missing type argument #{}", index),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::{
+        de::Reader,
+        log::LogEntry,
+        rt::{Node, Runtime},
+        test_util::{serialize, Foo, Point},
+        types::{List, Map},
+    };
+
+    use super::super::{Schema, Type, Value};
+
+    fn decode(schema: &Schema, bytes: &[u8]) -> Value {
+        schema.decode(&mut Reader::new(bytes)).unwrap()
+    }
+
+    fn fields(values: Vec<(u32, Value)>) -> Value {
+        Value::Struct {
+            fields: values.into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn default_value() {
+        let schema = Schema::of::<Foo>().unwrap();
+        let value = schema.default_value(schema.root()).unwrap();
+        assert_eq!(value, fields(vec![(0, Value::Int(0)), (1, Value::Int(0))]));
+    }
+
+    #[test]
+    fn decode_struct() {
+        let schema = Schema::of::<Point>().unwrap();
+        let bytes = serialize(Point::new(Runtime::new(), -1, 0, 2));
+        let value = decode(&schema, &bytes);
+
+        assert_eq!(
+            value,
+            fields(vec![
+                (0, Value::Int(-1)),
+                (1, Value::Int(0)),
+                (2, Value::Int(2))
+            ]),
+        );
+
+        assert_eq!(value.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn decode_skip_unknown() {
+        let schema = Schema::of::<Foo>().unwrap();
+        let value = decode(&schema, &[0, 1, 42, 1, 7, 8, 3]);
+        assert_eq!(
+            value,
+            fields(vec![(0, Value::Int(-1)), (1, Value::Int(-2))])
+        );
+    }
+
+    #[test]
+    fn decode_list() {
+        let schema = Schema::of::<Vec<Foo>>().unwrap();
+        let bytes = serialize(vec![Foo::new(1, 2), Foo::empty()]);
+        let value = decode(&schema, &bytes);
+
+        assert_eq!(
+            value,
+            Value::List(vec![
+                fields(vec![(0, Value::Int(1)), (1, Value::Int(2))]),
+                fields(vec![(0, Value::Int(0)), (1, Value::Int(0))]),
+            ]),
+        );
+
+        assert_eq!(value.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn decode_map() {
+        let schema = Schema::of::<Map<u32, List<Point>>>().unwrap();
+        let mut map: Map<u32, List<Point>> = Map::new(Runtime::new());
+        map.insert_with(5, List::new);
+        map.get_mut(&5)
+            .unwrap()
+            .push_with(|runtime| Point::new(runtime, 1, 0, 0));
+        map.insert_with(1, List::new);
+
+        let bytes = serialize(map);
+        let value = decode(&schema, &bytes);
+
+        let mut entries = IndexMap::new();
+        let point = fields(vec![
+            (0, Value::Int(1)),
+            (1, Value::Int(0)),
+            (2, Value::Int(0)),
+        ]);
+        entries.insert(5, Value::List(vec![point]));
+        entries.insert(1, Value::List(Vec::new()));
+
+        assert_eq!(value, Value::Map(entries));
+        assert_eq!(value.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn decode_option() {
+        let schema = Schema::of::<Option<Foo>>().unwrap();
+        let bytes = serialize(Some(Foo::new(0, -1)));
+        let value = decode(&schema, &bytes);
+
+        assert_eq!(
+            value,
+            Value::Option(Some(Box::new(fields(vec![
+                (0, Value::Int(0)),
+                (1, Value::Int(-1))
+            ])))),
+        );
+
+        assert_eq!(value.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn decode_enum() {
+        let schema = Schema::of::<LogEntry>().unwrap();
+        let bytes = serialize(LogEntry::new_map_remove(&Node::Root, 7));
+        let value = decode(&schema, &bytes);
+
+        assert_eq!(value.get(0), Some(&Value::List(Vec::new())));
+        assert_eq!(value.get(1), Some(&Value::UInt(7)));
+        assert!(matches!(value, Value::Enum { tag: 12, .. }));
+        assert_eq!(value.to_bytes(), bytes);
+    }
+
+    #[test]
+    #[should_panic(expected = "message `Point` not found in schema")]
+    fn decode_unknown_message() {
+        let schema = Schema::of::<Foo>().unwrap();
+        schema
+            .decode_type(&Type::of::<Point>(), &mut Reader::new(&[][..]))
+            .unwrap();
+    }
+
+    mod other {
+        use crate::steit_derive;
+
+        #[steit_derive(Debug, Serialize, Deserialize)]
+        #[steit(steit_owned)]
+        pub struct Foo {
+            #[steit(tag = 0)]
+            pub name: String,
+        }
+    }
+
+    #[test]
+    fn messages_sharing_a_name() {
+        use crate::steit_derive;
+
+        #[steit_derive(Debug, Serialize, Deserialize)]
+        #[steit(steit_owned)]
+        struct Both {
+            #[steit(tag = 0)]
+            foo: Foo,
+            #[steit(tag = 1)]
+            other_foo: other::Foo,
+        }
+
+        let error = Schema::of::<Both>().unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("different messages share the name `Foo`"));
+    }
+}
@@ -0,0 +1,432 @@
+use std::{collections::HashMap, io};
+
+use serde_json::{json, Value as Json};
+
+use crate::{
+    meta::{
+        EnumMeta, EnumRepr, FieldMeta, FieldTypeMeta, MessageMeta, NameMeta, StructMeta, TypeMeta,
+        VariantMeta,
+    },
+    wire_fmt::WireType,
+};
+
+use super::schema::{add_message, Schema, Type};
+
+impl Schema {
+    /// Exports the schema as JSON, so it can be loaded without the Rust types, e.g. by the CLI.
+    pub fn export(&self) -> Json {
+        let mut messages: Vec<_> = self.messages().collect();
+        messages.sort_by_key(|msg| msg.rust_name());
+
+        json!({
+            "root": export_type(self.root()),
+            "messages": messages.into_iter().map(export_message).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Loads a schema produced by [`export`].
+    ///
+    /// Metadata is expected to be `'static`, so everything loaded here is leaked.
+    /// This is meant to be called a few times per process, not in a loop.
+    ///
+    /// [`export`]: #method.export
+    pub fn import(json: &Json) -> io::Result<Self> {
+        let root = import_type(field(json, "root")?)?;
+        let mut messages = HashMap::new();
+
+        for msg in array(field(json, "messages")?)? {
+            let msg: &'static MessageMeta = leak(import_message(msg)?);
+            add_message(&mut messages, msg)?;
+        }
+
+        Ok(Self::from_parts(root, messages))
+    }
+}
+
+fn export_name(name: &NameMeta) -> Json {
+    let mut json = json!({ "rust": name.rust, "csharp": name.csharp });
+
+    // Left out unless given, as most names are of builtin types
+    if let Some(doc) = name.doc {
+        json["doc"] = json!(doc);
+    }
+
+    if !name.annotations.is_empty() {
+        let annotations: serde_json::Map<_, _> = name
+            .annotations
+            .iter()
+            .map(|(key, value)| (key.to_string(), json!(value)))
+            .collect();
+
+        json["annotations"] = Json::Object(annotations);
+    }
+
+    json
+}
+
+fn export_type(ty: &Type) -> Json {
+    match ty {
+        Type::Primitive(name, wire_type) => json!({
+            "primitive": export_name(name),
+            "wire_type": wire_type.value(),
+        }),
+
+        Type::Ref(name, args) => json!({
+            "ref": export_name(name),
+            "args": args.iter().map(export_type).collect::<Vec<_>>(),
+        }),
+    }
+}
+
+fn export_type_meta(ty: &TypeMeta) -> Json {
+    match ty {
+        TypeMeta::Primitive(name, wire_type) => json!({
+            "primitive": export_name(name),
+            "wire_type": wire_type.value(),
+        }),
+
+        TypeMeta::Ref(name, args) => json!({
+            "ref": export_name(name),
+            "args": args.iter().map(export_field_type).collect::<Vec<_>>(),
+        }),
+    }
+}
+
+fn export_field_type(ty: &FieldTypeMeta) -> Json {
+    match ty {
+        FieldTypeMeta::Type(ty) => export_type_meta(ty),
+        FieldTypeMeta::TypeParam(type_param) => json!({ "type_param": type_param }),
+    }
+}
+
+fn export_struct(r#struct: &StructMeta) -> Json {
+    let fields: Vec<_> = r#struct
+        .fields
+        .iter()
+        .map(|field| {
+            json!({
+                "name": export_name(field.name),
+                "ty": export_field_type(field.ty),
+                "tag": field.tag,
+                "deprecated": field.deprecated,
+            })
+        })
+        .collect();
+
+    json!({
+        "name": export_name(r#struct.name),
+        "module": r#struct.module,
+        "type_params": r#struct.type_params,
+        "fields": fields,
+        "reserved_tags": r#struct.reserved_tags,
+        "builtin": r#struct.builtin,
+    })
+}
+
+fn export_message(msg: &MessageMeta) -> Json {
+    match msg {
+        MessageMeta::Struct(r#struct) => json!({ "struct": export_struct(r#struct) }),
+
+        MessageMeta::Enum(r#enum) => {
+            let variants: Vec<_> = r#enum
+                .variants
+                .iter()
+                .map(|variant| {
+                    json!({
+                        "ty": export_struct(&variant.ty),
+                        "tag": variant.tag,
+                        "deprecated": variant.deprecated,
+                    })
+                })
+                .collect();
+
+            json!({
+                "enum": {
+                    "name": export_name(r#enum.name),
+                    "module": r#enum.module,
+                    "type_params": r#enum.type_params,
+                    "variants": variants,
+                    "reserved_tags": r#enum.reserved_tags,
+                    "repr": export_repr(r#enum.repr),
+                    "builtin": r#enum.builtin,
+                }
+            })
+        }
+    }
+}
+
+fn export_repr(repr: EnumRepr) -> &'static str {
+    match repr {
+        EnumRepr::Message => "message",
+        EnumRepr::Varint => "varint",
+    }
+}
+
+fn import_name(json: &Json) -> io::Result<&'static NameMeta> {
+    let csharp = match field(json, "csharp")? {
+        Json::Null => None,
+        csharp => Some(leak_str(string(csharp)?)),
+    };
+
+    let doc = match json.get("doc") {
+        Some(doc) => Some(leak_str(string(doc)?)),
+        None => None,
+    };
+
+    let mut annotations = Vec::new();
+
+    if let Some(json) = json.get("annotations") {
+        let object = json
+            .as_object()
+            .ok_or_else(|| invalid_schema(format!("expected an object, got {}", json)))?;
+
+        for (key, value) in object {
+            annotations.push((leak_str(key), leak_str(string(value)?)));
+        }
+    }
+
+    Ok(leak(NameMeta {
+        rust: leak_str(string(field(json, "rust")?)?),
+        csharp,
+        doc,
+        annotations: leak_slice(annotations),
+    }))
+}
+
+fn import_wire_type(json: &Json) -> io::Result<WireType> {
+    WireType::from_value(uint(field(json, "wire_type")?)? as u32)
+}
+
+fn import_type(json: &Json) -> io::Result<Type> {
+    if let Some(name) = json.get("primitive") {
+        return Ok(Type::Primitive(import_name(name)?, import_wire_type(json)?));
+    }
+
+    let name = import_name(field(json, "ref")?)?;
+    let mut args = Vec::new();
+
+    for arg in array(field(json, "args")?)? {
+        args.push(import_type(arg)?);
+    }
+
+    Ok(Type::Ref(name, args))
+}
+
+fn import_type_meta(json: &Json) -> io::Result<&'static TypeMeta> {
+    if let Some(name) = json.get("primitive") {
+        return Ok(leak(TypeMeta::Primitive(
+            import_name(name)?,
+            import_wire_type(json)?,
+        )));
+    }
+
+    let name = import_name(field(json, "ref")?)?;
+    let mut args = Vec::new();
+
+    for arg in array(field(json, "args")?)? {
+        args.push(import_field_type_value(arg)?);
+    }
+
+    Ok(leak(TypeMeta::Ref(name, leak_slice(args))))
+}
+
+fn import_field_type_value(json: &Json) -> io::Result<FieldTypeMeta> {
+    match json.get("type_param") {
+        Some(type_param) => Ok(FieldTypeMeta::TypeParam(leak_str(string(type_param)?))),
+        None => Ok(FieldTypeMeta::Type(import_type_meta(json)?)),
+    }
+}
+
+fn import_type_params(json: &Json) -> io::Result<&'static [&'static str]> {
+    let mut type_params = Vec::new();
+
+    for type_param in array(field(json, "type_params")?)? {
+        type_params.push(leak_str(string(type_param)?));
+    }
+
+    Ok(leak_slice(type_params))
+}
+
+fn import_repr(json: &Json) -> io::Result<EnumRepr> {
+    match string(json)? {
+        "message" => Ok(EnumRepr::Message),
+        "varint" => Ok(EnumRepr::Varint),
+        repr => Err(invalid_schema(format!("unknown enum repr `{}`", repr))),
+    }
+}
+
+fn import_reserved_tags(json: &Json) -> io::Result<&'static [u32]> {
+    let mut reserved_tags = Vec::new();
+
+    for tag in array(field(json, "reserved_tags")?)? {
+        reserved_tags.push(uint(tag)? as u32);
+    }
+
+    Ok(leak_slice(reserved_tags))
+}
+
+fn import_struct(json: &Json) -> io::Result<StructMeta> {
+    let mut fields = Vec::new();
+
+    for json in array(field(json, "fields")?)? {
+        fields.push(FieldMeta {
+            name: import_name(field(json, "name")?)?,
+            ty: leak(import_field_type_value(field(json, "ty")?)?),
+            tag: uint(field(json, "tag")?)? as u32,
+            deprecated: boolean(field(json, "deprecated")?)?,
+        });
+    }
+
+    Ok(StructMeta {
+        name: import_name(field(json, "name")?)?,
+        module: leak_str(string(field(json, "module")?)?),
+        type_params: import_type_params(json)?,
+        fields: leak_slice(fields),
+        reserved_tags: import_reserved_tags(json)?,
+        builtin: boolean(field(json, "builtin")?)?,
+    })
+}
+
+fn import_message(json: &Json) -> io::Result<MessageMeta> {
+    if let Some(json) = json.get("struct") {
+        return Ok(MessageMeta::Struct(import_struct(json)?));
+    }
+
+    let json = field(json, "enum")?;
+    let mut variants = Vec::new();
+
+    for json in array(field(json, "variants")?)? {
+        variants.push(VariantMeta {
+            ty: import_struct(field(json, "ty")?)?,
+            tag: uint(field(json, "tag")?)? as u32,
+            deprecated: boolean(field(json, "deprecated")?)?,
+        });
+    }
+
+    Ok(MessageMeta::Enum(EnumMeta {
+        name: import_name(field(json, "name")?)?,
+        module: leak_str(string(field(json, "module")?)?),
+        type_params: import_type_params(json)?,
+        variants: leak_slice(variants),
+        reserved_tags: import_reserved_tags(json)?,
+        repr: import_repr(field(json, "repr")?)?,
+        builtin: boolean(field(json, "builtin")?)?,
+    }))
+}
+
+fn leak<T>(value: T) -> &'static T {
+    Box::leak(Box::new(value))
+}
+
+fn leak_slice<T>(values: Vec<T>) -> &'static [T] {
+    Box::leak(values.into_boxed_slice())
+}
+
+fn leak_str(value: &str) -> &'static str {
+    Box::leak(value.to_string().into_boxed_str())
+}
+
+fn invalid_schema(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn field<'a>(json: &'a Json, name: &str) -> io::Result<&'a Json> {
+    json.get(name)
+        .ok_or_else(|| invalid_schema(format!("missing `{}` in schema: {}", name, json)))
+}
+
+fn array(json: &Json) -> io::Result<&Vec<Json>> {
+    json.as_array()
+        .ok_or_else(|| invalid_schema(format!("expected an array, got {}", json)))
+}
+
+fn string(json: &Json) -> io::Result<&str> {
+    json.as_str()
+        .ok_or_else(|| invalid_schema(format!("expected a string, got {}", json)))
+}
+
+fn uint(json: &Json) -> io::Result<u64> {
+    json.as_u64()
+        .ok_or_else(|| invalid_schema(format!("expected an unsigned integer, got {}", json)))
+}
+
+fn boolean(json: &Json) -> io::Result<bool> {
+    json.as_bool()
+        .ok_or_else(|| invalid_schema(format!("expected a boolean, got {}", json)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        de::Reader,
+        log::LogEntry,
+        meta::{HasMeta, MessageMeta},
+        rt::Runtime,
+        steit_derive,
+        test_util::{serialize, Point},
+        types::{List, Map},
+    };
+
+    use super::super::Schema;
+
+    /// A sign by the road.
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned, meta(layer = "props"))]
+    struct Sign {
+        /// Shown when read.
+        #[steit(tag = 0, meta(localized = "true"))]
+        text: u32,
+    }
+
+    #[test]
+    fn export_import() {
+        let schema = Schema::of::<Map<u32, List<Point>>>().unwrap();
+        let exported = schema.export();
+        let imported = Schema::import(&exported).unwrap();
+        assert_eq!(imported.export(), exported);
+    }
+
+    #[test]
+    fn export_import_enum() {
+        let schema = Schema::of::<Option<LogEntry>>().unwrap();
+        let exported = schema.export();
+        let imported = Schema::import(&exported).unwrap();
+        assert_eq!(imported.export(), exported);
+    }
+
+    #[test]
+    fn export_import_docs() {
+        let schema = Schema::of::<Sign>().unwrap();
+        let exported = schema.export();
+        let imported = Schema::import(&exported).unwrap();
+        assert_eq!(imported.export(), exported);
+
+        match imported.message(Sign::NAME.rust).unwrap() {
+            MessageMeta::Struct(r#struct) => {
+                assert_eq!(r#struct.doc(), Some("A sign by the road."));
+                assert_eq!(r#struct.name.annotation("layer"), Some("props"));
+                assert_eq!(r#struct.fields[0].doc(), Some("Shown when read."));
+                assert_eq!(
+                    r#struct.fields[0].name.annotation("localized"),
+                    Some("true")
+                );
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn decode_with_imported() {
+        let schema = Schema::import(&Schema::of::<Point>().unwrap().export()).unwrap();
+        let bytes = serialize(Point::new(Runtime::new(), 3, -2, 0));
+        let value = schema.decode(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(value.to_bytes(), bytes);
+    }
+
+    #[test]
+    #[should_panic(expected = "missing `messages` in schema")]
+    fn import_invalid() {
+        Schema::import(&serde_json::json!({ "root": { "ref": { "rust": "Point", "csharp": null }, "args": [] } })).unwrap();
+    }
+}
@@ -0,0 +1,505 @@
+use std::io;
+
+use indexmap::IndexMap;
+use serde_json::{Map as JsonMap, Value as Json};
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::LogEntry,
+    meta::{EnumMeta, EnumRepr, HasMeta, MessageMeta, StructMeta},
+    ser::Serialize,
+};
+
+use super::{
+    decode::{env, expect_arg, mismatch},
+    schema::{Schema, Type},
+    value::Value,
+};
+
+/// Renders any value as JSON, with fields and variants named after their Rust names.
+pub fn to_json<T: Serialize + HasMeta>(value: &T) -> io::Result<Json> {
+    let schema = Schema::of::<T>()?;
+    let mut bytes = Vec::new();
+    value.serialize(&mut bytes)?;
+    let value = schema.decode(&mut Reader::new(&*bytes))?;
+    schema.to_json(&value, schema.root())
+}
+
+/// Builds a value from its JSON form. Missing fields are left as their defaults.
+pub fn from_json<T: Deserialize + HasMeta>(json: &Json) -> io::Result<T> {
+    let schema = Schema::of::<T>()?;
+    let value = schema.from_json(json, schema.root())?;
+    T::deserialize(&mut Reader::new(&*value.to_bytes()))
+}
+
+impl Schema {
+    pub fn to_json(&self, value: &Value, ty: &Type) -> io::Result<Json> {
+        Ok(match (value, ty) {
+            (Value::Bool(value), _) => Json::from(*value),
+            (Value::Int(value), _) => Json::from(*value),
+            (Value::String(value), _) => Json::from(value.as_str()),
+            (Value::Bytes(bytes), _) => Json::from(bytes.as_slice()),
+
+            (Value::UInt(value), _) => match self.varint_enum(ty) {
+                Some(r#enum) => {
+                    let variant = self.expect_variant(r#enum.variants, *value as u32)?;
+                    Json::from(variant.ty.name.rust)
+                }
+                None => Json::from(*value),
+            },
+
+            (Value::List(items), Type::Ref(_, args)) => {
+                let item_ty = expect_arg(args, 0)?;
+                let mut array = Vec::with_capacity(items.len());
+
+                for item in items {
+                    array.push(self.to_json(item, item_ty)?);
+                }
+
+                Json::Array(array)
+            }
+
+            (Value::Map(entries), Type::Ref(_, args)) => {
+                let value_ty = expect_arg(args, 0)?;
+                let mut object = JsonMap::new();
+
+                for (key, value) in entries {
+                    object.insert(key.to_string(), self.to_json(value, value_ty)?);
+                }
+
+                Json::Object(object)
+            }
+
+            (Value::Option(value), Type::Ref(_, args)) => match value {
+                Some(value) => self.to_json(value, expect_arg(args, 0)?)?,
+                None => Json::Null,
+            },
+
+            (Value::Struct { fields }, Type::Ref(name, args)) => {
+                let r#struct = self.expect_struct(name.rust)?;
+                let env = env(r#struct.type_params, args);
+                Json::Object(self.fields_to_json(fields, r#struct, &env)?)
+            }
+
+            (Value::Enum { tag, fields }, Type::Ref(name, args)) => {
+                let r#enum = self.expect_enum(name.rust)?;
+                let env = env(r#enum.type_params, args);
+                let variant = self.expect_variant(r#enum.variants, *tag)?;
+
+                if variant.ty.fields.is_empty() {
+                    Json::from(variant.ty.name.rust)
+                } else {
+                    let mut object = JsonMap::new();
+                    let fields = self.fields_to_json(fields, &variant.ty, &env)?;
+                    object.insert(variant.ty.name.rust.to_string(), Json::Object(fields));
+                    Json::Object(object)
+                }
+            }
+
+            (value, ty) => return Err(mismatch(value, ty)),
+        })
+    }
+
+    /// Parses the JSON form of a value of type `ty`.
+    ///
+    /// Enum variants are written either as `{ "Variant": { ...fields } }`
+    /// or, for variants without fields and `repr = "varint"` enums, as `"Variant"`.
+    pub fn from_json(&self, json: &Json, ty: &Type) -> io::Result<Value> {
+        let mut value = self.default_value(ty)?;
+
+        match (&mut value, json) {
+            (_, Json::Null) => {}
+            (Value::Bool(value), Json::Bool(json)) => *value = *json,
+            (Value::String(value), Json::String(json)) => *value = json.clone(),
+
+            (Value::UInt(value), Json::String(variant_name)) => {
+                let r#enum = self.varint_enum(ty).ok_or_else(|| invalid_json(json, ty))?;
+
+                let variant = r#enum
+                    .variants
+                    .iter()
+                    .find(|variant| variant.ty.name.rust == variant_name)
+                    .ok_or_else(|| invalid_json(variant_name, ty))?;
+
+                *value = variant.tag as u64;
+            }
+
+            (Value::UInt(value), Json::Number(json)) => {
+                *value = json.as_u64().ok_or_else(|| invalid_json(json, ty))?;
+            }
+
+            (Value::Int(value), Json::Number(json)) => {
+                *value = json.as_i64().ok_or_else(|| invalid_json(json, ty))?;
+            }
+
+            (Value::Bytes(bytes), Json::Array(array)) => {
+                for byte in array {
+                    match byte.as_u64() {
+                        Some(byte) if byte <= u8::MAX as u64 => bytes.push(byte as u8),
+                        _ => return Err(invalid_json(byte, ty)),
+                    }
+                }
+            }
+
+            (Value::List(items), Json::Array(array)) => {
+                let item_ty = expect_arg(ty.args(), 0)?;
+
+                for item in array {
+                    items.push(self.from_json(item, item_ty)?);
+                }
+            }
+
+            (Value::Map(entries), Json::Object(object)) => {
+                let value_ty = expect_arg(ty.args(), 0)?;
+
+                for (key, value) in object {
+                    let key = key.parse().map_err(|_| invalid_json(key, ty))?;
+                    entries.insert(key, self.from_json(value, value_ty)?);
+                }
+            }
+
+            (Value::Option(value), json) => {
+                *value = Some(Box::new(self.from_json(json, expect_arg(ty.args(), 0)?)?));
+            }
+
+            (Value::Struct { fields }, Json::Object(object)) => {
+                let r#struct = self.expect_struct(ty.name().rust)?;
+                let env = env(r#struct.type_params, ty.args());
+                self.fields_from_json(fields, object, r#struct, &env)?;
+            }
+
+            (value @ Value::Enum { .. }, json) => {
+                let r#enum = self.expect_enum(ty.name().rust)?;
+                let env = env(r#enum.type_params, ty.args());
+
+                let (variant_name, object) = match json {
+                    Json::String(variant_name) => (variant_name, None),
+                    Json::Object(object) if object.len() == 1 => match object.iter().next() {
+                        Some((variant_name, Json::Object(fields))) => (variant_name, Some(fields)),
+                        _ => return Err(invalid_json(json, ty)),
+                    },
+                    _ => return Err(invalid_json(json, ty)),
+                };
+
+                let variant = r#enum
+                    .variants
+                    .iter()
+                    .find(|variant| variant.ty.name.rust == variant_name)
+                    .ok_or_else(|| invalid_json(variant_name, ty))?;
+
+                let mut fields = self.default_fields(&variant.ty, &env)?;
+
+                if let Some(object) = object {
+                    self.fields_from_json(&mut fields, object, &variant.ty, &env)?;
+                }
+
+                *value = Value::Enum {
+                    tag: variant.tag,
+                    fields,
+                };
+            }
+
+            _ => return Err(invalid_json(json, ty)),
+        }
+
+        Ok(value)
+    }
+
+    /// Finds the `repr = "varint"` enum which `ty` stands for, if any.
+    /// Such enums are primitives on the wire, their variants are only listed in the schema.
+    fn varint_enum(&self, ty: &Type) -> Option<&'static EnumMeta> {
+        match (ty, self.message(ty.name().rust)) {
+            (Type::Primitive(..), Some(MessageMeta::Enum(r#enum)))
+                if r#enum.repr == EnumRepr::Varint =>
+            {
+                Some(r#enum)
+            }
+            _ => None,
+        }
+    }
+
+    /// Renders a log entry as `{ "op": ..., "path": [...], ... }`,
+    /// with struct fields and enum variants in `path` resolved to their names.
+    pub fn entry_to_json(&self, entry: &LogEntry) -> io::Result<Json> {
+        let mut object = JsonMap::new();
+
+        let (op, path) = match entry {
+            LogEntry::Update { path, .. } => ("update", path),
+            LogEntry::ListPush { path, .. } => ("list_push", path),
+            LogEntry::ListPop { path, .. } => ("list_pop", path),
+            LogEntry::MapRemove { path, .. } => ("map_remove", path),
+            LogEntry::Checksum { path, .. } => ("checksum", path),
+        };
+
+        let (names, ty) = self.resolve_path(path)?;
+
+        object.insert("op".to_string(), Json::from(op));
+        object.insert("path".to_string(), Json::Array(names));
+
+        match entry {
+            LogEntry::Update { value, .. } => {
+                let value = self.decode_type(&ty, &mut Reader::new(value.as_raw()))?;
+                object.insert("value".to_string(), self.to_json(&value, &ty)?);
+            }
+
+            LogEntry::ListPush { item, .. } => {
+                let item_ty = expect_arg(ty.args(), 0)?;
+                let item = self.decode_type(item_ty, &mut Reader::new(item.as_raw()))?;
+                object.insert("item".to_string(), self.to_json(&item, item_ty)?);
+            }
+
+            LogEntry::ListPop { .. } => {}
+
+            LogEntry::MapRemove { key, .. } => {
+                object.insert("key".to_string(), Json::from(*key));
+            }
+
+            LogEntry::Checksum { hash, .. } => {
+                object.insert("hash".to_string(), Json::from(*hash));
+            }
+        }
+
+        Ok(Json::Object(object))
+    }
+
+    /// Finds the type at `path`, naming every struct field and enum variant along the way.
+    fn resolve_path(&self, path: &[u32]) -> io::Result<(Vec<Json>, Type)> {
+        let mut names = Vec::with_capacity(path.len());
+        let mut ty = self.root().clone();
+        let mut path = path.iter().copied();
+
+        while let Some(tag) = path.next() {
+            ty = match ty.name().rust {
+                "Vec" | "List" | "Map" => {
+                    names.push(Json::from(tag));
+                    expect_arg(ty.args(), 0)?.clone()
+                }
+
+                name => match self.expect_message(name)? {
+                    MessageMeta::Struct(r#struct) => {
+                        let env = env(r#struct.type_params, ty.args());
+                        self.resolve_field(&mut names, r#struct, tag, &env)?
+                    }
+
+                    MessageMeta::Enum(r#enum) => {
+                        let env = env(r#enum.type_params, ty.args());
+                        let variant = self.expect_variant(r#enum.variants, tag)?;
+                        names.push(Json::from(variant.ty.name.rust));
+
+                        match path.next() {
+                            Some(tag) => self.resolve_field(&mut names, &variant.ty, tag, &env)?,
+                            None => break,
+                        }
+                    }
+                },
+            };
+        }
+
+        Ok((names, ty))
+    }
+
+    fn resolve_field(
+        &self,
+        names: &mut Vec<Json>,
+        r#struct: &'static StructMeta,
+        tag: u32,
+        env: &[(&str, Type)],
+    ) -> io::Result<Type> {
+        let field = r#struct
+            .fields
+            .iter()
+            .find(|field| field.tag == tag)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unexpected tag {} in `{}`", tag, r#struct.name.rust),
+                )
+            })?;
+
+        names.push(Json::from(field.name.rust));
+        Type::resolve(field.ty, env)
+    }
+
+    fn fields_to_json(
+        &self,
+        fields: &IndexMap<u32, Value>,
+        r#struct: &'static StructMeta,
+        env: &[(&str, Type)],
+    ) -> io::Result<JsonMap<String, Json>> {
+        let mut object = JsonMap::new();
+
+        for field in r#struct.fields {
+            if let Some(value) = fields.get(&field.tag) {
+                let ty = Type::resolve(field.ty, env)?;
+                object.insert(field.name.rust.to_string(), self.to_json(value, &ty)?);
+            }
+        }
+
+        Ok(object)
+    }
+
+    fn fields_from_json(
+        &self,
+        fields: &mut IndexMap<u32, Value>,
+        object: &JsonMap<String, Json>,
+        r#struct: &'static StructMeta,
+        env: &[(&str, Type)],
+    ) -> io::Result<()> {
+        for (name, json) in object {
+            let field = r#struct
+                .fields
+                .iter()
+                .find(|field| field.name.rust == name)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unknown field `{}` in `{}`", name, r#struct.name.rust),
+                    )
+                })?;
+
+            let ty = Type::resolve(field.ty, env)?;
+            fields.insert(field.tag, self.from_json(json, &ty)?);
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid_json(json: impl std::fmt::Display, ty: &Type) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("cannot read `{}` as `{}`", json, ty.name().rust),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        log::{loggers::BufferLogger, LogEntry},
+        rt::{Node, Runtime, SizeCache},
+        state::State,
+        steit_derive,
+        test_util::Point,
+        types::{Bytes, List, Map},
+    };
+
+    use super::{from_json, to_json, Schema};
+
+    #[test]
+    fn struct_to_json() {
+        let json = to_json(&Point::new(Runtime::new(), -1, 0, 2)).unwrap();
+        assert_eq!(json, json!({ "x": -1, "y": 0, "z": 2 }));
+    }
+
+    #[test]
+    fn struct_from_json() {
+        let point: Point = from_json(&json!({ "z": 7, "x": -3 })).unwrap();
+        assert_eq!(point, Point::new(Runtime::new(), -3, 0, 7));
+    }
+
+    #[test]
+    fn enum_to_json() {
+        let json = to_json(&LogEntry::new_map_remove(&Node::Root, 7)).unwrap();
+        assert_eq!(json, json!({ "MapRemove": { "path": [], "key": 7 } }));
+    }
+
+    #[test]
+    fn enum_from_json() {
+        let entry: LogEntry = from_json(&json!({ "ListPop": { "path": [1, 2] } })).unwrap();
+        assert!(matches!(entry, LogEntry::ListPop { path, .. } if path == vec![1, 2]));
+    }
+
+    #[test]
+    fn map_json_round_trip() {
+        let schema = Schema::of::<Map<u32, List<Point>>>().unwrap();
+        let json = json!({ "3": [{ "x": 1, "y": 0, "z": -1 }], "1": [] });
+        let value = schema.from_json(&json, schema.root()).unwrap();
+        assert_eq!(schema.to_json(&value, schema.root()).unwrap(), json);
+    }
+
+    #[steit_derive(Clone, Copy, PartialEq, Debug, State)]
+    #[steit(steit_owned, repr = "varint")]
+    enum Direction {
+        #[steit(tag = 0)]
+        North,
+        #[steit(tag = 3)]
+        West,
+    }
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned)]
+    struct Compass {
+        #[steit(tag = 0)]
+        facing: Direction,
+        #[steit(tag = 1)]
+        path: List<Direction>,
+    }
+
+    #[test]
+    fn varint_enum_json_round_trip() {
+        let mut compass = Compass::new(Runtime::new());
+        compass.facing = Direction::West;
+        compass.path.push(Direction::North);
+        compass.path.push(Direction::West);
+
+        let json = to_json(&compass).unwrap();
+        assert_eq!(json, json!({ "facing": "West", "path": ["North", "West"] }),);
+
+        assert_eq!(from_json::<Compass>(&json).unwrap(), compass);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot read `South` as `Direction`")]
+    fn unknown_varint_variant() {
+        from_json::<Compass>(&json!({ "facing": "South" })).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown field `w` in `Point`")]
+    fn unknown_field() {
+        from_json::<Point>(&json!({ "w": 1 })).unwrap();
+    }
+
+    #[test]
+    fn entry_to_json() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut list = List::<Point>::with_runtime(runtime);
+        list.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        list.get_mut(0).unwrap().set_z(-5);
+        list.pop();
+
+        let schema = Schema::of::<List<Point>>().unwrap();
+        let entries = logger.lock().unwrap().pluck();
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| schema.entry_to_json(entry).unwrap())
+            .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                json!({ "op": "list_push", "path": [], "item": { "x": 1, "y": 2, "z": 3 } }),
+                json!({ "op": "update", "path": [0, "z"], "value": -5 }),
+                json!({ "op": "list_pop", "path": [] }),
+            ],
+        );
+    }
+
+    #[test]
+    fn entry_to_json_variant_path() {
+        let schema = Schema::of::<LogEntry>().unwrap();
+
+        let entry = LogEntry::Update {
+            path: vec![12, 1],
+            value: Bytes::from_value(&9u32),
+            size_cache: SizeCache::new(),
+        };
+
+        assert_eq!(
+            schema.entry_to_json(&entry).unwrap(),
+            json!({ "op": "update", "path": ["MapRemove", "key"], "value": 9 }),
+        );
+    }
+}
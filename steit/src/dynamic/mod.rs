@@ -0,0 +1,15 @@
+mod decode;
+#[cfg(feature = "json")]
+mod export;
+#[cfg(feature = "json")]
+mod json;
+mod observe;
+mod replay;
+mod schema;
+mod value;
+
+#[cfg(feature = "json")]
+pub use json::*;
+pub use observe::*;
+pub use schema::*;
+pub use value::*;
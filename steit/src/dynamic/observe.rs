@@ -0,0 +1,649 @@
+use std::{fmt, io, str::FromStr};
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::{LogEntry, LogEntryKind},
+    meta::HasMeta,
+    ser::Serialize,
+    state::{diverged, State},
+    wire_fmt::HasWireType,
+};
+
+use super::{schema::Schema, value::Value};
+
+/// A segment of a [`PathPattern`].
+///
+/// [`PathPattern`]: struct.PathPattern.html
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Segment {
+    Tag(u32),
+    // Any field, list index, map key or variant
+    Any,
+}
+
+/// Matches paths of log entries of the same length, segment by segment.
+///
+/// Parsed from strings such as `"2/*/0"`, the empty string being the root.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct PathPattern {
+    segments: Vec<Segment>,
+}
+
+impl PathPattern {
+    pub fn new(segments: Vec<Segment>) -> Self {
+        Self { segments }
+    }
+
+    pub fn root() -> Self {
+        Self::new(Vec::new())
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    pub fn matches(&self, path: &[u32]) -> bool {
+        self.segments.len() == path.len() && self.overlaps(path)
+    }
+
+    /// Whether an entry at `path` may change what is at the paths this pattern matches,
+    /// i.e. one of them starts with the other.
+    pub fn overlaps(&self, path: &[u32]) -> bool {
+        self.segments
+            .iter()
+            .zip(path)
+            .all(|(segment, &tag)| match *segment {
+                Segment::Tag(expected) => expected == tag,
+                Segment::Any => true,
+            })
+    }
+}
+
+impl FromStr for PathPattern {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_matches('/');
+
+        if s.is_empty() {
+            return Ok(Self::root());
+        }
+
+        let segments = s
+            .split('/')
+            .map(|segment| match segment {
+                "*" => Ok(Segment::Any),
+                tag => tag.parse().map(Segment::Tag).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("invalid path segment `{}`, expected a tag or `*`", tag),
+                    )
+                }),
+            })
+            .collect::<io::Result<_>>()?;
+
+        Ok(Self::new(segments))
+    }
+}
+
+impl From<&[u32]> for PathPattern {
+    fn from(path: &[u32]) -> Self {
+        Self::new(path.iter().copied().map(Segment::Tag).collect())
+    }
+}
+
+impl<const N: usize> From<[u32; N]> for PathPattern {
+    fn from(path: [u32; N]) -> Self {
+        Self::from(&path[..])
+    }
+}
+
+impl fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let segments: Vec<_> = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Tag(tag) => tag.to_string(),
+                Segment::Any => "*".to_string(),
+            })
+            .collect();
+
+        f.write_str(&segments.join("/"))
+    }
+}
+
+/// What a log entry did at the path of a [`ChangeEvent`].
+///
+/// Values of `Update` are those at the path itself,
+/// while those of the others are items or entries of the list or map at the path.
+///
+/// [`ChangeEvent`]: struct.ChangeEvent.html
+#[derive(Clone, PartialEq, Debug)]
+pub enum Change<T> {
+    // `old` is `None` when a map entry is inserted
+    Update { old: Option<T>, new: T },
+    ListPush { index: u32, item: T },
+    ListPop { index: u32, item: T },
+    MapRemove { key: u32, value: T },
+}
+
+impl<T> Change<T> {
+    pub fn kind(&self) -> LogEntryKind {
+        match self {
+            Change::Update { .. } => LogEntryKind::Update,
+            Change::ListPush { .. } => LogEntryKind::ListPush,
+            Change::ListPop { .. } => LogEntryKind::ListPop,
+            Change::MapRemove { .. } => LogEntryKind::MapRemove,
+        }
+    }
+
+    fn try_map<U>(&self, mut f: impl FnMut(&T) -> io::Result<U>) -> io::Result<Change<U>> {
+        Ok(match self {
+            Change::Update { old, new } => Change::Update {
+                old: old.as_ref().map(&mut f).transpose()?,
+                new: f(new)?,
+            },
+            Change::ListPush { index, item } => Change::ListPush {
+                index: *index,
+                item: f(item)?,
+            },
+            Change::ListPop { index, item } => Change::ListPop {
+                index: *index,
+                item: f(item)?,
+            },
+            Change::MapRemove { key, value } => Change::MapRemove {
+                key: *key,
+                value: f(value)?,
+            },
+        })
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ChangeEvent<'a, T> {
+    pub path: &'a [u32],
+    pub change: Change<T>,
+}
+
+type Callback = Box<dyn FnMut(&[u32], &Change<Value>) -> io::Result<()>>;
+
+struct Subscription {
+    pattern: PathPattern,
+    callback: Callback,
+}
+
+// What is known of a change before it is applied
+enum Before {
+    Update(Option<Value>),
+    ListPush(u32),
+    Done(Change<Value>),
+}
+
+/// Replays log entries on a `State`, firing typed events to the callbacks
+/// subscribed to the paths of the entries.
+///
+/// Old values are read from a schema-decoded copy of the state, which costs about
+/// as much memory once something is observed. Only entries which may change
+/// the observed paths are applied to it.
+pub struct Observer<S> {
+    state: S,
+    schema: Schema,
+    // Only in sync under the observed paths, decoded once needed
+    mirror: Option<Value>,
+    // Whether entries were left out of `mirror`, which new subscriptions may observe
+    skipped: bool,
+    subscriptions: Vec<Subscription>,
+}
+
+impl<S: State + HasMeta> Observer<S> {
+    pub fn new(state: S) -> io::Result<Self> {
+        if !state.is_root() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "only root `State` objects can be observed",
+            ));
+        }
+
+        Ok(Self {
+            state,
+            schema: Schema::of::<S>()?,
+            mirror: None,
+            skipped: false,
+            subscriptions: Vec::new(),
+        })
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn into_state(self) -> S {
+        self.state
+    }
+
+    /// Calls `callback` whenever an entry changes a path matched by `pattern`,
+    /// with values decoded as `T`. That is the type at the path for updates,
+    /// and the item or value type of the list or map at the path otherwise.
+    ///
+    /// Values which cannot be decoded as `T` fail the entry being applied,
+    /// after it has been applied.
+    pub fn on_change<T: Deserialize>(
+        &mut self,
+        pattern: impl Into<PathPattern>,
+        mut callback: impl FnMut(ChangeEvent<T>) + 'static,
+    ) -> &mut Self {
+        if self.skipped {
+            self.mirror = None;
+            self.skipped = false;
+        }
+
+        self.subscriptions.push(Subscription {
+            pattern: pattern.into(),
+            callback: Box::new(move |path, change| {
+                let change =
+                    change.try_map(|value| T::deserialize(&mut Reader::new(&*value.to_bytes())))?;
+
+                callback(ChangeEvent { path, change });
+                Ok(())
+            }),
+        });
+
+        self
+    }
+
+    /// Applies `entry` to the state, then fires the events it causes.
+    pub fn apply(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let checksum;
+
+        let (kind, path, key, bytes): (_, &[u32], _, &[u8]) = match entry {
+            LogEntry::Update { path, value, .. } => {
+                (LogEntryKind::Update, path, None, value.as_raw())
+            }
+            LogEntry::ListPush { path, item, .. } => {
+                (LogEntryKind::ListPush, path, None, item.as_raw())
+            }
+            LogEntry::ListPop { path, .. } => (LogEntryKind::ListPop, path, None, &[]),
+            LogEntry::MapRemove { path, key, .. } => {
+                (LogEntryKind::MapRemove, path, Some(*key), &[])
+            }
+            LogEntry::Checksum { path, hash, .. } => {
+                checksum = hash.to_bytes();
+                (LogEntryKind::Checksum, path, None, &checksum)
+            }
+        };
+
+        let tracked = self
+            .subscriptions
+            .iter()
+            .any(|subscription| subscription.pattern.overlaps(path));
+
+        let observed = self
+            .subscriptions
+            .iter()
+            .any(|subscription| subscription.pattern.matches(path));
+
+        let mirror = match &mut self.mirror {
+            Some(mirror) if tracked => Some(mirror),
+            None if tracked => {
+                let state = self.state.to_bytes();
+                Some(
+                    self.mirror
+                        .insert(self.schema.decode(&mut Reader::new(&*state))?),
+                )
+            }
+            _ => None,
+        };
+
+        let before = match mirror {
+            Some(mirror) if observed => match (kind, key, value_at(mirror, path)) {
+                (LogEntryKind::Update, _, old) => Some(Before::Update(old.cloned())),
+
+                (LogEntryKind::ListPush, _, Some(Value::List(items))) => {
+                    Some(Before::ListPush(items.len() as u32))
+                }
+
+                (LogEntryKind::ListPop, _, Some(Value::List(items))) => items.last().map(|item| {
+                    Before::Done(Change::ListPop {
+                        index: items.len() as u32 - 1,
+                        item: item.clone(),
+                    })
+                }),
+
+                (LogEntryKind::MapRemove, Some(key), Some(Value::Map(entries))) => {
+                    entries.get(&key).map(|value| {
+                        Before::Done(Change::MapRemove {
+                            key,
+                            value: value.clone(),
+                        })
+                    })
+                }
+
+                _ => None,
+            },
+
+            _ => None,
+        };
+
+        let handled = self
+            .state
+            .handle(path.iter().copied(), kind, key, &mut Reader::new(bytes));
+
+        if kind == LogEntryKind::Checksum {
+            return handled.map_err(|error| diverged(path, error));
+        }
+
+        handled?;
+
+        let mirror = match &mut self.mirror {
+            Some(mirror) if tracked => mirror,
+            _ => {
+                self.skipped = true;
+                return Ok(());
+            }
+        };
+
+        self.schema.apply(mirror, entry)?;
+
+        let change = match (before, value_at(mirror, path)) {
+            (Some(Before::Update(old)), Some(new)) => Change::Update {
+                old,
+                new: new.clone(),
+            },
+
+            (Some(Before::ListPush(index)), Some(Value::List(items))) => Change::ListPush {
+                index,
+                item: items[index as usize].clone(),
+            },
+
+            (Some(Before::Done(change)), _) => change,
+            _ => return Ok(()),
+        };
+
+        for subscription in &mut self.subscriptions {
+            if subscription.pattern.matches(path) {
+                (subscription.callback)(path, &change)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies a sequence of log entries the same way `State::replay` does.
+    pub fn replay(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        while !reader.eof()? {
+            let entry = LogEntry::deserialize_nested(LogEntry::WIRE_TYPE, reader)?;
+            self.apply(&entry)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Follows `path` the way log entries do, enums taking the tag of their active variant first.
+fn value_at<'a>(value: &'a Value, path: &[u32]) -> Option<&'a Value> {
+    let (&tag, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return Some(value),
+    };
+
+    match value {
+        Value::Enum { tag: current, .. } if tag != *current => None,
+
+        Value::Enum { .. } => {
+            let (&field_tag, rest) = rest.split_first()?;
+            value_at(value.get(field_tag)?, rest)
+        }
+
+        value => value_at(value.get(tag)?, rest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        de::{Deserialize, Reader},
+        log::loggers::BufferLogger,
+        meta::HasMeta,
+        rt::{LoggerHandle, Runtime},
+        state::State,
+        steit_derive,
+        test_util::Point,
+        types::{List, Map},
+    };
+
+    use super::{Change, Observer, PathPattern, Segment};
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    enum Shape {
+        #[steit(tag = 0)]
+        Empty,
+        #[steit(tag = 1)]
+        Circle {
+            #[steit(tag = 0)]
+            radius: u32,
+        },
+    }
+
+    type Events<T> = Rc<RefCell<Vec<(Vec<u32>, Change<T>)>>>;
+
+    fn with_logger<T: State>() -> (T, LoggerHandle<BufferLogger>) {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        (T::with_runtime(runtime), logger)
+    }
+
+    fn observe<T: State + HasMeta, V: Deserialize + 'static>(
+        observer: &mut Observer<T>,
+        pattern: &str,
+    ) -> Events<V> {
+        let events = Events::default();
+        let recorded = events.clone();
+
+        observer.on_change(pattern.parse::<PathPattern>().unwrap(), move |event| {
+            recorded
+                .borrow_mut()
+                .push((event.path.to_vec(), event.change));
+        });
+
+        events
+    }
+
+    fn replay<T: State + HasMeta>(observer: &mut Observer<T>, bytes: &[u8]) {
+        observer.replay(&mut Reader::new(bytes)).unwrap();
+    }
+
+    #[test]
+    fn path_pattern() {
+        let pattern: PathPattern = "/2/*/0".parse().unwrap();
+        assert_eq!(
+            pattern.segments(),
+            &[Segment::Tag(2), Segment::Any, Segment::Tag(0)]
+        );
+        assert_eq!(pattern.to_string(), "2/*/0");
+        assert!(pattern.matches(&[2, 7, 0]));
+        assert!(!pattern.matches(&[2, 7]));
+        assert!(!pattern.matches(&[3, 7, 0]));
+        assert!(pattern.overlaps(&[2, 7]));
+        assert!(pattern.overlaps(&[2, 7, 0, 1]));
+        assert!(!pattern.overlaps(&[2, 7, 1]));
+        assert_eq!("".parse::<PathPattern>().unwrap(), PathPattern::root());
+        assert_eq!(PathPattern::from([1, 2]), "1/2".parse().unwrap());
+        assert!("1/x".parse::<PathPattern>().is_err());
+    }
+
+    #[test]
+    fn field_updates() {
+        let (mut list, logger) = with_logger::<List<Point>>();
+        list.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        list.push_with(|runtime| Point::new(runtime, 4, 5, 6));
+        let mut observer = Observer::new(List::<Point>::new(Runtime::new())).unwrap();
+        replay(&mut observer, &logger.lock().unwrap().bytes());
+
+        let events = observe::<_, i32>(&mut observer, "*/1");
+        let bytes_len = logger.lock().unwrap().bytes().len();
+        list.get_mut(1).unwrap().set_y(-7);
+        list.get_mut(0).unwrap().set_x(9);
+        replay(&mut observer, &logger.lock().unwrap().bytes()[bytes_len..]);
+
+        assert_eq!(
+            *events.borrow(),
+            [(
+                vec![1, 1],
+                Change::Update {
+                    old: Some(5),
+                    new: -7
+                }
+            )]
+        );
+        assert_eq!(observer.state()[1].y, -7);
+        assert_eq!(observer.state()[0].x, 9);
+    }
+
+    #[test]
+    fn observe_after_skipped_entries() {
+        let (mut point, logger) = with_logger::<Point>();
+        let mut observer = Observer::new(Point::with_runtime(Runtime::new())).unwrap();
+        let xs = observe::<_, i32>(&mut observer, "0");
+
+        point.set_y(2);
+        replay(&mut observer, &logger.lock().unwrap().pluck_bytes());
+
+        let ys = observe::<_, i32>(&mut observer, "1");
+        point.set_x(1).set_y(3);
+        replay(&mut observer, &logger.lock().unwrap().pluck_bytes());
+
+        assert_eq!(
+            *xs.borrow(),
+            [(
+                vec![0],
+                Change::Update {
+                    old: Some(0),
+                    new: 1
+                }
+            )]
+        );
+        assert_eq!(
+            *ys.borrow(),
+            [(
+                vec![1],
+                Change::Update {
+                    old: Some(2),
+                    new: 3
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn list_push_pop() {
+        let (mut list, logger) = with_logger::<List<Point>>();
+        let mut observer = Observer::new(List::<Point>::new(Runtime::new())).unwrap();
+        let events = observe::<_, Point>(&mut observer, "");
+
+        list.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        list.push_with(|runtime| Point::new(runtime, 4, 5, 6));
+        list.pop();
+        replay(&mut observer, &logger.lock().unwrap().bytes());
+
+        let changes: Vec<_> = events
+            .borrow()
+            .iter()
+            .map(|(_, change)| match change {
+                Change::ListPush { index, item } => ("push", *index, item.x),
+                Change::ListPop { index, item } => ("pop", *index, item.x),
+                _ => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(changes, [("push", 0, 1), ("push", 1, 4), ("pop", 1, 4)]);
+        assert_eq!(observer.state().len(), 1);
+    }
+
+    #[test]
+    fn map_insert_remove() {
+        let (mut map, logger) = with_logger::<Map<u32, i32>>();
+        let mut observer = Observer::new(Map::<u32, i32>::new(Runtime::new())).unwrap();
+        let inserted = observe::<_, i32>(&mut observer, "*");
+        let removed = observe::<_, i32>(&mut observer, "");
+
+        map.insert(3, 10);
+        map.insert(3, 20);
+        map.remove(&3);
+        replay(&mut observer, &logger.lock().unwrap().bytes());
+
+        assert_eq!(
+            *inserted.borrow(),
+            [
+                (vec![3], Change::Update { old: None, new: 10 }),
+                (
+                    vec![3],
+                    Change::Update {
+                        old: Some(10),
+                        new: 20
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            *removed.borrow(),
+            [(vec![], Change::MapRemove { key: 3, value: 20 })]
+        );
+    }
+
+    #[test]
+    fn variant_fields() {
+        let (mut shape, logger) = with_logger::<Shape>();
+        let mut observer = Observer::new(Shape::with_runtime(Runtime::new())).unwrap();
+        let variants = observe::<_, Shape>(&mut observer, "");
+        let radiuses = observe::<_, u32>(&mut observer, "1/0");
+
+        shape.set_circle_radius(3);
+        shape.circle_radius_mut().unwrap().set(5);
+        replay(&mut observer, &logger.lock().unwrap().bytes());
+
+        match &variants.borrow()[..] {
+            [(_, Change::Update { old, new })] => {
+                assert!(matches!(old, Some(Shape::Empty { .. })));
+                assert!(matches!(new, Shape::Circle { radius: 0, .. }));
+            }
+
+            events => panic!("unexpected events {:?}", events),
+        }
+
+        // Switching variants is logged apart from setting the field
+        let radiuses: Vec<_> = radiuses
+            .borrow()
+            .iter()
+            .map(|(_, change)| change.clone())
+            .collect();
+
+        assert_eq!(
+            radiuses,
+            [
+                Change::Update {
+                    old: Some(0),
+                    new: 3
+                },
+                Change::Update {
+                    old: Some(3),
+                    new: 5
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn mismatched_type() {
+        let (mut point, logger) = with_logger::<Point>();
+        let mut observer = Observer::new(Point::with_runtime(Runtime::new())).unwrap();
+        observe::<_, Point>(&mut observer, "0");
+
+        point.set_x(1);
+        let bytes = logger.lock().unwrap().bytes();
+        assert!(observer.replay(&mut Reader::new(&*bytes)).is_err());
+        assert_eq!(observer.state().x, 1);
+    }
+}
@@ -0,0 +1,269 @@
+use std::io;
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::LogEntry,
+    wire_fmt::HasWireType,
+};
+
+use super::{
+    decode::{env, expect_arg},
+    schema::{Schema, Type},
+    value::Value,
+};
+
+impl Schema {
+    /// Applies a single log entry to `value`, which must be of the root type.
+    pub fn apply(&self, value: &mut Value, entry: &LogEntry) -> io::Result<()> {
+        let (path, kind) = match entry {
+            LogEntry::Update { path, value, .. } => (path, Change::Update(value.as_raw())),
+            LogEntry::ListPush { path, item, .. } => (path, Change::ListPush(item.as_raw())),
+            LogEntry::ListPop { path, .. } => (path, Change::ListPop),
+            LogEntry::MapRemove { path, key, .. } => (path, Change::MapRemove(*key)),
+            // Values can't be encoded back, so there is nothing to check against
+            LogEntry::Checksum { .. } => return Ok(()),
+        };
+
+        self.handle(value, self.root(), path, kind)
+    }
+
+    /// Replays a sequence of log entries on `value`, the same way `State::replay` does.
+    pub fn replay(&self, value: &mut Value, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        while !reader.eof()? {
+            let entry = LogEntry::deserialize_nested(LogEntry::WIRE_TYPE, reader)?;
+            self.apply(value, &entry)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle(&self, value: &mut Value, ty: &Type, path: &[u32], change: Change) -> io::Result<()> {
+        let (&tag, path) = match path.split_first() {
+            Some(split) => split,
+            None => return self.handle_here(value, ty, change),
+        };
+
+        match (value, ty) {
+            (Value::Struct { fields }, Type::Ref(name, args)) => {
+                let r#struct = self.expect_struct(name.rust)?;
+                let env = env(r#struct.type_params, args);
+
+                match (
+                    fields.get_mut(&tag),
+                    self.field_type(r#struct.fields, tag, &env)?,
+                ) {
+                    (Some(value), Some(ty)) => self.handle(value, &ty, path, change),
+                    _ => Err(unexpected_tag(tag)),
+                }
+            }
+
+            (
+                Value::Enum {
+                    tag: current,
+                    fields,
+                },
+                Type::Ref(name, args),
+            ) => {
+                if tag != *current {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unexpected variant tag {}", tag),
+                    ));
+                }
+
+                let r#enum = self.expect_enum(name.rust)?;
+                let env = env(r#enum.type_params, args);
+                let variant = r#enum.variants.iter().find(|variant| variant.tag == tag);
+
+                let (&tag, path) = match path.split_first() {
+                    Some(split) => split,
+
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "`LogEntryKind::Update` is not supported on variants but their enums",
+                        ));
+                    }
+                };
+
+                let ty = match variant {
+                    Some(variant) => self.field_type(variant.ty.fields, tag, &env)?,
+                    None => None,
+                };
+
+                match (fields.get_mut(&tag), ty) {
+                    (Some(value), Some(ty)) => self.handle(value, &ty, path, change),
+                    _ => Err(unexpected_tag(tag)),
+                }
+            }
+
+            (Value::List(items), Type::Ref(_, args)) => match items.get_mut(tag as usize) {
+                Some(item) => self.handle(item, expect_arg(args, 0)?, path, change),
+
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("index {} out of bounds", tag),
+                )),
+            },
+
+            (Value::Map(entries), Type::Ref(_, args)) => {
+                let value_ty = expect_arg(args, 0)?;
+
+                if let Some(value) = entries.get_mut(&tag) {
+                    self.handle(value, value_ty, path, change)
+                } else if let (Change::Update(bytes), true) = (&change, path.is_empty()) {
+                    let mut value = self.default_value(value_ty)?;
+                    self.merge(&mut value, value_ty, &mut Reader::new(*bytes))?;
+                    entries.insert(tag, value);
+                    Ok(())
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("key {} not found", tag),
+                    ))
+                }
+            }
+
+            _ => Err(unexpected_tag(tag)),
+        }
+    }
+
+    fn handle_here(&self, value: &mut Value, ty: &Type, change: Change) -> io::Result<()> {
+        match (change, value) {
+            (Change::Update(bytes), value) => {
+                let mut new_value = self.default_value(ty)?;
+                self.merge(&mut new_value, ty, &mut Reader::new(bytes))?;
+                *value = new_value;
+                Ok(())
+            }
+
+            (Change::ListPush(bytes), Value::List(items)) => {
+                let item_ty = expect_arg(ty.args(), 0)?;
+                let mut item = self.default_value(item_ty)?;
+                self.merge(&mut item, item_ty, &mut Reader::new(bytes))?;
+                items.push(item);
+                Ok(())
+            }
+
+            (Change::ListPop, Value::List(items)) => match items.pop() {
+                Some(_) => Ok(()),
+
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "cannot pop from an empty `List`",
+                )),
+            },
+
+            (Change::MapRemove(key), Value::Map(entries)) => match entries.swap_remove(&key) {
+                Some(_) => Ok(()),
+
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("key {} not found", key),
+                )),
+            },
+
+            (change, _) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{:?} is not supported on `{}`", change, ty.name().rust),
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Change<'a> {
+    Update(&'a [u8]),
+    ListPush(&'a [u8]),
+    ListPop,
+    MapRemove(u32),
+}
+
+fn unexpected_tag(tag: u32) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected tag {}", tag),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        de::Reader,
+        log::loggers::BufferLogger,
+        rt::{LoggerHandle, Runtime},
+        state::State,
+        test_util::{serialize, Point},
+        types::{List, Map},
+    };
+
+    use super::super::{Schema, Type, Value};
+
+    fn with_logger<T: State>() -> (T, LoggerHandle<BufferLogger>) {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        (T::with_runtime(runtime), logger)
+    }
+
+    fn replay(schema: &Schema, value: &mut Value, bytes: &[u8]) {
+        schema.replay(value, &mut Reader::new(bytes)).unwrap();
+    }
+
+    #[test]
+    fn replay_list() {
+        let (mut list, logger) = with_logger::<List<Point>>();
+        let schema = Schema::of::<List<Point>>().unwrap();
+        let mut value = schema.default_value(schema.root()).unwrap();
+
+        list.push_with(|runtime| Point::new(runtime, -1, -1, -1));
+        list.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        list.get_mut(1).unwrap().set_y(-7);
+        list.pop();
+        list.get_mut(0).unwrap().set_x(5);
+
+        replay(&schema, &mut value, &logger.lock().unwrap().bytes());
+        assert_eq!(value.to_bytes(), serialize(list));
+    }
+
+    #[test]
+    fn replay_map() {
+        let (mut map, logger) = with_logger::<Map<u32, List<i32>>>();
+        let schema = Schema::of::<Map<u32, List<i32>>>().unwrap();
+        let mut value = schema.default_value(schema.root()).unwrap();
+
+        map.insert_with(1, List::new);
+        map.insert_with(3, List::new);
+        map.insert_with(7, List::new);
+        map.get_mut(&3).unwrap().push(-3);
+        map.remove(&1);
+
+        replay(&schema, &mut value, &logger.lock().unwrap().bytes());
+        assert_eq!(value.to_bytes(), serialize(map));
+        assert_eq!(value.get_path(vec![3, 0]), Some(&Value::Int(-3)));
+    }
+
+    #[test]
+    fn replay_update_nested() {
+        let schema = Schema::of::<List<Point>>().unwrap();
+        let point = schema.decode_type(&Type::of::<Point>(), &mut Reader::new(&[0, 1][..]));
+        let mut value = Value::List(vec![point.unwrap()]);
+        replay(&schema, &mut value, &[8, 0, 2, 2, 0, 2, 10, 1, 100]);
+        assert_eq!(value.get_path(vec![0, 2]), Some(&Value::Int(50)));
+        assert_eq!(value.get_path(vec![0, 0]), Some(&Value::Int(-1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "index 0 out of bounds")]
+    fn replay_update_out_of_bounds() {
+        let schema = Schema::of::<List<i32>>().unwrap();
+        let mut value = Value::List(Vec::new());
+        replay(&schema, &mut value, &[7, 0, 2, 1, 0, 10, 1, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot pop from an empty `List`")]
+    fn replay_pop_empty() {
+        let schema = Schema::of::<List<i32>>().unwrap();
+        let mut value = Value::List(Vec::new());
+        replay(&schema, &mut value, &[4, 9, 2, 0]);
+    }
+}
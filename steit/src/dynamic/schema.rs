@@ -0,0 +1,198 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    io, ptr,
+};
+
+use crate::{
+    meta::{FieldTypeMeta, HasMeta, MessageMeta, MetaLink, NameMeta, TypeMeta},
+    wire_fmt::WireType,
+};
+
+/// A fully resolved type, i.e. a [`TypeMeta`] with all of its type parameters substituted.
+///
+/// [`TypeMeta`]: ../meta/enum.TypeMeta.html
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Type {
+    Primitive(&'static NameMeta, WireType),
+    Ref(&'static NameMeta, Vec<Type>),
+}
+
+impl Type {
+    pub fn of<T: HasMeta>() -> Self {
+        Self::from_meta(T::TYPE)
+    }
+
+    /// Resolves a [`TypeMeta`] which is expected to have no free type parameters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a type parameter is found.
+    ///
+    /// [`TypeMeta`]: ../meta/enum.TypeMeta.html
+    pub fn from_meta(ty: &'static TypeMeta) -> Self {
+        Self::resolve_type(ty, &[]).unwrap()
+    }
+
+    pub(super) fn resolve(ty: &'static FieldTypeMeta, env: &[(&str, Type)]) -> io::Result<Self> {
+        match ty {
+            FieldTypeMeta::Type(ty) => Self::resolve_type(ty, env),
+
+            FieldTypeMeta::TypeParam(type_param) => env
+                .iter()
+                .find(|(name, _)| name == type_param)
+                .map(|(_, ty)| ty.clone())
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("unbound type parameter `{}`", type_param),
+                    )
+                }),
+        }
+    }
+
+    fn resolve_type(ty: &'static TypeMeta, env: &[(&str, Type)]) -> io::Result<Self> {
+        match ty {
+            TypeMeta::Primitive(name, wire_type) => Ok(Type::Primitive(name, *wire_type)),
+
+            TypeMeta::Ref(name, args) => {
+                let mut resolved_args = Vec::with_capacity(args.len());
+
+                for arg in args.iter() {
+                    resolved_args.push(Self::resolve(arg, env)?);
+                }
+
+                Ok(Type::Ref(name, resolved_args))
+            }
+        }
+    }
+
+    pub fn name(&self) -> &'static NameMeta {
+        match self {
+            Type::Primitive(name, _) | Type::Ref(name, _) => name,
+        }
+    }
+
+    pub fn args(&self) -> &[Type] {
+        match self {
+            Type::Primitive(_, _) => &[],
+            Type::Ref(_, args) => args,
+        }
+    }
+
+    pub fn wire_type(&self) -> WireType {
+        match self {
+            Type::Primitive(_, wire_type) => *wire_type,
+            Type::Ref(_, _) => WireType::Sized,
+        }
+    }
+}
+
+/// A runtime schema holding every message reachable from a root type.
+///
+/// Messages are looked up by their Rust names,
+/// so different messages sharing a name, e.g. from different modules, are rejected.
+#[derive(Clone, Debug)]
+pub struct Schema {
+    root: Type,
+    messages: HashMap<&'static str, &'static MessageMeta>,
+}
+
+impl Schema {
+    pub fn of<T: HasMeta>() -> io::Result<Self> {
+        Self::from_link(T::LINK)
+    }
+
+    pub fn from_link(link: &'static MetaLink) -> io::Result<Self> {
+        let mut schema = Self {
+            root: Type::from_meta(link.r#type),
+            messages: HashMap::new(),
+        };
+
+        schema.add_link(link)?;
+        Ok(schema)
+    }
+
+    #[cfg(feature = "json")]
+    pub(super) fn from_parts(
+        root: Type,
+        messages: HashMap<&'static str, &'static MessageMeta>,
+    ) -> Self {
+        Self { root, messages }
+    }
+
+    /// Adds all messages reachable from `link`, which makes them decodable as non-root types.
+    pub fn add_link(&mut self, link: &'static MetaLink) -> io::Result<()> {
+        let mut visited_types = HashSet::new();
+        visit_link(link, &mut visited_types, &mut self.messages)
+    }
+
+    pub fn root(&self) -> &Type {
+        &self.root
+    }
+
+    pub fn message(&self, name: &str) -> Option<&'static MessageMeta> {
+        self.messages.get(name).copied()
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = &'static MessageMeta> + '_ {
+        self.messages.values().copied()
+    }
+
+    pub(super) fn expect_message(&self, name: &str) -> io::Result<&'static MessageMeta> {
+        self.message(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("message `{}` not found in schema", name),
+            )
+        })
+    }
+}
+
+fn visit_link(
+    link: &'static MetaLink,
+    visited_types: &mut HashSet<&'static TypeMeta>,
+    messages: &mut HashMap<&'static str, &'static MessageMeta>,
+) -> io::Result<()> {
+    if let Some(msg) = &link.msg {
+        add_message(messages, msg)?;
+    }
+
+    if visited_types.insert(link.r#type) {
+        for &link in (link.links)() {
+            visit_link(link, visited_types, messages)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Keys `msg` by its Rust name, failing if a different message already has it,
+/// as it would otherwise be decoded with the layout of the other one.
+pub(super) fn add_message(
+    messages: &mut HashMap<&'static str, &'static MessageMeta>,
+    msg: &'static MessageMeta,
+) -> io::Result<()> {
+    match messages.entry(msg.rust_name()) {
+        Entry::Vacant(entry) => {
+            entry.insert(msg);
+        }
+
+        Entry::Occupied(entry) => {
+            let existing: &MessageMeta = entry.get();
+
+            if !ptr::eq(existing, msg) && existing != msg {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "different messages share the name `{}`, in `{}` and `{}`",
+                        msg.rust_name(),
+                        existing.module(),
+                        msg.module(),
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
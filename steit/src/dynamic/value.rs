@@ -0,0 +1,245 @@
+use std::io;
+
+use indexmap::IndexMap;
+
+use crate::{
+    ser::Serialize,
+    wire_fmt::{self, WireType},
+};
+
+/// A schema-less representation of any steit value.
+///
+/// `Struct` and `Enum` fields as well as `Map` entries are keyed by their tags,
+/// so a [`Schema`] is needed to make sense of them, but not to serialize them back.
+///
+/// [`Schema`]: struct.Schema.html
+#[derive(Clone, PartialEq, Debug)]
+pub enum Value {
+    Bool(bool),
+    UInt(u64),
+    Int(i64),
+    String(String),
+    Bytes(Vec<u8>),
+    // `Vec`, arrays and `List`
+    List(Vec<Value>),
+    Map(IndexMap<u32, Value>),
+    Option(Option<Box<Value>>),
+    Struct {
+        fields: IndexMap<u32, Value>,
+    },
+    Enum {
+        tag: u32,
+        fields: IndexMap<u32, Value>,
+    },
+}
+
+impl Value {
+    pub fn wire_type(&self) -> WireType {
+        match self {
+            Value::Bool(_) | Value::UInt(_) | Value::Int(_) => WireType::Varint,
+            _ => WireType::Sized,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Value::UInt(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Gets a field of a `Struct` or of the active variant of an `Enum`,
+    /// an item of a `List` or an entry of a `Map`.
+    pub fn get(&self, tag: u32) -> Option<&Value> {
+        match self {
+            Value::Struct { fields } | Value::Enum { fields, .. } | Value::Map(fields) => {
+                fields.get(&tag)
+            }
+            Value::List(items) => items.get(tag as usize),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, tag: u32) -> Option<&mut Value> {
+        match self {
+            Value::Struct { fields } | Value::Enum { fields, .. } | Value::Map(fields) => {
+                fields.get_mut(&tag)
+            }
+            Value::List(items) => items.get_mut(tag as usize),
+            _ => None,
+        }
+    }
+
+    pub fn get_path(&self, path: impl IntoIterator<Item = u32>) -> Option<&Value> {
+        let mut value = self;
+
+        for tag in path {
+            value = value.get(tag)?;
+        }
+
+        Some(value)
+    }
+
+    pub fn compute_size(&self) -> u32 {
+        match self {
+            Value::Bool(_) => 1,
+            Value::UInt(value) => value.compute_size(),
+            Value::Int(value) => value.compute_size(),
+            Value::String(value) => value.len() as u32,
+            Value::Bytes(bytes) => bytes.len() as u32,
+
+            Value::List(items) => items
+                .iter()
+                .map(|item| item.compute_size_nested(None, false))
+                .sum(),
+
+            Value::Map(entries) => entries
+                .iter()
+                .map(|(&tag, value)| value.compute_size_nested(tag, false))
+                .sum(),
+
+            Value::Option(value) => value
+                .as_ref()
+                .map_or(0, |value| value.compute_size_nested(None, false)),
+
+            Value::Struct { fields } => compute_fields_size(fields),
+            Value::Enum { tag, fields } => tag.compute_size() + compute_fields_size(fields),
+        }
+    }
+
+    pub fn is_omissible(&self) -> bool {
+        match self {
+            Value::Bool(value) => !value,
+            Value::UInt(value) => *value == 0,
+            Value::Int(value) => *value == 0,
+            _ => self.compute_size() == 0,
+        }
+    }
+
+    pub fn compute_size_nested(&self, tag: impl Into<Option<u32>>, is_omissible: bool) -> u32 {
+        let tag = tag.into();
+
+        if tag.is_some() && is_omissible && self.is_omissible() {
+            return 0;
+        }
+
+        let mut size = self.compute_size();
+
+        if self.wire_type() == WireType::Sized {
+            size += size.compute_size();
+        }
+
+        if let Some(tag) = tag {
+            size += self.key(tag).compute_size();
+        }
+
+        size
+    }
+
+    pub fn serialize(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        match self {
+            Value::Bool(value) => value.serialize(writer),
+            Value::UInt(value) => value.serialize(writer),
+            Value::Int(value) => value.serialize(writer),
+            Value::String(value) => writer.write_all(value.as_bytes()),
+            Value::Bytes(bytes) => writer.write_all(bytes),
+
+            Value::List(items) => {
+                for item in items {
+                    item.serialize_nested(None, false, writer)?;
+                }
+
+                Ok(())
+            }
+
+            Value::Map(entries) => {
+                for (&tag, value) in entries {
+                    value.serialize_nested(tag, false, writer)?;
+                }
+
+                Ok(())
+            }
+
+            Value::Option(value) => match value {
+                Some(value) => value.serialize_nested(None, false, writer),
+                None => Ok(()),
+            },
+
+            Value::Struct { fields } => serialize_fields(fields, writer),
+
+            Value::Enum { tag, fields } => {
+                tag.serialize(writer)?;
+                serialize_fields(fields, writer)
+            }
+        }
+    }
+
+    pub fn serialize_nested(
+        &self,
+        tag: impl Into<Option<u32>>,
+        is_omissible: bool,
+        writer: &mut impl io::Write,
+    ) -> io::Result<()> {
+        let tag = tag.into();
+
+        if tag.is_some() && is_omissible && self.is_omissible() {
+            return Ok(());
+        }
+
+        if let Some(tag) = tag {
+            wire_fmt::tag(tag, self.wire_type())?.serialize(writer)?;
+        }
+
+        if self.wire_type() == WireType::Sized {
+            self.compute_size().serialize(writer)?;
+        }
+
+        self.serialize(writer)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn key(&self, tag: u32) -> u32 {
+        tag << wire_fmt::WIRE_TYPE_BITS | self.wire_type().value() as u32
+    }
+}
+
+fn compute_fields_size(fields: &IndexMap<u32, Value>) -> u32 {
+    fields
+        .iter()
+        .map(|(&tag, value)| value.compute_size_nested(tag, true))
+        .sum()
+}
+
+fn serialize_fields(fields: &IndexMap<u32, Value>, writer: &mut impl io::Write) -> io::Result<()> {
+    for (&tag, value) in fields {
+        value.serialize_nested(tag, true, writer)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,259 @@
+use std::{collections::HashSet, fs, io, path::Path};
+
+use crate::meta::{HasMeta, MetaLink};
+
+use super::{gen_util, generator::Generator, setting::Setting};
+
+/// Generates code for several root types at once, typically from a `build.rs` or an xtask.
+///
+/// ```ignore
+/// GenBuilder::new(CSharpGenerator, Setting::new(&out_dir, CSharpSetting::new("Game")))
+///     .root::<Match>()
+///     .root::<Lobby>()
+///     .clean_stale(true)
+///     .build()?;
+/// ```
+pub struct GenBuilder<G: Generator> {
+    generator: G,
+    setting: Setting<G::Setting>,
+    roots: Vec<&'static MetaLink>,
+    clean_stale: bool,
+}
+
+/// Names of the files touched by [`GenBuilder::build`], without their directory.
+///
+/// [`GenBuilder::build`]: struct.GenBuilder.html#method.build
+#[derive(Default, Debug)]
+pub struct GenReport {
+    pub written: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl<G: Generator> GenBuilder<G> {
+    pub fn new(generator: G, setting: Setting<G::Setting>) -> Self {
+        Self {
+            generator,
+            setting,
+            roots: Vec::new(),
+            clean_stale: false,
+        }
+    }
+
+    pub fn root<T: HasMeta>(self) -> Self {
+        self.root_link(T::LINK)
+    }
+
+    pub fn root_link(mut self, link: &'static MetaLink) -> Self {
+        self.roots.push(link);
+        self
+    }
+
+    /// Removes files with the generator's extension in the output directory
+    /// which have not been generated by this build, e.g. those of deleted types.
+    pub fn clean_stale(mut self, clean_stale: bool) -> Self {
+        self.clean_stale = clean_stale;
+        self
+    }
+
+    /// Generates all messages reachable from the roots.
+    ///
+    /// Files whose content would not change are left untouched so that their
+    /// modification times stay the same, which avoids needless rebuilds downstream.
+    /// Fails without writing anything if different messages share a generated name.
+    pub fn build(&self) -> io::Result<GenReport> {
+        let msgs = gen_util::collect_meta(self.roots.iter().copied(), self.setting.get_name)?;
+
+        let files = gen_util::write_files(msgs, &self.setting, G::FILE_EXTENSION, |msgs| {
+            self.generator.render(msgs, &self.setting)
+        })?;
+
+        let mut report = GenReport::default();
+        let mut file_names = HashSet::new();
+
+        for file in files {
+            if file.changed {
+                report.written.push(file.name.clone());
+            } else {
+                report.unchanged.push(file.name.clone());
+            }
+
+            file_names.insert(file.name);
+        }
+
+        if self.clean_stale {
+            report.removed = remove_stale(&self.setting.out_dir, G::FILE_EXTENSION, &file_names)?;
+        }
+
+        Ok(report)
+    }
+}
+
+fn remove_stale(
+    dir: &Path,
+    extension: &str,
+    file_names: &HashSet<String>,
+) -> io::Result<Vec<String>> {
+    let mut removed = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if !path.is_file() || path.extension() != Some(extension.as_ref()) {
+            continue;
+        }
+
+        if let Some(file_name) = path.file_name().and_then(|name| name.to_str()) {
+            if !file_names.contains(file_name) {
+                fs::remove_file(&path)?;
+                removed.push(file_name.to_string());
+            }
+        }
+    }
+
+    removed.sort();
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use crate::{
+        gen::{
+            generators::{CSharpGenerator, CSharpSetting},
+            Setting,
+        },
+        steit_derive,
+        test_util::{Foo, Point},
+        types::List,
+    };
+
+    use super::GenBuilder;
+
+    mod other {
+        use crate::steit_derive;
+
+        #[steit_derive(Debug, Serialize, Deserialize)]
+        #[steit(steit_owned)]
+        pub struct Foo {
+            #[steit(tag = 0)]
+            pub name: String,
+        }
+    }
+
+    #[steit_derive(Debug, Serialize, Deserialize)]
+    #[steit(steit_owned)]
+    struct Pair {
+        #[steit(tag = 0)]
+        left: Foo,
+        #[steit(tag = 1)]
+        right: Foo,
+    }
+
+    fn out_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("steit-gen-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn builder(dir: &PathBuf) -> GenBuilder<CSharpGenerator> {
+        let setting = Setting::new(dir, CSharpSetting::new("Steit.Test")).skip_builtins(false);
+        GenBuilder::new(CSharpGenerator, setting)
+    }
+
+    #[test]
+    fn multiple_roots() {
+        let dir = out_dir("roots");
+        let report = builder(&dir)
+            .root::<Pair>()
+            .root::<List<Point>>()
+            .build()
+            .unwrap();
+
+        assert_eq!(report.written, vec!["Foo.cs", "Pair.cs", "Point.cs"]);
+        assert!(report.unchanged.is_empty());
+        assert!(dir.join("Point.cs").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn skip_unchanged() {
+        let dir = out_dir("unchanged");
+        builder(&dir).root::<Pair>().build().unwrap();
+        let report = builder(&dir).root::<Pair>().build().unwrap();
+
+        assert!(report.written.is_empty());
+        assert_eq!(report.unchanged, vec!["Foo.cs", "Pair.cs"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn clean_stale() {
+        let dir = out_dir("stale");
+        builder(&dir)
+            .root::<Pair>()
+            .root::<Point>()
+            .build()
+            .unwrap();
+        fs::write(dir.join("Notes.txt"), "keep me").unwrap();
+
+        let report = builder(&dir)
+            .root::<Point>()
+            .clean_stale(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(report.removed, vec!["Foo.cs", "Pair.cs"]);
+        assert!(dir.join("Notes.txt").exists());
+        assert!(!dir.join("Pair.cs").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn single_file() {
+        let dir = out_dir("single");
+        builder(&dir).root::<Point>().build().unwrap();
+
+        let setting = Setting::new(&dir, CSharpSetting::new("Steit.Test"))
+            .skip_builtins(false)
+            .single_file("Generated");
+
+        let report = GenBuilder::new(CSharpGenerator, setting)
+            .root::<Pair>()
+            .root::<Point>()
+            .clean_stale(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(report.written, vec!["Generated.cs"]);
+        assert_eq!(report.removed, vec!["Point.cs"]);
+
+        let source = fs::read_to_string(dir.join("Generated.cs")).unwrap();
+        assert!(source.contains("class Foo : IState"));
+        assert!(source.contains("class Pair : IState"));
+        assert!(source.contains("class Point : IState"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn name_collision() {
+        let dir = out_dir("collision");
+        let error = builder(&dir)
+            .root::<Pair>()
+            .root::<other::Foo>()
+            .build()
+            .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "different messages are generated under the same name: Foo",
+        );
+
+        assert!(!dir.join("Pair.cs").exists());
+    }
+}
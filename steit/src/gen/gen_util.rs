@@ -1,31 +1,164 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fs, io,
+    path::Path,
+    ptr,
+};
 
-use crate::meta::{HasMeta, MessageMeta, MetaLink, TypeMeta};
+use crate::meta::{MessageMeta, MetaLink, TypeMeta};
 
-pub fn collect_meta<T: HasMeta>(
+use super::setting::Setting;
+
+/// Collects every message reachable from `links`, keyed by their generated names.
+pub fn collect_meta(
+    links: impl IntoIterator<Item = &'static MetaLink>,
     get_name: fn(&'static MessageMeta) -> String,
-) -> HashMap<String, &'static MessageMeta> {
+) -> io::Result<HashMap<String, &'static MessageMeta>> {
     let mut visited_types = HashSet::new();
-    let mut collected_msgs = HashMap::new();
-    visit_link(T::LINK, get_name, &mut visited_types, &mut collected_msgs);
-    collected_msgs
+    let mut collected_msgs = Vec::new();
+
+    for link in links {
+        visit_link(link, &mut visited_types, &mut collected_msgs);
+    }
+
+    name_meta(collected_msgs, get_name)
+}
+
+/// Keys messages by their generated names,
+/// failing if different messages would be generated under the same name.
+pub fn name_meta(
+    msgs: impl IntoIterator<Item = &'static MessageMeta>,
+    get_name: fn(&'static MessageMeta) -> String,
+) -> io::Result<HashMap<String, &'static MessageMeta>> {
+    let mut named_msgs = HashMap::new();
+    let mut collisions = Vec::new();
+
+    for msg in msgs {
+        match named_msgs.entry(get_name(msg)) {
+            Entry::Vacant(entry) => {
+                entry.insert(msg);
+            }
+
+            Entry::Occupied(entry) => {
+                let existing: &MessageMeta = entry.get();
+
+                if !ptr::eq(existing, msg) && existing != msg && !collisions.contains(entry.key()) {
+                    collisions.push(entry.key().clone());
+                }
+            }
+        }
+    }
+
+    if !collisions.is_empty() {
+        collisions.sort();
+
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "different messages are generated under the same name: {}",
+                collisions.join(", "),
+            ),
+        ));
+    }
+
+    Ok(named_msgs)
+}
+
+/// Groups the messages which are not skipped into the files they are generated to.
+/// Files and the messages in each of them are sorted by name.
+fn group_files<T>(
+    msgs: HashMap<String, &'static MessageMeta>,
+    setting: &Setting<T>,
+    extension: &str,
+) -> Vec<(String, Vec<(String, &'static MessageMeta)>)> {
+    let mut msgs: Vec<_> = msgs
+        .into_iter()
+        .filter(|(name, msg)| !setting.skips(name, msg))
+        .collect();
+
+    msgs.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    match &setting.single_file {
+        Some(_) if msgs.is_empty() => Vec::new(),
+        Some(file_name) => vec![(format!("{}.{}", file_name, extension), msgs)],
+
+        None => msgs
+            .into_iter()
+            .map(|(name, msg)| (format!("{}.{}", name, extension), vec![(name, msg)]))
+            .collect(),
+    }
+}
+
+/// A file written by [`write_files`], along with the messages generated into it.
+///
+/// [`write_files`]: fn.write_files.html
+pub struct GeneratedFile {
+    pub name: String,
+    pub msg_names: Vec<String>,
+    // Whether the content differs from what was there before
+    pub changed: bool,
+}
+
+/// Renders the messages which are not skipped into their files in the output directory,
+/// leaving untouched those whose content would not change.
+pub fn write_files<T>(
+    msgs: HashMap<String, &'static MessageMeta>,
+    setting: &Setting<T>,
+    extension: &str,
+    render: impl Fn(&[&'static MessageMeta]) -> String,
+) -> io::Result<Vec<GeneratedFile>> {
+    fs::create_dir_all(&setting.out_dir)?;
+
+    let mut files = Vec::new();
+
+    for (file_name, msgs) in group_files(msgs, setting, extension) {
+        let (msg_names, msgs): (Vec<_>, Vec<_>) = msgs.into_iter().unzip();
+        let changed = write_if_changed(&setting.out_dir.join(&file_name), &render(&msgs))?;
+
+        files.push(GeneratedFile {
+            name: file_name,
+            msg_names,
+            changed,
+        });
+    }
+
+    Ok(files)
+}
+
+/// Writes `content` to `path` unless the file already has the exact same content.
+/// Returns whether the file was written.
+fn write_if_changed(path: &Path, content: &str) -> io::Result<bool> {
+    match fs::read(path) {
+        Ok(existing) if existing == content.as_bytes() => Ok(false),
+
+        Ok(_) => {
+            fs::write(path, content)?;
+            Ok(true)
+        }
+
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            fs::write(path, content)?;
+            Ok(true)
+        }
+
+        Err(error) => Err(error),
+    }
 }
 
 fn visit_link(
     entry: &'static MetaLink,
-    get_name: fn(&'static MessageMeta) -> String,
     visited_types: &mut HashSet<&'static TypeMeta>,
-    collected_msgs: &mut HashMap<String, &'static MessageMeta>,
+    collected_msgs: &mut Vec<&'static MessageMeta>,
 ) {
     if let Some(msg) = &entry.msg {
-        collected_msgs.entry(get_name(msg)).or_insert(msg);
+        collected_msgs.push(msg);
     }
 
     if !visited_types.contains(entry.r#type) {
         visited_types.insert(entry.r#type);
 
         for &link in (entry.links)() {
-            visit_link(link, get_name, visited_types, collected_msgs);
+            visit_link(link, visited_types, collected_msgs);
         }
     }
 }
@@ -1,62 +1,90 @@
-use std::{
-    fs::File,
-    io::{self, Write},
-};
+use std::{collections::HashMap, io};
 
-use crate::meta::{EnumMeta, HasMeta, MessageMeta, StructMeta};
+use crate::{
+    dynamic::Schema,
+    meta::{EnumMeta, HasMeta, MessageMeta, StructMeta},
+};
 
 use super::{gen_util, setting::Setting, writer::Writer};
 
 pub trait Generator {
     const INDENT_SIZE: usize;
+    const FILE_EXTENSION: &'static str;
 
     type Setting;
 
-    fn gen_struct(
-        &self,
-        r#struct: &StructMeta,
-        is_variant: bool,
-        setting: &Self::Setting,
-        writer: &mut Writer,
-    );
+    /// Generates a top-level struct. Variants are generated by `gen_enum` along with their enums.
+    fn gen_struct(&self, r#struct: &StructMeta, setting: &Self::Setting, writer: &mut Writer);
 
     fn gen_enum(&self, r#enum: &EnumMeta, setting: &Self::Setting, writer: &mut Writer);
 
     fn generate<T: HasMeta>(&self, setting: &Setting<Self::Setting>) -> io::Result<Vec<String>> {
-        let mut generated_names = Vec::new();
+        let msgs = gen_util::collect_meta(vec![T::LINK], setting.get_name)?;
+        self.generate_messages(msgs, setting)
+    }
 
-        for (name, meta) in gen_util::collect_meta::<T>(setting.get_name) {
-            if meta.is_builtin() && setting.skip_builtins {
-                continue;
-            }
+    /// Generates every message of a runtime schema, e.g. one loaded from a file.
+    fn generate_schema(
+        &self,
+        schema: &Schema,
+        setting: &Setting<Self::Setting>,
+    ) -> io::Result<Vec<String>> {
+        let msgs = gen_util::name_meta(schema.messages(), setting.get_name)?;
+        self.generate_messages(msgs, setting)
+    }
 
-            if setting.skip_names.contains(&name) {
-                continue;
-            }
+    fn generate_messages(
+        &self,
+        msgs: HashMap<String, &'static MessageMeta>,
+        setting: &Setting<Self::Setting>,
+    ) -> io::Result<Vec<String>> {
+        let files = gen_util::write_files(msgs, setting, Self::FILE_EXTENSION, |msgs| {
+            self.render(msgs, setting)
+        })?;
+
+        Ok(files.into_iter().flat_map(|file| file.msg_names).collect())
+    }
+
+    /// Writes what comes before the messages of a file, e.g. imports.
+    fn gen_file_opening(
+        &self,
+        _msgs: &[&'static MessageMeta],
+        _setting: &Self::Setting,
+        _writer: &mut Writer,
+    ) {
+    }
+
+    /// Writes what comes after the messages of a file.
+    fn gen_file_closing(
+        &self,
+        _msgs: &[&'static MessageMeta],
+        _setting: &Self::Setting,
+        _writer: &mut Writer,
+    ) {
+    }
 
-            let mut writer = Writer::new(Self::INDENT_SIZE);
+    /// Renders the source of a file containing `msgs`.
+    fn render(&self, msgs: &[&'static MessageMeta], setting: &Setting<Self::Setting>) -> String {
+        let mut writer = Writer::new(Self::INDENT_SIZE);
+        self.gen_file_opening(msgs, setting, &mut writer);
 
-            match meta {
+        for (index, msg) in msgs.iter().enumerate() {
+            if index > 0 {
+                writer.newline();
+            }
+
+            match msg {
                 MessageMeta::Struct(r#struct) => {
-                    self.gen_struct(&r#struct, false, &*setting, &mut writer);
+                    self.gen_struct(r#struct, setting, &mut writer);
                 }
 
                 MessageMeta::Enum(r#enum) => {
-                    self.gen_enum(&r#enum, &*setting, &mut writer);
+                    self.gen_enum(r#enum, setting, &mut writer);
                 }
             };
-
-            let source = writer.end();
-
-            let path = setting.out_dir.join(format!("{}.cs", name));
-            let file = File::create(path)?;
-
-            let mut writer = io::BufWriter::new(file);
-
-            writer.write_all(source.as_bytes())?;
-            generated_names.push(name);
         }
 
-        Ok(generated_names)
+        self.gen_file_closing(msgs, setting, &mut writer);
+        writer.end()
     }
 }
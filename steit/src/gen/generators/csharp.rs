@@ -7,6 +7,11 @@ use crate::{
 pub struct CSharpSetting {
     namespace: String,
     used_namespaces: Vec<String>,
+    module_namespaces: Vec<(String, String)>,
+    nullable: bool,
+    serializable: bool,
+    unity: bool,
+    readonly_properties: bool,
 }
 
 impl CSharpSetting {
@@ -14,6 +19,11 @@ impl CSharpSetting {
         Self {
             namespace: namespace.into(),
             used_namespaces: Vec::new(),
+            module_namespaces: Vec::new(),
+            nullable: false,
+            serializable: false,
+            unity: false,
+            readonly_properties: false,
         }
     }
 
@@ -28,52 +38,150 @@ impl CSharpSetting {
 
         self
     }
-}
 
-pub struct CSharpGenerator;
+    /// Puts types declared in the Rust module `module`, or in any of its submodules,
+    /// under `namespace` instead of the default one. The longest matching module wins.
+    ///
+    /// Every mapped namespace is imported by every generated file,
+    /// so types can refer to each other across namespaces.
+    pub fn namespace_for(
+        mut self,
+        module: impl Into<String>,
+        namespace: impl Into<String>,
+    ) -> Self {
+        self.module_namespaces
+            .push((module.into(), namespace.into()));
+        self
+    }
 
-impl CSharpGenerator {
-    pub fn gen_file_opening(&self, setting: &<Self as Generator>::Setting, writer: &mut Writer) {
-        writer.writeln("using System;").newline();
+    /// Enables C# 8 nullable reference types and annotates nullable references with `?`.
+    pub fn nullable(mut self, nullable: bool) -> Self {
+        self.nullable = nullable;
+        self
+    }
 
-        let mut used_namespaces = vec![
-            "Steit.Builtins".to_string(),
-            "Steit.Codec".to_string(),
-            "Steit.Collections".to_string(),
-            "Steit.State".to_string(),
-            "Steit.State.Event".to_string(),
-        ];
+    /// Marks generated classes as `[Serializable]`.
+    pub fn serializable(mut self, serializable: bool) -> Self {
+        self.serializable = serializable;
+        self
+    }
 
-        used_namespaces.extend_from_slice(&setting.used_namespaces);
-        used_namespaces.sort();
+    /// Makes generated classes serializable by Unity,
+    /// i.e. `[Serializable]` with `[SerializeField]` on the fields backing properties.
+    pub fn unity(mut self, unity: bool) -> Self {
+        self.unity = unity;
+        self
+    }
 
-        for used_namespace in used_namespaces {
-            writer.writeln(format!("using {};", used_namespace));
+    /// Exposes properties through getters only, backed by private fields,
+    /// instead of auto-properties with a `private set`.
+    pub fn readonly_properties(mut self, readonly_properties: bool) -> Self {
+        self.readonly_properties = readonly_properties;
+        self
+    }
+
+    fn namespace_of(&self, module: &str) -> &str {
+        self.module_namespaces
+            .iter()
+            .filter(|(prefix, _)| {
+                matches!(
+                    module.strip_prefix(prefix.as_str()),
+                    Some(rest) if rest.is_empty() || rest.starts_with("::")
+                )
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(&self.namespace, |(_, namespace)| namespace)
+    }
+
+    fn nullable_suffix(&self) -> &'static str {
+        if self.nullable {
+            "?"
+        } else {
+            ""
         }
+    }
+}
+
+pub struct CSharpGenerator;
 
+impl CSharpGenerator {
+    fn gen_namespace_opening(&self, module: &str, setting: &CSharpSetting, writer: &mut Writer) {
         writer
-            .newline()
-            .writeln(format!("namespace {} {{", &setting.namespace))
+            .writeln(format!("namespace {} {{", setting.namespace_of(module)))
             .indent();
     }
 
-    pub fn gen_file_closing(&self, writer: &mut Writer) {
+    fn gen_namespace_closing(&self, writer: &mut Writer) {
         writer.outdent_writeln("}");
     }
-}
 
-impl Generator for CSharpGenerator {
-    const INDENT_SIZE: usize = 4;
+    /// Carries doc comments of Rust declarations over as XML doc comments.
+    fn gen_doc(&self, doc: Option<&str>, writer: &mut Writer) {
+        if let Some(doc) = doc {
+            writer.writeln("/// <summary>");
 
-    type Setting = CSharpSetting;
+            for line in doc.lines() {
+                writer.writeln(format!("/// {}", xml_escape(line)).trim_end());
+            }
 
-    fn gen_struct(
+            writer.writeln("/// </summary>");
+        }
+    }
+
+    fn gen_class_attributes(&self, setting: &CSharpSetting, writer: &mut Writer) {
+        if setting.serializable || setting.unity {
+            writer.writeln("[Serializable]");
+        }
+    }
+
+    /// Declares a plain C# enum, along with a reader extension so that it can be read
+    /// the same way as primitives, e.g. `reader.ReadDirection()`.
+    fn gen_varint_enum(&self, r#enum: &EnumMeta, setting: &CSharpSetting, writer: &mut Writer) {
+        let name = r#enum.name.csharp(String::from);
+
+        self.gen_namespace_opening(r#enum.module, setting, writer);
+        self.gen_doc(r#enum.name.doc, writer);
+
+        writer
+            .writeln(format!("public enum {} : UInt32 {{", name))
+            .indent();
+
+        for variant in r#enum.variants {
+            self.gen_doc(variant.doc(), writer);
+            writer.writeln(format!(
+                "{}{} = {},",
+                obsolete(variant.deprecated),
+                variant
+                    .ty
+                    .name
+                    .csharp(|name| str_util::to_camel_case(name, true)),
+                variant.tag,
+            ));
+        }
+
+        writer
+            .outdent_writeln("}")
+            .newline()
+            .writeln(format!("public static class {}Reader {{", name))
+            .indent_writeln(format!(
+                "public static {0} Read{0}(this IReader reader) {{ return ({0}) reader.ReadUInt32(); }}",
+                name,
+            ))
+            .outdent_writeln("}");
+
+        self.gen_namespace_closing(writer);
+    }
+
+    /// Declares the class of a struct, or of a variant when `container` names its enum type.
+    /// Variants keep a reference to their enum, which reports changes of their fields as well.
+    fn gen_class(
         &self,
         r#struct: &StructMeta,
-        is_variant: bool,
-        setting: &Self::Setting,
+        container: Option<&str>,
+        setting: &CSharpSetting,
         writer: &mut Writer,
     ) {
+        let is_variant = container.is_some();
         let name = r#struct.name.csharp(String::from);
         let var_name = str_util::uncap_first_char(&name);
 
@@ -84,12 +192,21 @@ impl Generator for CSharpGenerator {
         };
 
         let variant_accessibility = if is_variant { "internal" } else { "public" };
-        let fields: Vec<_> = r#struct.fields.iter().map(CSharpField::from_meta).collect();
+        let nullable = setting.nullable_suffix();
+
+        let fields: Vec<_> = r#struct
+            .fields
+            .iter()
+            .map(|field| CSharpField::from_meta(field, setting))
+            .collect();
 
         if !is_variant {
-            self.gen_file_opening(setting, writer);
+            self.gen_namespace_opening(r#struct.module, setting, writer);
         }
 
+        self.gen_doc(r#struct.doc(), writer);
+        self.gen_class_attributes(setting, writer);
+
         writer
             .writeln(format!(
                 "public sealed partial class {} : IState {{",
@@ -99,24 +216,36 @@ impl Generator for CSharpGenerator {
 
         writer.writeln("public Path Path { get; }");
 
+        if let Some(container) = container {
+            writer.writeln(format!("public {} Container {{ get; }}", container));
+        }
+
         if fields.len() > 1 {
             writer.newline();
         }
 
         // Declare properties
         for field in &fields {
-            writer.writeln(format!(
-                "public {} {} {{ get; private set; }}",
-                field.type_name, field.upper_camel_case_name,
-            ));
+            self.gen_property(
+                &field.type_name,
+                &field.upper_camel_case_name,
+                field.meta.deprecated,
+                field.meta.doc(),
+                setting,
+                writer,
+            );
         }
 
+        let container_param = match container {
+            Some(container) => format!("{} container, ", container),
+            None => String::new(),
+        };
+
         writer
             .newline()
             .writeln(format!(
-                // "{} {}(Path? path = null) {{",
-                "{} {}(Path path = null) {{",
-                variant_accessibility, name,
+                "{} {}({}Path{} path = null) {{",
+                variant_accessibility, name, container_param, nullable,
             ))
             .indent();
 
@@ -133,6 +262,10 @@ impl Generator for CSharpGenerator {
 
         writer.writeln("this.Path = path ?? Path.Root;");
 
+        if is_variant {
+            writer.writeln("this.Container = container;");
+        }
+
         if r#struct.type_params.len() > 1 && fields.len() > 1 {
             writer.newline();
         }
@@ -140,7 +273,15 @@ impl Generator for CSharpGenerator {
         // Initiate nested states
         for field in &fields {
             let init = match field.meta.ty {
-                FieldTypeMeta::Type(TypeMeta::Primitive(_, _)) => None,
+                // Strings are the only primitives which are references
+                FieldTypeMeta::Type(TypeMeta::Primitive(_, _)) => {
+                    if setting.nullable && field.type_name == "String" {
+                        Some("\"\"".to_string())
+                    } else {
+                        None
+                    }
+                }
+
                 FieldTypeMeta::Type(TypeMeta::Ref(_, _)) => Some(format!(
                     "new {}(this.Path.GetNested({}))",
                     field.type_name, field.meta.tag,
@@ -152,7 +293,7 @@ impl Generator for CSharpGenerator {
             };
 
             if let Some(init) = init {
-                writer.writeln(format!("this.{} = {};", field.upper_camel_case_name, init));
+                writer.writeln(format!("this.{} = {};", field.target_name, init));
             }
         }
 
@@ -161,9 +302,8 @@ impl Generator for CSharpGenerator {
         // Declare events
         for field in &fields {
             writer.writeln(format!(
-                // "public static event EventHandler<FieldUpdateEventArgs<{}, {}>>? On{}Update;",
-                "public static event EventHandler<FieldUpdateEventArgs<{}, {}>> On{}Update;",
-                field.type_name, type_name, field.upper_camel_case_name,
+                "public static event EventHandler<FieldUpdateEventArgs<{}, {}>>{} On{}Update;",
+                field.type_name, type_name, nullable, field.upper_camel_case_name,
             ));
         }
 
@@ -227,11 +367,15 @@ impl Generator for CSharpGenerator {
             .newline()
             .newline()
             .writeln(format!(
-                // "{} static {} Deserialize(IReader reader, Path? path = null) {{",
-                "{} static {} Deserialize(IReader reader, Path path = null) {{",
-                variant_accessibility, type_name,
+                "{} static {} Deserialize(IReader reader, {}Path{} path = null) {{",
+                variant_accessibility, type_name, container_param, nullable,
+            ))
+            .indent_writeln(format!(
+                "var {} = new {}({}path);",
+                var_name,
+                type_name,
+                if is_variant { "container, " } else { "" },
             ))
-            .indent_writeln(format!("var {} = new {}(path);", var_name, type_name))
             .writeln(format!(
                 "{}.Replace(reader, shouldNotify: false);",
                 var_name
@@ -267,8 +411,10 @@ impl Generator for CSharpGenerator {
             .outdent_writeln("}")
             .outdent_writeln("}")
             .newline()
-            // .writeln("public IState? GetNested(UInt32 tag) {")
-            .writeln("public IState GetNested(UInt32 tag) {")
+            .writeln(format!(
+                "public IState{} GetNested(UInt32 tag) {{",
+                nullable
+            ))
             .indent_writeln("switch (tag) {")
             .indent();
 
@@ -303,28 +449,31 @@ impl Generator for CSharpGenerator {
             match field.meta.ty {
                 FieldTypeMeta::Type(TypeMeta::Primitive(_, _)) => {
                     writer.writeln(format!(
-                        "case {0}: this.{1} = this.MaybeNotify({0}, reader.Read{2}(), this.{1}, On{1}Update, shouldNotify); break;",
+                        "case {0}: this.{3} = this.MaybeNotify({0}, reader.Read{2}(), this.{1}, On{1}Update, shouldNotify); break;",
                         field.meta.tag,
                         field.upper_camel_case_name,
                         field.type_name,
+                        field.target_name,
                     ));
                 }
 
                 FieldTypeMeta::Type(TypeMeta::Ref(_, _)) => {
                     writer.writeln(format!(
-                        "case {0}: this.{1} = this.MaybeNotify({0}, {2}.Deserialize(reader, this.Path.GetNested({0})), this.{1}, On{1}Update, shouldNotify); break;",
+                        "case {0}: this.{3} = this.MaybeNotify({0}, {2}.Deserialize(reader, this.Path.GetNested({0})), this.{1}, On{1}Update, shouldNotify); break;",
                         field.meta.tag,
                         field.upper_camel_case_name,
                         field.type_name,
+                        field.target_name,
                     ));
                 }
 
                 FieldTypeMeta::TypeParam(_) => {
                     writer.writeln(format!(
-                        "case {0}: this.{1} = this.MaybeNotify({0}, StateFactory.Deserialize<{2}>(reader, this.Path, {0}), this.{1}, On{1}Update, shouldNotify); break;",
+                        "case {0}: this.{3} = this.MaybeNotify({0}, StateFactory.Deserialize<{2}>(reader, this.Path, {0}), this.{1}, On{1}Update, shouldNotify); break;",
                         field.meta.tag,
                         field.upper_camel_case_name,
                         field.type_name,
+                        field.target_name,
                     ));
                 }
             };
@@ -348,9 +497,8 @@ impl Generator for CSharpGenerator {
             .writeln("TValue newValue,")
             .writeln("TValue oldValue,")
             .writeln(format!(
-                // "EventHandler<FieldUpdateEventArgs<TValue, {}>>? handler,",
-                "EventHandler<FieldUpdateEventArgs<TValue, {}>> handler,",
-                type_name
+                "EventHandler<FieldUpdateEventArgs<TValue, {}>>{} handler,",
+                type_name, nullable,
             ))
             .writeln("bool shouldNotify")
             .outdent_writeln(") {")
@@ -359,7 +507,13 @@ impl Generator for CSharpGenerator {
                 "var args = new FieldUpdateEventArgs<TValue, {}>(tag, newValue, oldValue, this);",
                 type_name
             ))
-            .writeln("handler?.Invoke(this, args);")
+            .writeln("handler?.Invoke(this, args);");
+
+        if is_variant {
+            writer.writeln("this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);");
+        }
+
+        writer
             .outdent_writeln("}")
             .newline()
             .writeln("return newValue;")
@@ -367,11 +521,132 @@ impl Generator for CSharpGenerator {
             .outdent_writeln("}");
 
         if !is_variant {
-            self.gen_file_closing(writer);
+            self.gen_namespace_closing(writer);
         }
     }
 
+    /// Declares a property which only the class itself can set, through `target_name(name)`.
+    fn gen_property(
+        &self,
+        type_name: &str,
+        name: &str,
+        deprecated: bool,
+        doc: Option<&str>,
+        setting: &CSharpSetting,
+        writer: &mut Writer,
+    ) {
+        let obsolete = obsolete(deprecated);
+
+        if setting.readonly_properties {
+            let serialize_field = if setting.unity {
+                "[UnityEngine.SerializeField] "
+            } else {
+                ""
+            };
+
+            writer.writeln(format!(
+                "{}private {} {};",
+                serialize_field,
+                type_name,
+                target_name(name, setting),
+            ));
+
+            self.gen_doc(doc, writer);
+
+            writer.writeln(format!(
+                "{}public {} {} {{ get {{ return this.{}; }} }}",
+                obsolete,
+                type_name,
+                name,
+                target_name(name, setting),
+            ));
+        } else {
+            let serialize_field = if setting.unity {
+                "[field: UnityEngine.SerializeField] "
+            } else {
+                ""
+            };
+
+            self.gen_doc(doc, writer);
+
+            writer.writeln(format!(
+                "{}{}public {} {} {{ get; private set; }}",
+                serialize_field, obsolete, type_name, name,
+            ));
+        }
+    }
+}
+
+impl Generator for CSharpGenerator {
+    const INDENT_SIZE: usize = 4;
+    const FILE_EXTENSION: &'static str = "cs";
+
+    type Setting = CSharpSetting;
+
+    fn gen_file_opening(
+        &self,
+        msgs: &[&'static MessageMeta],
+        setting: &Self::Setting,
+        writer: &mut Writer,
+    ) {
+        if setting.nullable {
+            writer.writeln("#nullable enable").newline();
+        }
+
+        // Generated code keeps handling obsolete members, only their users should be warned
+        if msgs.iter().any(|msg| has_deprecated(msg)) {
+            writer.writeln("#pragma warning disable CS0618").newline();
+        }
+
+        writer.writeln("using System;").newline();
+
+        let mut used_namespaces = vec![
+            "Steit.Builtins".to_string(),
+            "Steit.Codec".to_string(),
+            "Steit.Collections".to_string(),
+            "Steit.State".to_string(),
+            "Steit.State.Event".to_string(),
+        ];
+
+        used_namespaces.extend_from_slice(&setting.used_namespaces);
+
+        // Types may refer to those of other namespaces, unless they all share one
+        if !setting.module_namespaces.is_empty() {
+            let namespaces = setting
+                .module_namespaces
+                .iter()
+                .map(|(_, namespace)| namespace)
+                .chain(Some(&setting.namespace));
+
+            for namespace in namespaces {
+                if !msgs
+                    .iter()
+                    .all(|msg| setting.namespace_of(msg.module()) == namespace)
+                {
+                    used_namespaces.push(namespace.clone());
+                }
+            }
+        }
+
+        used_namespaces.sort();
+        used_namespaces.dedup();
+
+        for used_namespace in used_namespaces {
+            writer.writeln(format!("using {};", used_namespace));
+        }
+
+        writer.newline();
+    }
+
+    fn gen_struct(&self, r#struct: &StructMeta, setting: &Self::Setting, writer: &mut Writer) {
+        self.gen_class(r#struct, None, setting, writer);
+    }
+
     fn gen_enum(&self, r#enum: &EnumMeta, setting: &Self::Setting, writer: &mut Writer) {
+        if r#enum.repr == EnumRepr::Varint {
+            return self.gen_varint_enum(r#enum, setting, writer);
+        }
+
         let name = r#enum.name.csharp(String::from);
         let var_name = str_util::uncap_first_char(&name);
         let type_name = format!("{}{}", &name, type_params(r#enum.type_params));
@@ -387,7 +662,13 @@ impl Generator for CSharpGenerator {
             .find(|variant| variant.meta.default())
             .unwrap_or_else(|| panic!("expected a default variant for enum {}", name));
 
-        self.gen_file_opening(setting, writer);
+        let nullable = setting.nullable_suffix();
+        let tag_target = target_name("Tag", setting);
+        let variant_target = target_name("Variant", setting);
+
+        self.gen_namespace_opening(r#enum.module, setting, writer);
+        self.gen_doc(r#enum.name.doc, writer);
+        self.gen_class_attributes(setting, writer);
 
         writer
             .writeln(format!(
@@ -407,28 +688,34 @@ impl Generator for CSharpGenerator {
         writer
             .newline()
             .writeln("public Path Path { get; }")
-            .newline()
-            .writeln("public UInt32 Tag { get; private set; }")
-            .writeln("public IState Variant { get; private set; }")
             .newline();
 
+        self.gen_property("UInt32", "Tag", false, None, setting, writer);
+        self.gen_property("IState", "Variant", false, None, setting, writer);
+        writer.newline();
+
         // Return variant values
         for variant in r#enum.variants {
+            self.gen_doc(variant.doc(), writer);
             writer.writeln(format!(
-                // "public {0}? {0}Variant {{ get {{ return this.Variant as {0}; }} }}",
-                "public {0} {0}Variant {{ get {{ return this.Variant as {0}; }} }}",
+                "{0}public {1}{2} {1}Variant {{ get {{ return this.Variant as {1}; }} }}",
+                obsolete(variant.deprecated),
                 variant.ty.name.csharp(String::from),
+                nullable,
             ));
         }
 
         writer
             .newline()
             .writeln(format!(
-                "public {}(Path path = null) : this(path, {}) {{ }}",
-                name, default_variant.meta.tag,
+                "public {}(Path{} path = null) : this(path, {}) {{ }}",
+                name, nullable, default_variant.meta.tag,
             ))
             .newline()
-            .writeln(format!("public {}(Path path, UInt32 tag) {{", name))
+            .writeln(format!(
+                "public {}(Path{} path, UInt32 tag) {{",
+                name, nullable
+            ))
             .indent();
 
         for type_param in r#enum.type_params {
@@ -449,22 +736,24 @@ impl Generator for CSharpGenerator {
         }
 
         writer
-            .writeln("this.Tag = tag;")
+            .writeln(format!("this.{} = tag;", tag_target))
             .newline()
             .writeln("switch (tag) {")
             .indent();
 
         for variant in &variants {
             writer.writeln(format!(
-                "case {0}: this.Variant = new {1}(this.Path.GetNested({0})); break;",
+                "case {0}: this.{2} = new {1}(this, this.Path.GetNested({0})); break;",
                 variant.meta.tag,
                 variant.meta.ty.name.csharp(String::from),
+                variant_target,
             ));
         }
 
         writer
             .writeln(format!(
-                "default: this.Variant = new {}(this.Path.GetNested({})); break;",
+                "default: this.{} = new {}(this, this.Path.GetNested({})); break;",
+                variant_target,
                 default_variant.meta.ty.name.csharp(String::from),
                 default_variant.meta.tag,
             ))
@@ -474,27 +763,34 @@ impl Generator for CSharpGenerator {
 
         for variant in &variants {
             writer.writeln(format!(
-                "public static {0} New{1}(Path path = null) {{ return new {0}(path, {2}); }}",
-                name, variant.upper_camel_case_name, variant.meta.tag,
+                "{4}public static {0} New{1}(Path{3} path = null) {{ return new {0}(path, {2}); }}",
+                name,
+                variant.upper_camel_case_name,
+                variant.meta.tag,
+                nullable,
+                obsolete(variant.meta.deprecated),
             ));
         }
 
         writer
             .newline()
             .writeln(format!(
-                // "public static event EventHandler<VariantUpdateEventArgs<{}>>? OnUpdate;",
-                "public static event EventHandler<VariantUpdateEventArgs<{}>> OnUpdate;",
-                type_name,
+                "public static event EventHandler<VariantUpdateEventArgs<{}>>{} OnUpdate;",
+                type_name, nullable,
+            ))
+            .writeln(format!(
+                "public static event EventHandler<VariantFieldUpdateEventArgs<{}>>{} OnFieldUpdate;",
+                type_name, nullable,
             ))
             .newline()
             .writeln("public static void ClearUpdateHandlers() {")
             .indent_writeln("OnUpdate = null;")
+            .writeln("OnFieldUpdate = null;")
             .outdent_writeln("}")
             .newline()
             .writeln(format!(
-                // "public static {} Deserialize(IReader reader, Path? path = null) {{",
-                "public static {} Deserialize(IReader reader, Path path = null) {{",
-                type_name,
+                "public static {} Deserialize(IReader reader, Path{} path = null) {{",
+                type_name, nullable,
             ))
             .indent_writeln(format!("var {} = new {}(path);", var_name, type_name))
             .writeln(format!(
@@ -518,8 +814,7 @@ impl Generator for CSharpGenerator {
             .outdent_writeln("}")
             .outdent_writeln("}")
             .newline()
-            // .writeln("public IState? GetNested(UInt32 tag) {")
-            .writeln("public IState GetNested(UInt32 tag) {")
+            .writeln(format!("public IState{} GetNested(UInt32 tag) {{", nullable))
             .indent_writeln("return tag == this.Tag ? this.Variant : null;")
             .outdent_writeln("}")
             .newline()
@@ -530,7 +825,7 @@ impl Generator for CSharpGenerator {
         // Replace fields and notify event handlers
         for variant in r#enum.variants {
             writer.writeln(format!(
-                "case {0}: this.UpdateAndNotify({0}, {1}.Deserialize(reader, this.Path.GetNested({0})), shouldNotify); break;",
+                "case {0}: this.UpdateAndNotify({0}, {1}.Deserialize(reader, this, this.Path.GetNested({0})), shouldNotify); break;",
                 variant.tag, variant.ty.name.csharp(String::from),
             ));
         }
@@ -553,8 +848,19 @@ impl Generator for CSharpGenerator {
             .writeln(format!("{}.OnUpdate?.Invoke(this, args);", type_name))
             .outdent_writeln("}")
             .newline()
-            .writeln("this.Tag = newTag;")
-            .writeln("this.Variant = newVariant;")
+            .writeln(format!("this.{} = newTag;", tag_target))
+            .writeln(format!("this.{} = newVariant;", variant_target))
+            .outdent_writeln("}")
+            .newline()
+            .writeln(format!(
+                "internal void NotifyFieldUpdate(UInt32 fieldTag, Object{0} newValue, Object{0} oldValue, IState variant) {{",
+                nullable,
+            ))
+            .indent_writeln(format!(
+                "var args = new VariantFieldUpdateEventArgs<{}>(this.Tag, fieldTag, newValue, oldValue, variant, this);",
+                type_name,
+            ))
+            .writeln(format!("{}.OnFieldUpdate?.Invoke(this, args);", type_name))
             .outdent_writeln("}");
 
         for variant in r#enum.variants {
@@ -567,12 +873,12 @@ impl Generator for CSharpGenerator {
                 ))
                 .newline();
 
-            self.gen_struct(&variant.ty, true, setting, writer);
+            self.gen_class(&variant.ty, Some(&type_name), setting, writer);
         }
 
         writer.outdent_writeln("}");
 
-        self.gen_file_closing(writer);
+        self.gen_namespace_closing(writer);
     }
 }
 
@@ -580,16 +886,21 @@ struct CSharpField {
     meta: &'static FieldMeta,
     // UpperCamelCase
     upper_camel_case_name: String,
+    // What to assign to set the property
+    target_name: String,
     type_name: String,
 }
 
 impl CSharpField {
-    pub fn from_meta(field: &'static FieldMeta) -> Self {
+    pub fn from_meta(field: &'static FieldMeta, setting: &CSharpSetting) -> Self {
+        let upper_camel_case_name = field
+            .name
+            .csharp(|name| str_util::to_camel_case(name, true));
+
         Self {
             meta: field,
-            upper_camel_case_name: field
-                .name
-                .csharp(|name| str_util::to_camel_case(name, true)),
+            target_name: target_name(&upper_camel_case_name, setting),
+            upper_camel_case_name,
             type_name: field_type(field.ty),
         }
     }
@@ -613,6 +924,41 @@ impl CSharpVariant {
     }
 }
 
+fn obsolete(deprecated: bool) -> &'static str {
+    if deprecated {
+        "[Obsolete] "
+    } else {
+        ""
+    }
+}
+
+fn has_deprecated(msg: &MessageMeta) -> bool {
+    let has_deprecated_fields =
+        |r#struct: &StructMeta| r#struct.fields.iter().any(|field| field.deprecated);
+
+    match msg {
+        MessageMeta::Struct(r#struct) => has_deprecated_fields(r#struct),
+        MessageMeta::Enum(r#enum) => r#enum
+            .variants
+            .iter()
+            .any(|variant| variant.deprecated || has_deprecated_fields(&variant.ty)),
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn target_name(property_name: &str, setting: &CSharpSetting) -> String {
+    if setting.readonly_properties {
+        format!("_{}", str_util::uncap_first_char(property_name))
+    } else {
+        property_name.to_string()
+    }
+}
+
 fn type_params(type_params: &'static [&'static str]) -> String {
     if type_params.is_empty() {
         return "".to_string();
@@ -655,3 +1001,172 @@ fn field_type(ty: &'static FieldTypeMeta) -> String {
         FieldTypeMeta::TypeParam(type_param) => type_param.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        gen::{Generator, Setting},
+        log::LogEntry,
+        meta::{HasMeta, MessageMeta},
+        steit_derive,
+        test_util::Point,
+    };
+
+    use super::{CSharpGenerator, CSharpSetting};
+
+    /// A character of the player.
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned, reserved_tags(2))]
+    struct Hero {
+        /// Health points, dead when <= 0.
+        #[steit(tag = 0)]
+        hp: i32,
+        #[steit(tag = 1, deprecated)]
+        mana: i32,
+    }
+
+    #[steit_derive(Clone, Copy, Debug, State)]
+    #[steit(steit_owned, repr = "varint")]
+    enum Direction {
+        #[steit(tag = 0)]
+        North,
+        #[steit(tag = 1)]
+        East,
+        #[steit(tag = 2, deprecated)]
+        Up,
+    }
+
+    #[steit_derive(Debug, State)]
+    #[steit(steit_owned)]
+    enum Action {
+        #[steit(tag = 0)]
+        Idle,
+        /// Replaced by emotes.
+        #[steit(tag = 1, deprecated)]
+        Dance,
+    }
+
+    fn msg<T: HasMeta>() -> &'static MessageMeta {
+        T::LINK.msg.as_ref().unwrap()
+    }
+
+    fn render(msgs: &[&'static MessageMeta], setting: CSharpSetting) -> String {
+        CSharpGenerator.render(msgs, &Setting::new(&"", setting))
+    }
+
+    #[test]
+    fn default() {
+        let source = render(&[msg::<Point>()], CSharpSetting::new("Steit.Test"));
+        assert!(source.starts_with("using System;\n\nusing Steit.Builtins;\n"));
+        assert!(source.contains("\nnamespace Steit.Test {\n"));
+        assert!(source.contains("public Int32 X { get; private set; }"));
+        assert!(source.contains("public Point(Path path = null) {"));
+        assert!(!source.contains("Path?"));
+        assert!(!source.contains("[Serializable]"));
+        assert!(!source.contains("#pragma"));
+    }
+
+    #[test]
+    fn varint_enum() {
+        let source = render(&[msg::<Direction>()], CSharpSetting::new("Steit.Test"));
+        assert!(source.contains("    public enum Direction : UInt32 {\n        North = 0,\n        East = 1,\n        [Obsolete] Up = 2,\n    }\n"));
+        assert!(source.contains("public static Direction ReadDirection(this IReader reader) { return (Direction) reader.ReadUInt32(); }"));
+        assert!(!source.contains("IEnumState"));
+    }
+
+    #[test]
+    fn obsolete() {
+        let setting = CSharpSetting::new("Steit.Test");
+        let source = render(&[msg::<Hero>(), msg::<Action>()], setting);
+        assert!(source.starts_with("#pragma warning disable CS0618\n\nusing System;\n"));
+        assert!(source.contains("\n        public Int32 Hp { get; private set; }"));
+        assert!(source.contains("[Obsolete] public Int32 Mana { get; private set; }"));
+        assert!(source.contains("\n        public Idle IdleVariant {"));
+        assert!(source.contains("[Obsolete] public Dance DanceVariant {"));
+        assert!(source.contains("[Obsolete] public static Action NewDance(Path path = null) {"));
+    }
+
+    #[test]
+    fn doc_comments() {
+        let setting = CSharpSetting::new("Steit.Test");
+        let source = render(&[msg::<Hero>(), msg::<Action>()], setting);
+        assert!(source.contains("    /// <summary>\n    /// A character of the player.\n    /// </summary>\n    public sealed partial class Hero : IState {"));
+        assert!(source.contains("        /// Health points, dead when &lt;= 0.\n        /// </summary>\n        public Int32 Hp {"));
+        assert!(source.contains("        /// Replaced by emotes.\n        /// </summary>\n        [Obsolete] public Dance DanceVariant {"));
+        assert!(source.contains("        /// Replaced by emotes.\n        /// </summary>\n        public sealed partial class Dance : IState {"));
+        assert!(!source.contains("/// <summary>\n        public Int32 Mana"));
+    }
+
+    #[test]
+    fn variant_field_updates() {
+        let source = render(&[msg::<LogEntry>()], CSharpSetting::new("Steit.Test"));
+        assert!(
+            source.contains("EventHandler<VariantFieldUpdateEventArgs<LogEntry>> OnFieldUpdate;")
+        );
+        assert!(source.contains("internal Update(LogEntry container, Path path = null) {"));
+        assert!(source.contains("this.Container = container;"));
+        assert!(source.contains("this.Container.NotifyFieldUpdate(tag, newValue, oldValue, this);"));
+        assert!(source.contains("new Update(this, this.Path.GetNested(0))"));
+        assert!(source.contains("Update.Deserialize(reader, this, this.Path.GetNested(0))"));
+    }
+
+    #[test]
+    fn nullable() {
+        let setting = CSharpSetting::new("Steit.Test").nullable(true);
+        let source = render(&[msg::<LogEntry>()], setting);
+        assert!(source.starts_with("#nullable enable\n\nusing System;\n"));
+        assert!(source.contains("public LogEntry(Path? path = null) : this(path, 0) { }"));
+        assert!(source.contains("public IState? GetNested(UInt32 tag) {"));
+        assert!(source.contains("public Update? UpdateVariant {"));
+        assert!(source.contains("EventHandler<VariantUpdateEventArgs<LogEntry>>? OnUpdate;"));
+    }
+
+    #[test]
+    fn namespace_for() {
+        let setting = CSharpSetting::new("Steit.Test")
+            .namespace_for("steit", "Steit.Rust")
+            .namespace_for("steit::test_util", "Steit.Test.Util")
+            .namespace_for("steit::test", "Steit.Test.Other");
+
+        let source = render(&[msg::<Point>()], setting);
+        assert!(source.contains("\nnamespace Steit.Test.Util {\n"));
+        assert!(source.contains("using Steit.Rust;"));
+        assert!(source.contains("using Steit.Test;"));
+        assert!(source.contains("using Steit.Test.Other;"));
+        assert!(!source.contains("using Steit.Test.Util;"));
+    }
+
+    #[test]
+    fn unity_readonly_properties() {
+        let setting = CSharpSetting::new("Steit.Test")
+            .unity(true)
+            .readonly_properties(true);
+
+        let source = render(&[msg::<Point>()], setting);
+        assert!(source.contains("[Serializable]\n    public sealed partial class Point : IState {"));
+        assert!(source.contains("[UnityEngine.SerializeField] private Int32 _x;"));
+        assert!(source.contains("public Int32 X { get { return this._x; } }"));
+        assert!(
+            source.contains("case 0: this._x = this.MaybeNotify(0, reader.ReadInt32(), this.X,")
+        );
+    }
+
+    #[test]
+    fn unity_auto_properties() {
+        let setting = CSharpSetting::new("Steit.Test").unity(true);
+        let source = render(&[msg::<Point>()], setting);
+        assert!(source
+            .contains("[field: UnityEngine.SerializeField] public Int32 X { get; private set; }"));
+    }
+
+    #[test]
+    fn multiple_messages() {
+        let setting = CSharpSetting::new("Steit.Test").namespace_for("steit::log", "Steit.Log");
+        let source = render(&[msg::<LogEntry>(), msg::<Point>()], setting);
+        assert_eq!(source.matches("using System;").count(), 1);
+        assert!(source.contains("using Steit.Log;"));
+        assert!(source.contains("using Steit.Test;"));
+        assert!(source.contains("\nnamespace Steit.Log {\n"));
+        assert!(source.contains("}\n\nnamespace Steit.Test {\n"));
+    }
+}
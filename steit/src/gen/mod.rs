@@ -1,11 +1,13 @@
 pub mod generators;
 pub mod str_util;
 
+mod builder;
 mod gen_util;
 mod generator;
 mod setting;
 mod writer;
 
+pub use builder::*;
 pub use generator::*;
 pub use setting::*;
 pub use writer::*;
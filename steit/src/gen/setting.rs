@@ -7,6 +7,7 @@ pub struct Setting<T> {
     pub(in crate::gen) get_name: fn(&'static MessageMeta) -> String,
     pub(in crate::gen) skip_builtins: bool,
     pub(in crate::gen) skip_names: HashSet<String>,
+    pub(in crate::gen) single_file: Option<String>,
     inner: T,
 }
 
@@ -28,6 +29,7 @@ impl<T> Setting<T> {
             get_name: GET_NAME_CSHARP,
             skip_builtins: true,
             skip_names: HashSet::new(),
+            single_file: None,
             inner,
         }
     }
@@ -55,4 +57,14 @@ impl<T> Setting<T> {
 
         self
     }
+
+    /// Generates all messages into one file named `name`, without an extension.
+    pub fn single_file(mut self, name: impl Into<String>) -> Self {
+        self.single_file = Some(name.into());
+        self
+    }
+
+    pub(in crate::gen) fn skips(&self, name: &str, msg: &MessageMeta) -> bool {
+        (msg.is_builtin() && self.skip_builtins) || self.skip_names.contains(name)
+    }
 }
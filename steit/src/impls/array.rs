@@ -4,7 +4,7 @@ use crate::{
     de::{Deserialize, Reader},
     meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
     rt::SizeCache,
-    ser::Serialize,
+    ser::{Serialize, Writer},
     wire_fmt::{HasWireType, WireType},
 };
 
@@ -25,7 +25,7 @@ macro_rules! impl_array {
                 size
             }
 
-            fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+            fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
                 for item in self {
                     item.serialize_nested(None, false, writer)?;
                 }
@@ -59,6 +59,8 @@ macro_rules! impl_array {
             const NAME: &'static NameMeta = &NameMeta {
                 rust: "Vec",
                 csharp: Some("Vector"),
+                doc: None,
+                annotations: &[],
             };
 
             const TYPE: &'static TypeMeta =
@@ -1,9 +1,13 @@
-use std::{collections::HashMap, hash::Hash, io};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    io::{self, Write},
+};
 
 use crate::{
     de::{Deserialize, Reader},
     rt::SizeCache,
-    ser::Serialize,
+    ser::{Serialize, Writer},
     wire_fmt::{HasWireType, WireType},
 };
 
@@ -23,10 +27,27 @@ impl<K: Serialize, V: Serialize> Serialize for HashMap<K, V> {
         size
     }
 
-    fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
-        for (key, value) in self {
-            key.serialize_nested(None, false, writer)?;
-            value.serialize_nested(None, false, writer)?;
+    fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
+        if writer.is_canonical() {
+            let mut entries = Vec::with_capacity(self.len());
+
+            for (key, value) in self {
+                let mut key_bytes = writer.nested(Vec::new());
+                key.serialize_nested(None, false, &mut key_bytes)?;
+                entries.push((key_bytes.into_inner(), value));
+            }
+
+            entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (key_bytes, value) in entries {
+                writer.write_all(&key_bytes)?;
+                value.serialize_nested(None, false, writer)?;
+            }
+        } else {
+            for (key, value) in self {
+                key.serialize_nested(None, false, writer)?;
+                value.serialize_nested(None, false, writer)?;
+            }
         }
 
         Ok(())
@@ -54,6 +75,8 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
+        de::Deserialize,
+        ser::Serialize,
         test_case,
         test_util::{
             assert_merge, assert_ser_de, assert_serialize, assert_serialize_nested, assert_size,
@@ -89,4 +112,13 @@ mod tests {
     test_case!(back_and_forth_02: assert_ser_de; hash_map!(0 => -1, -1 => 2, 2 => -3));
     test_case!(back_and_forth_03: assert_ser_de; hash_map!(-1337 => 1337, -1_000_000 => 1_000_000));
     test_case!(back_and_forth_04: assert_ser_de; hash_map!(42 => 42));
+
+    #[test]
+    fn serialize_canonical() {
+        let map = hash_map!(1337u16 => 1i8, 1 => 2, 300 => 3);
+        let bytes = map.to_canonical_bytes();
+        assert_eq!(bytes, &[1, 4, 172, 2, 6, 185, 10, 2]);
+        assert_eq!(HashMap::from_canonical_bytes(&bytes).unwrap(), map);
+        assert!(HashMap::<u16, i8>::from_canonical_bytes(&[185, 10, 2, 1, 4]).is_err());
+    }
 }
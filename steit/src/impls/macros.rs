@@ -8,9 +8,9 @@ macro_rules! impl_serialize_primitive {
 
             fn serialize_cached(
                 &self,
-                writer: &mut impl ::std::io::Write,
+                writer: &mut $crate::ser::Writer<impl ::std::io::Write>,
             ) -> ::std::io::Result<()> {
-                self.serialize(writer)
+                $serialize(self, writer)
             }
 
             fn size_cache(&self) -> Option<&$crate::rt::SizeCache> {
@@ -70,6 +70,7 @@ macro_rules! impl_state_primitive {
                 if path.is_empty() {
                     match kind {
                         $crate::log::LogEntryKind::Update => self.handle_update(reader),
+                        $crate::log::LogEntryKind::Checksum => self.handle_checksum(reader),
 
                         _ => Err(::std::io::Error::new(
                             ::std::io::ErrorKind::InvalidData,
@@ -98,6 +99,8 @@ macro_rules! impl_meta_primitive {
             const NAME: &'static $crate::meta::NameMeta = &$crate::meta::NameMeta {
                 rust: stringify!($type),
                 csharp: Some($csharp_name),
+                doc: None,
+                annotations: &[],
             };
 
             const TYPE: &'static $crate::meta::TypeMeta =
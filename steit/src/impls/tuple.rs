@@ -5,7 +5,7 @@ use std::io::{self, Read};
 use crate::{
     de::{Deserialize, Reader},
     rt::SizeCache,
-    ser::Serialize,
+    ser::{Serialize, Writer},
     wire_fmt::{HasWireType, WireType},
 };
 
@@ -23,7 +23,7 @@ macro_rules! impl_tuple {
                 size
             }
 
-            fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+            fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
                 let ($($name),+) = self;
                 $($name.serialize_nested(None, false, writer)?;)+
                 Ok(())
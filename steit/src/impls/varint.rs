@@ -1,14 +1,14 @@
 macro_rules! impl_unsigned_varint {
-    (u64, $dummy:ident, "UInt64") => {
-        impl_unsigned_varint!(@impl u64, $dummy, size_64, i64, "UInt64");
+    (u64, "UInt64") => {
+        impl_unsigned_varint!(@impl u64, size_64, i64, "UInt64");
     };
 
-    ($type:ty, $dummy:ident, $csharp_name:literal) => {
-        impl_unsigned_varint!(@impl $type, $dummy, size_32, i32, $csharp_name);
+    ($type:ty, $csharp_name:literal) => {
+        impl_unsigned_varint!(@impl $type, size_32, i32, $csharp_name);
     };
 
-    (@impl $type:ty, $dummy:ident, $size_fn:ident, $size_type:ty, $csharp_name:literal) => {
-        const $dummy: () = {
+    (@impl $type:ty, $size_fn:ident, $size_type:ty, $csharp_name:literal) => {
+        const _: () = {
             impl $crate::wire_fmt::HasWireType for $type {
                 const WIRE_TYPE: $crate::wire_fmt::WireType =
                     $crate::wire_fmt::WireType::Varint;
@@ -68,14 +68,14 @@ macro_rules! impl_unsigned_varint {
     };
 }
 
-impl_unsigned_varint!(u8, _U8_IMPLS, "Byte");
-impl_unsigned_varint!(u16, _U16_IMPLS, "UInt16");
-impl_unsigned_varint!(u32, _U32_IMPLS, "UInt32");
-impl_unsigned_varint!(u64, _U64_IMPLS, "UInt64");
+impl_unsigned_varint!(u8, "Byte");
+impl_unsigned_varint!(u16, "UInt16");
+impl_unsigned_varint!(u32, "UInt32");
+impl_unsigned_varint!(u64, "UInt64");
 
 macro_rules! impl_signed_varint {
-    ($type:ty, $dummy:ident, $unsigned_type:ty, $csharp_name:literal) => {
-        const $dummy: () = {
+    ($type:ty, $unsigned_type:ty, $csharp_name:literal) => {
+        const _: () = {
             impl $crate::wire_fmt::HasWireType for $type {
                 const WIRE_TYPE: $crate::wire_fmt::WireType = $crate::wire_fmt::WireType::Varint;
             }
@@ -123,10 +123,10 @@ macro_rules! impl_signed_varint {
     };
 }
 
-impl_signed_varint!(i8, _I8_IMPLS, u8, "SByte");
-impl_signed_varint!(i16, _I16_IMPLS, u16, "Int16");
-impl_signed_varint!(i32, _I32_IMPLS, u32, "Int32");
-impl_signed_varint!(i64, _I64_IMPLS, u64, "Int64");
+impl_signed_varint!(i8, u8, "SByte");
+impl_signed_varint!(i16, u16, "Int16");
+impl_signed_varint!(i32, u32, "Int32");
+impl_signed_varint!(i64, u64, "Int64");
 
 /// Gets varint size in bytes of a 32-bit integer.
 ///
@@ -4,7 +4,7 @@ use crate::{
     de::{Deserialize, Reader},
     meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
     rt::SizeCache,
-    ser::Serialize,
+    ser::{Serialize, Writer},
     wire_fmt::{HasWireType, WireType},
 };
 
@@ -23,7 +23,7 @@ impl<T: Serialize> Serialize for Vec<T> {
         size
     }
 
-    fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+    fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
         for item in self {
             item.serialize_nested(None, false, writer)?;
         }
@@ -51,6 +51,8 @@ impl<T: HasMeta> HasMeta for Vec<T> {
     const NAME: &'static NameMeta = &NameMeta {
         rust: "Vec",
         csharp: Some("Vector"),
+        doc: None,
+        annotations: &[],
     };
 
     const TYPE: &'static TypeMeta = &TypeMeta::Ref(Self::NAME, &[FieldTypeMeta::Type(T::TYPE)]);
@@ -1,9 +1,14 @@
+#[cfg(feature = "codec")]
+pub mod codec;
 pub mod de;
+pub mod dynamic;
 pub mod gen;
 pub mod log;
 pub mod meta;
 pub mod rt;
 pub mod ser;
+#[cfg(feature = "serde")]
+pub mod serde_bridge;
 pub mod state;
 pub mod types;
 pub mod wire_fmt;
@@ -1,9 +1,4 @@
-use crate::{
-    rt::{Node, SizeCache},
-    ser::Serialize,
-    steit_derive,
-    types::Bytes,
-};
+use crate::{rt::SizeCache, ser::Serialize, steit_derive, types::Bytes};
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum LogEntryKind {
@@ -11,6 +6,7 @@ pub enum LogEntryKind {
     ListPush = 8,
     ListPop = 9,
     MapRemove = 12,
+    Checksum = 16,
 }
 
 // `LogEntry` is flattened by putting `path` in each variant to save some serialization size.
@@ -43,46 +39,67 @@ pub enum LogEntry {
         #[steit(tag = 1)]
         key: u32,
     },
+    /// Carries the hash of the state at `path` when logged, for replicas to check theirs against.
+    #[steit(tag = 16)]
+    Checksum {
+        #[steit(tag = 0, csharp_name = "flatten_path")]
+        path: Vec<u32>,
+        #[steit(tag = 1)]
+        hash: u64,
+    },
 }
 
+/// Entries take their path as anything turning into a `Vec<u32>`, such as the path resolved by
+/// [`Runtime::path`] or a `&Node<u32>` as they used to.
+///
+/// [`Runtime::path`]: ../rt/struct.Runtime.html#method.path
 impl LogEntry {
-    pub fn new_update(path: &Node<u32>, value: &impl Serialize) -> Self {
+    pub fn new_update(path: impl Into<Vec<u32>>, value: &impl Serialize) -> Self {
         LogEntry::Update {
-            path: path.collect_values(),
+            path: path.into(),
             value: Bytes::from_value(value),
             size_cache: SizeCache::new(),
         }
     }
 
-    pub fn new_list_push(path: &Node<u32>, item: &impl Serialize) -> Self {
+    pub fn new_list_push(path: impl Into<Vec<u32>>, item: &impl Serialize) -> Self {
         LogEntry::ListPush {
-            path: path.collect_values(),
+            path: path.into(),
             item: Bytes::from_value(item),
             size_cache: SizeCache::new(),
         }
     }
 
-    pub fn new_list_pop(path: &Node<u32>) -> Self {
+    pub fn new_list_pop(path: impl Into<Vec<u32>>) -> Self {
         LogEntry::ListPop {
-            path: path.collect_values(),
+            path: path.into(),
             size_cache: SizeCache::new(),
         }
     }
 
-    pub fn new_map_remove(path: &Node<u32>, key: u32) -> Self {
+    pub fn new_map_remove(path: impl Into<Vec<u32>>, key: u32) -> Self {
         LogEntry::MapRemove {
-            path: path.collect_values(),
+            path: path.into(),
             key,
             size_cache: SizeCache::new(),
         }
     }
 
+    pub fn new_checksum(path: impl Into<Vec<u32>>, hash: u64) -> Self {
+        LogEntry::Checksum {
+            path: path.into(),
+            hash,
+            size_cache: SizeCache::new(),
+        }
+    }
+
     pub fn kind(&self) -> LogEntryKind {
         match self {
             LogEntry::Update { .. } => LogEntryKind::Update,
             LogEntry::ListPush { .. } => LogEntryKind::ListPush,
             LogEntry::ListPop { .. } => LogEntryKind::ListPop,
             LogEntry::MapRemove { .. } => LogEntryKind::MapRemove,
+            LogEntry::Checksum { .. } => LogEntryKind::Checksum,
         }
     }
 }
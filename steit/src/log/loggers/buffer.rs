@@ -15,23 +15,25 @@ impl BufferLogger {
         Self::default()
     }
 
-    pub fn bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-
-        for entry in &self.entries {
-            entry.cache_size();
-            entry.serialize_nested(None, false, &mut bytes).unwrap();
-        }
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
 
-        bytes
+    pub fn bytes(&self) -> Vec<u8> {
+        entries_to_bytes(&self.entries)
     }
 
     pub fn clear(&mut self) {
         self.entries.clear();
     }
 
+    /// Drops the `count` oldest entries, e.g. those a snapshot has made redundant.
+    pub fn discard(&mut self, count: usize) {
+        self.entries.drain(..count.min(self.entries.len()));
+    }
+
     pub fn pluck(&mut self) -> Vec<LogEntry> {
-        std::mem::replace(&mut self.entries, Vec::new())
+        std::mem::take(&mut self.entries)
     }
 
     pub fn pluck_bytes(&mut self) -> Vec<u8> {
@@ -47,3 +49,15 @@ impl Logger for BufferLogger {
         Ok(())
     }
 }
+
+pub(super) fn entries_to_bytes(entries: &[LogEntry]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    for entry in entries {
+        entry
+            .serialize_nested_streaming(None, false, &mut bytes)
+            .unwrap();
+    }
+
+    bytes
+}
@@ -0,0 +1,378 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    de::Reader,
+    log::{LogEntry, Logger},
+    state::State,
+};
+
+use super::buffer::{entries_to_bytes, BufferLogger};
+
+/// A serialized root state, taken once the entries before `position` had been logged.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Snapshot {
+    pub position: u64,
+    pub bytes: Vec<u8>,
+}
+
+/// What a client needs to reach the current log position, see [`CheckpointLogger::catch_up`].
+///
+/// [`CheckpointLogger::catch_up`]: struct.CheckpointLogger.html#method.catch_up
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CatchUp {
+    /// Replaces the whole state first, when the client is too far behind to be sent entries only.
+    pub snapshot: Option<Vec<u8>>,
+    /// Serialized entries, to be replayed after the snapshot if any.
+    pub entries: Vec<u8>,
+    /// The log position the client is at afterwards, which it should ask from next time.
+    pub position: u64,
+}
+
+impl CatchUp {
+    /// Total number of bytes to be sent.
+    pub fn size(&self) -> usize {
+        self.snapshot.as_ref().map_or(0, Vec::len) + self.entries.len()
+    }
+
+    /// Brings `state`, which must be a root, to the position of this catch-up.
+    pub fn apply<T: State>(&self, state: &mut T) -> io::Result<()> {
+        if let Some(snapshot) = &self.snapshot {
+            *state = T::with_runtime(state.runtime().clone());
+            state.merge(&mut Reader::new(&**snapshot))?;
+        }
+
+        state.replay(&mut Reader::new(&*self.entries))
+    }
+}
+
+/// Buffers the log of a root state along with snapshots of it, so that clients which join late
+/// or reconnect can be brought up to date with a small payload.
+///
+/// Since a logger can't reach the state it logs for, the owner of the state is expected
+/// to call [`checkpoint_if_due`] regularly, e.g. once per tick, which takes a snapshot
+/// every so many entries or so much time as configured. Entries are kept back to
+/// the checkpoint before the latest, so that clients slightly behind the latest one
+/// can still be sent entries only.
+///
+/// [`checkpoint_if_due`]: #method.checkpoint_if_due
+pub struct CheckpointLogger {
+    buffer: BufferLogger,
+    buffer_position: u64,
+    snapshot: Option<Snapshot>,
+    every_entries: Option<u64>,
+    every: Option<Duration>,
+    checkpointed_at: Instant,
+}
+
+impl CheckpointLogger {
+    /// Creates a logger which only takes snapshots when asked to.
+    pub fn new() -> Self {
+        Self {
+            buffer: BufferLogger::new(),
+            buffer_position: 0,
+            snapshot: None,
+            every_entries: None,
+            every: None,
+            checkpointed_at: Instant::now(),
+        }
+    }
+
+    /// Makes a checkpoint due once `count` entries have been logged since the last one.
+    pub fn every_entries(mut self, count: u64) -> Self {
+        self.every_entries = Some(count);
+        self
+    }
+
+    /// Makes a checkpoint due once `duration` has passed since the last one.
+    pub fn every(mut self, duration: Duration) -> Self {
+        self.every = Some(duration);
+        self
+    }
+
+    /// Number of entries logged so far.
+    pub fn position(&self) -> u64 {
+        self.buffer_position + self.buffer.entries().len() as u64
+    }
+
+    /// Position of the oldest entry still buffered.
+    pub fn oldest_position(&self) -> u64 {
+        self.buffer_position
+    }
+
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+
+    pub fn is_due(&self) -> bool {
+        let since = self.position() - self.snapshot.as_ref().map_or(0, |s| s.position);
+
+        self.snapshot.is_none()
+            || matches!(self.every_entries, Some(count) if since >= count)
+            || matches!(
+                self.every,
+                Some(duration) if since > 0 && self.checkpointed_at.elapsed() >= duration
+            )
+    }
+
+    /// Takes a snapshot of `state`, which must be the root this logger is logging for.
+    ///
+    /// Entries older than the previous checkpoint are dropped.
+    pub fn checkpoint(&mut self, state: &impl State) -> io::Result<()> {
+        if !state.is_root() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "`checkpoint` can only be called on the root `State` object",
+            ));
+        }
+
+        let position = self.position();
+
+        if let Some(previous) = &self.snapshot {
+            self.buffer
+                .discard((previous.position - self.buffer_position) as usize);
+            self.buffer_position = previous.position;
+        }
+
+        self.snapshot = Some(Snapshot {
+            position,
+            bytes: state.to_bytes(),
+        });
+        self.checkpointed_at = Instant::now();
+
+        Ok(())
+    }
+
+    /// Takes a snapshot of `state` if one is due, returning whether it did.
+    pub fn checkpoint_if_due(&mut self, state: &impl State) -> io::Result<bool> {
+        if self.is_due() {
+            self.checkpoint(state)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn entries_since(&self, position: u64) -> Vec<u8> {
+        let start = (position - self.buffer_position) as usize;
+        entries_to_bytes(&self.buffer.entries()[start..])
+    }
+
+    /// Builds the smallest catch-up for a client at `since`, or for a new client if `None`.
+    ///
+    /// It's either the entries since that position alone, or the latest snapshot
+    /// and the entries since it, the latter being the only option if the entries
+    /// the client misses aren't buffered anymore.
+    pub fn catch_up(&self, since: Option<u64>) -> io::Result<CatchUp> {
+        let position = self.position();
+
+        if let Some(since) = since {
+            if since > position {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("position {} is ahead of the log at {}", since, position),
+                ));
+            }
+        }
+
+        let entries_only = since
+            .filter(|&since| since >= self.buffer_position)
+            .map(|since| CatchUp {
+                snapshot: None,
+                entries: self.entries_since(since),
+                position,
+            });
+
+        let with_snapshot = self
+            .snapshot
+            .as_ref()
+            .filter(|snapshot| match since {
+                Some(since) => since <= snapshot.position,
+                None => true,
+            })
+            .map(|snapshot| CatchUp {
+                snapshot: Some(snapshot.bytes.clone()),
+                entries: self.entries_since(snapshot.position),
+                position,
+            });
+
+        match (entries_only, with_snapshot) {
+            (Some(entries_only), Some(with_snapshot)) => {
+                if with_snapshot.size() < entries_only.size() {
+                    Ok(with_snapshot)
+                } else {
+                    Ok(entries_only)
+                }
+            }
+
+            (Some(catch_up), None) | (None, Some(catch_up)) => Ok(catch_up),
+
+            (None, None) => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no checkpoint has been taken yet",
+            )),
+        }
+    }
+}
+
+impl Default for CheckpointLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Logger for CheckpointLogger {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        self.buffer.log(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use crate::{
+        log::loggers::BufferLogger,
+        rt::{LoggerHandle, Runtime},
+        test_util::Point,
+        types::List,
+    };
+
+    use super::CheckpointLogger;
+
+    fn points(logger: CheckpointLogger) -> (List<Point>, LoggerHandle<CheckpointLogger>) {
+        let (runtime, logger) = Runtime::with_logger_returned(logger);
+        (List::new(runtime), logger)
+    }
+
+    fn client() -> List<Point> {
+        List::new(Runtime::with_logger(BufferLogger::new()))
+    }
+
+    fn push(points: &mut List<Point>, x: i32) {
+        points.push_with(|runtime| Point::new(runtime, x, x, x));
+    }
+
+    #[test]
+    fn catch_up_new_client() {
+        let (mut points, logger) = points(CheckpointLogger::new());
+        push(&mut points, 1);
+        logger.lock().unwrap().checkpoint(&points).unwrap();
+        push(&mut points, 2);
+
+        let catch_up = logger.lock().unwrap().catch_up(None).unwrap();
+        assert!(catch_up.snapshot.is_some());
+        assert_eq!(catch_up.position, 2);
+
+        let mut client = client();
+        catch_up.apply(&mut client).unwrap();
+        assert_eq!(client, points);
+    }
+
+    #[test]
+    fn catch_up_entries_only() {
+        let (mut points, logger) = points(CheckpointLogger::new());
+        logger.lock().unwrap().checkpoint(&points).unwrap();
+        push(&mut points, 1);
+
+        let mut client = client();
+        let catch_up = logger.lock().unwrap().catch_up(None).unwrap();
+        catch_up.apply(&mut client).unwrap();
+
+        push(&mut points, 2);
+        logger.lock().unwrap().checkpoint(&points).unwrap();
+        push(&mut points, 3);
+
+        let catch_up = logger
+            .lock()
+            .unwrap()
+            .catch_up(Some(catch_up.position))
+            .unwrap();
+
+        assert_eq!(catch_up.snapshot, None);
+        assert_eq!(catch_up.position, 3);
+
+        catch_up.apply(&mut client).unwrap();
+        assert_eq!(client, points);
+    }
+
+    #[test]
+    fn catch_up_picks_smaller() {
+        let (mut points, logger) = points(CheckpointLogger::new());
+        logger.lock().unwrap().checkpoint(&points).unwrap();
+
+        for x in 0..10 {
+            push(&mut points, x);
+            points.pop();
+        }
+
+        logger.lock().unwrap().checkpoint(&points).unwrap();
+        let catch_up = logger.lock().unwrap().catch_up(Some(0)).unwrap();
+        assert_eq!(catch_up.snapshot, Some(Vec::new()));
+        assert!(catch_up.entries.is_empty());
+
+        let mut client = client();
+        catch_up.apply(&mut client).unwrap();
+        assert_eq!(client, points);
+    }
+
+    #[test]
+    fn discard_before_previous_checkpoint() {
+        let (mut points, logger) = points(CheckpointLogger::new());
+
+        for x in 0..3 {
+            push(&mut points, x);
+            logger.lock().unwrap().checkpoint(&points).unwrap();
+        }
+
+        let logger = logger.lock().unwrap();
+        assert_eq!(logger.oldest_position(), 2);
+        assert_eq!(logger.snapshot().unwrap().position, 3);
+
+        assert!(logger.catch_up(Some(1)).unwrap().snapshot.is_some());
+        assert!(logger.catch_up(Some(2)).unwrap().snapshot.is_none());
+    }
+
+    #[test]
+    fn catch_up_errors() {
+        let (mut points, logger) = points(CheckpointLogger::new());
+        assert!(logger.lock().unwrap().catch_up(None).is_err());
+
+        push(&mut points, 1);
+        assert!(logger.lock().unwrap().catch_up(Some(0)).is_ok());
+        assert!(logger.lock().unwrap().catch_up(Some(2)).is_err());
+        assert!(logger.lock().unwrap().checkpoint(&points[0]).is_err());
+    }
+
+    #[test]
+    fn due_every_entries() {
+        let (mut points, logger) = points(CheckpointLogger::new().every_entries(2));
+        assert!(logger.lock().unwrap().checkpoint_if_due(&points).unwrap());
+
+        push(&mut points, 1);
+        assert!(!logger.lock().unwrap().checkpoint_if_due(&points).unwrap());
+
+        push(&mut points, 2);
+        assert!(logger.lock().unwrap().checkpoint_if_due(&points).unwrap());
+        assert_eq!(logger.lock().unwrap().snapshot().unwrap().position, 2);
+    }
+
+    #[test]
+    fn due_every() {
+        let duration = Duration::from_millis(10);
+        let (mut points, logger) = points(CheckpointLogger::new().every(duration));
+        assert!(logger.lock().unwrap().checkpoint_if_due(&points).unwrap());
+
+        push(&mut points, 1);
+        assert!(!logger.lock().unwrap().is_due());
+
+        thread::sleep(duration);
+        assert!(logger.lock().unwrap().is_due());
+
+        logger.lock().unwrap().checkpoint(&points).unwrap();
+        thread::sleep(duration);
+        assert!(!logger.lock().unwrap().is_due());
+    }
+}
@@ -0,0 +1,41 @@
+use std::io;
+
+use crate::{
+    dynamic::Schema,
+    log::{LogEntry, Logger},
+    meta::HasMeta,
+};
+
+/// Writes each entry as a line of JSON, with paths and values resolved against a schema.
+pub struct JsonLogger {
+    schema: Schema,
+    writer: Box<dyn io::Write + Send>,
+}
+
+impl JsonLogger {
+    pub fn new(schema: Schema, writer: impl io::Write + Send + 'static) -> Self {
+        Self {
+            schema,
+            writer: Box::new(writer),
+        }
+    }
+
+    pub fn of<T: HasMeta>(writer: impl io::Write + Send + 'static) -> io::Result<Self> {
+        Ok(Self::new(Schema::of::<T>()?, writer))
+    }
+
+    pub fn stdout<T: HasMeta>() -> io::Result<Self> {
+        Self::of::<T>(io::stdout())
+    }
+
+    pub fn stderr<T: HasMeta>() -> io::Result<Self> {
+        Self::of::<T>(io::stderr())
+    }
+}
+
+impl Logger for JsonLogger {
+    fn log(&mut self, entry: LogEntry) -> io::Result<()> {
+        let json = self.schema.entry_to_json(&entry)?;
+        writeln!(self.writer, "{}", json)
+    }
+}
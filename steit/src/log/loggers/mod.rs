@@ -1,9 +1,15 @@
 mod buffer;
+mod checkpoint;
+#[cfg(feature = "json")]
+mod json;
 mod noop;
 mod panic;
 mod writer;
 
 pub use buffer::*;
+pub use checkpoint::*;
+#[cfg(feature = "json")]
+pub use json::*;
 pub use noop::*;
 pub use panic::*;
 pub use writer::*;
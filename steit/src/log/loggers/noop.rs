@@ -7,7 +7,7 @@ pub struct NoopLogger;
 
 impl NoopLogger {
     pub fn new() -> Self {
-        Self::default()
+        Self
     }
 }
 
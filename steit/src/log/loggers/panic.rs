@@ -7,7 +7,7 @@ pub struct PanicLogger;
 
 impl PanicLogger {
     pub fn new() -> Self {
-        Self::default()
+        Self
     }
 }
 
@@ -34,8 +34,7 @@ impl Default for WriterLogger {
 impl Logger for WriterLogger {
     fn log(&mut self, entry: LogEntry) -> io::Result<()> {
         let mut bytes = Vec::new();
-        entry.cache_size();
-        entry.serialize_nested(None, false, &mut bytes)?;
+        entry.serialize_nested_streaming(None, false, &mut bytes)?;
         writeln!(self.writer, "{:#?} => {:?}", entry, &bytes)
     }
 }
@@ -1,6 +1,6 @@
 use super::{name::NameMeta, r#type::FieldTypeMeta};
 
-#[derive(Debug)]
+#[derive(PartialEq, Eq, Debug)]
 pub enum MessageMeta {
     Struct(StructMeta),
     Enum(EnumMeta),
@@ -21,6 +21,21 @@ impl MessageMeta {
         }
     }
 
+    /// Path of the Rust module the message is declared in, e.g. `game::units`.
+    pub fn module(&self) -> &'static str {
+        match *self {
+            MessageMeta::Struct(StructMeta { module, .. })
+            | MessageMeta::Enum(EnumMeta { module, .. }) => module,
+        }
+    }
+
+    pub fn doc(&self) -> Option<&'static str> {
+        match *self {
+            MessageMeta::Struct(StructMeta { name, .. })
+            | MessageMeta::Enum(EnumMeta { name, .. }) => name.doc,
+        }
+    }
+
     pub fn is_builtin(&self) -> bool {
         match self {
             MessageMeta::Struct(StructMeta { builtin, .. })
@@ -29,37 +44,71 @@ impl MessageMeta {
     }
 }
 
-#[derive(Debug)]
+#[derive(PartialEq, Eq, Debug)]
 pub struct StructMeta {
     pub name: &'static NameMeta,
+    pub module: &'static str,
     pub type_params: &'static [&'static str],
     pub fields: &'static [FieldMeta],
+    // Tags of removed fields, which must not be reused
+    pub reserved_tags: &'static [u32],
     pub builtin: bool,
 }
 
-#[derive(Debug)]
+impl StructMeta {
+    pub fn doc(&self) -> Option<&'static str> {
+        self.name.doc
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
 pub struct EnumMeta {
     pub name: &'static NameMeta,
+    pub module: &'static str,
     pub type_params: &'static [&'static str],
     pub variants: &'static [VariantMeta],
+    pub reserved_tags: &'static [u32],
+    pub repr: EnumRepr,
     pub builtin: bool,
 }
 
-#[derive(Debug)]
+/// How values of an enum are put on the wire.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EnumRepr {
+    // A message with the variant nested under its tag
+    Message,
+    // Only the variant tag, for enums without fields. Their `TypeMeta`s are primitives.
+    Varint,
+}
+
+#[derive(PartialEq, Eq, Debug)]
 pub struct VariantMeta {
     pub ty: StructMeta,
     pub tag: u32,
+    pub deprecated: bool,
 }
 
 impl VariantMeta {
     pub fn default(&self) -> bool {
         self.tag == 0
     }
+
+    pub fn doc(&self) -> Option<&'static str> {
+        self.ty.name.doc
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct FieldMeta {
     pub name: &'static NameMeta,
     pub ty: &'static FieldTypeMeta,
     pub tag: u32,
+    // Still decoded and replayed, but on its way out
+    pub deprecated: bool,
+}
+
+impl FieldMeta {
+    pub fn doc(&self) -> Option<&'static str> {
+        self.name.doc
+    }
 }
@@ -2,6 +2,10 @@
 pub struct NameMeta {
     pub rust: &'static str,
     pub csharp: Option<&'static str>,
+    // Doc comments of the declaration, with the leading `///` stripped
+    pub doc: Option<&'static str>,
+    // Free-form `#[steit(meta(key = "value"))]` pairs, left to downstream tools
+    pub annotations: &'static [(&'static str, &'static str)],
 }
 
 impl NameMeta {
@@ -9,6 +13,8 @@ impl NameMeta {
         Self {
             rust: rust_name,
             csharp: None,
+            doc: None,
+            annotations: &[],
         }
     }
 
@@ -18,4 +24,28 @@ impl NameMeta {
             None => transform(self.rust),
         }
     }
+
+    /// Returns the value annotated under `key`, if any.
+    pub fn annotation(&self, key: &str) -> Option<&'static str> {
+        self.annotations
+            .iter()
+            .find(|(annotated_key, _)| *annotated_key == key)
+            .map(|(_, value)| *value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NameMeta;
+
+    #[test]
+    fn annotation() {
+        let name = NameMeta {
+            annotations: &[("unit", "hp"), ("max", "100")],
+            ..NameMeta::new("health")
+        };
+
+        assert_eq!(name.annotation("max"), Some("100"));
+        assert_eq!(name.annotation("min"), None);
+    }
 }
@@ -14,3 +14,13 @@ pub enum TypeMeta {
     // In our scope, a type argument holds the same content as a field type.
     Ref(&'static NameMeta, &'static [FieldTypeMeta]),
 }
+
+impl TypeMeta {
+    /// Wire type of values of this type, usable in constants.
+    pub const fn wire_type(&self) -> WireType {
+        match self {
+            TypeMeta::Primitive(_, wire_type) => *wire_type,
+            TypeMeta::Ref(_, _) => WireType::Sized,
+        }
+    }
+}
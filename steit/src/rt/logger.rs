@@ -1,12 +1,13 @@
 use std::{
     io,
     ops::{Deref, DerefMut},
-    sync::{Arc, Mutex},
 };
 
 use crate::log::{LogEntry, Logger};
 
-pub type LoggerHandle<T> = Arc<Mutex<RuntimeLogger<T>>>;
+use super::sync::{Lock, Shared};
+
+pub type LoggerHandle<T> = Shared<Lock<RuntimeLogger<T>>>;
 
 pub trait PausableLogger: Logger {
     fn pause(&mut self) -> u32;
@@ -2,8 +2,11 @@ mod logger;
 mod node;
 mod runtime;
 mod size_cache;
+mod stamp;
+mod sync;
 
 pub use logger::*;
 pub use node::*;
 pub use runtime::*;
 pub use size_cache::*;
+pub use sync::*;
@@ -1,26 +1,28 @@
-use std::{fmt, sync::Arc};
+use std::fmt;
+
+use super::sync::Shared;
 
 pub enum Node<T> {
     Root,
-    Child { parent: Arc<Self>, value: T },
+    Child { parent: Shared<Self>, value: T },
 }
 
 impl<T> Node<T> {
-    pub fn child(parent: &Arc<Self>, value: T) -> Self {
+    pub fn child(parent: &Shared<Self>, value: T) -> Self {
         Node::Child {
             parent: parent.clone(),
             value,
         }
     }
 
-    pub fn get_parent(&self) -> Option<Arc<Self>> {
+    pub fn get_parent(&self) -> Option<Shared<Self>> {
         match self {
             Node::Root => None,
             Node::Child { parent, .. } => Some(parent.clone()),
         }
     }
 
-    pub fn parent(&self) -> Arc<Self> {
+    pub fn parent(&self) -> Shared<Self> {
         self.get_parent()
             .expect("there is no parent node of the root")
     }
@@ -55,6 +57,12 @@ impl<T: Copy> Node<T> {
     }
 }
 
+impl<T: Copy> From<&Node<T>> for Vec<T> {
+    fn from(node: &Node<T>) -> Self {
+        node.collect_values()
+    }
+}
+
 struct DebugNode<'a, T>(&'a Node<T>);
 
 impl<'a, T: fmt::Debug> fmt::Debug for DebugNode<'a, T> {
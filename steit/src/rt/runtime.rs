@@ -2,7 +2,7 @@ use std::{
     fmt,
     hash::{Hash, Hasher},
     io,
-    sync::{Arc, Mutex},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
 };
 
 use crate::{
@@ -12,27 +12,164 @@ use crate::{
 
 use super::{
     logger::{LoggerHandle, PausableLogger, RuntimeLogger},
-    node::Node,
+    stamp::Stamp,
+    sync::{Lock, Shared},
 };
 
+/// Where a runtime sits, either at the root, which holds the logger,
+/// or under some field of another runtime.
 #[derive(Clone)]
+enum Link {
+    Root {
+        logger: Shared<Lock<dyn PausableLogger>>,
+    },
+    Child {
+        parent: Shared<Position>,
+        field_number: u32,
+    },
+}
+
+struct Position {
+    link: Lock<Link>,
+    // Renewed on each change of the state at this position or under it
+    stamp: Stamp,
+    // Bumped each time the position is moved, which outdates whatever was resolved under it
+    moves: AtomicU64,
+    // Runtimes owning the position, i.e. the one it was made for and its clones. Nested runtimes
+    // and those made by `Runtime::parent` link to it as well but don't count.
+    owners: AtomicUsize,
+    resolved: Lock<Option<Resolved>>,
+}
+
+/// Path and logger of a position, kept along with the parents they were resolved through
+/// and how many times each of them had moved, so that a move anywhere above is noticed.
+struct Resolved {
+    path: Vec<u32>,
+    logger: Shared<Lock<dyn PausableLogger>>,
+    parents: Vec<(Shared<Position>, u64)>,
+    moves: u64,
+}
+
+impl Resolved {
+    fn is_current(&self, position: &Position) -> bool {
+        self.moves == position.moves.load(Ordering::Acquire)
+            && self
+                .parents
+                .iter()
+                .all(|(parent, moves)| *moves == parent.moves.load(Ordering::Acquire))
+    }
+}
+
+impl Position {
+    fn new(link: Link, stamp: Stamp) -> Self {
+        Self {
+            link: Lock::new(link),
+            stamp,
+            moves: AtomicU64::new(0),
+            owners: AtomicUsize::new(0),
+            resolved: Lock::new(None),
+        }
+    }
+
+    /// Hands the resolved path and logger to `f`, resolving them again only if this position
+    /// or one of its parents has moved since, and renews the stamps along the path if asked to.
+    fn with_resolved<R>(&self, renew: bool, f: impl FnOnce(&Resolved) -> R) -> R {
+        let mut resolved = self.resolved.lock().unwrap();
+
+        if !matches!(&*resolved, Some(resolved) if resolved.is_current(self)) {
+            *resolved = Some(self.resolve());
+        }
+
+        let resolved = resolved.as_ref().unwrap();
+
+        if renew {
+            let stamp = self.stamp.renew();
+
+            for (parent, _) in &resolved.parents {
+                parent.stamp.set(stamp);
+            }
+        }
+
+        f(resolved)
+    }
+
+    fn resolve(&self) -> Resolved {
+        // Loaded before the link, so that a move in between leaves the result outdated
+        let moves = self.moves.load(Ordering::Acquire);
+
+        match &*self.link.lock().unwrap() {
+            Link::Root { logger } => Resolved {
+                path: Vec::new(),
+                logger: logger.clone(),
+                parents: Vec::new(),
+                moves,
+            },
+
+            Link::Child {
+                parent,
+                field_number,
+            } => parent.with_resolved(false, |resolved| {
+                let mut path = Vec::with_capacity(resolved.path.len() + 1);
+                path.extend_from_slice(&resolved.path);
+                path.push(*field_number);
+
+                let mut parents = Vec::with_capacity(resolved.parents.len() + 1);
+                parents.extend(resolved.parents.iter().cloned());
+                parents.push((parent.clone(), resolved.moves));
+
+                Resolved {
+                    path,
+                    logger: resolved.logger.clone(),
+                    parents,
+                    moves,
+                }
+            }),
+        }
+    }
+
+    fn is_under(&self, position: &Shared<Position>) -> bool {
+        match &*self.link.lock().unwrap() {
+            Link::Root { .. } => false,
+            Link::Child { parent, .. } => {
+                Shared::ptr_eq(parent, position) || parent.is_under(position)
+            }
+        }
+    }
+}
+
+/// Places a state in its tree, giving it the path and logger its changes are logged with.
+///
+/// A runtime only links to the runtime of its parent, so moving it with [`relocate`] moves
+/// every runtime nested in it in one step, however large the subtree is. Paths and loggers
+/// are resolved when first logged with and kept until the runtime or one of its parents moves.
+///
+/// Clones share their position with the original, which [`relocate`] refuses to move
+/// while any of them is alive, so that a moved state can't drag along a state it was cloned from.
+/// It likewise refuses runtimes returned by [`parent`], which only borrow the position
+/// of the parent state.
+///
+/// [`parent`]: #method.parent
+/// [`relocate`]: #method.relocate
 pub struct Runtime {
-    logger: Arc<Mutex<dyn PausableLogger>>,
-    path: Arc<Node<u32>>,
+    position: Shared<Position>,
+    // Unset for runtimes made by `parent`, whose position belongs to another runtime
+    owned: bool,
 }
 
 macro_rules! impl_entry {
     ($entry:ident, $new_entry:ident $(, $param_name:ident : $param_type:ty )* $(,)?) => {
         pub fn $entry(&self $(, $param_name: $param_type )*) -> LogEntry {
-            LogEntry::$new_entry(&self.path $(, $param_name )*)
+            LogEntry::$new_entry(self.path() $(, $param_name )*)
         }
     };
 }
 
 macro_rules! impl_log {
-    ($log:ident, $entry:ident $(, $param_name:ident : $param_type:ty )* $(,)?) => {
+    ($log:ident, $new_entry:ident $(, $param_name:ident : $param_type:ty )* $(,)?) => {
         pub fn $log(&self $(, $param_name: $param_type)*) -> io::Result<()> {
-            self.log(self.$entry($($param_name ),*))
+            self.log_at(true, |path| {
+                LogEntry::$new_entry(path.to_vec() $(, $param_name )*)
+            })
         }
     };
 }
@@ -43,51 +180,108 @@ impl Runtime {
     }
 
     pub fn with_logger_returned<T: Logger + 'static>(logger: T) -> (Self, LoggerHandle<T>) {
-        let logger = Arc::new(Mutex::new(RuntimeLogger::new(logger)));
+        let logger = Shared::new(Lock::new(RuntimeLogger::new(logger)));
 
-        (
-            Self {
-                logger: logger.clone(),
-                path: Arc::new(Node::Root),
-            },
-            logger,
-        )
+        let link = Link::Root {
+            logger: logger.clone(),
+        };
+
+        (Self::at(link, Stamp::root()), logger)
     }
 
     pub fn with_logger<T: Logger + 'static>(logger: T) -> Self {
         Self::with_logger_returned(logger).0
     }
 
-    pub fn nested(&self, field_number: u32) -> Self {
-        Self {
-            logger: self.logger.clone(),
-            path: Arc::new(Node::child(&self.path, field_number)),
+    fn at(link: Link, stamp: Stamp) -> Self {
+        Self::sharing(Shared::new(Position::new(link, stamp)), true)
+    }
+
+    fn sharing(position: Shared<Position>, owned: bool) -> Self {
+        if owned {
+            position.owners.fetch_add(1, Ordering::Relaxed);
         }
+
+        Self { position, owned }
     }
 
-    pub fn parent(&self) -> Self {
-        Self {
-            logger: self.logger.clone(),
-            path: self.path.parent(),
+    fn parent_position(&self) -> Shared<Position> {
+        match &*self.position.link.lock().unwrap() {
+            Link::Root { .. } => panic!("there is no parent node of the root"),
+            Link::Child { parent, .. } => parent.clone(),
         }
     }
 
+    pub fn nested(&self, field_number: u32) -> Self {
+        let link = Link::Child {
+            parent: self.position.clone(),
+            field_number,
+        };
+
+        Self::at(link, self.position.stamp.nested())
+    }
+
+    /// Returns a runtime at the position of the parent, which logs as the parent does
+    /// but can't be moved with [`relocate`].
+    ///
+    /// [`relocate`]: #method.relocate
+    pub fn parent(&self) -> Self {
+        Self::sharing(self.parent_position(), false)
+    }
+
     pub fn sibling(&self, field_number: u32) -> Self {
-        Self {
-            logger: self.logger.clone(),
-            path: Arc::new(Node::child(&self.path.parent(), field_number)),
+        let parent = self.parent_position();
+        let stamp = parent.stamp.nested();
+
+        Self::at(
+            Link::Child {
+                parent,
+                field_number,
+            },
+            stamp,
+        )
+    }
+
+    /// Moves this runtime to where `runtime` is, in place, so that every runtime nested in it
+    /// follows without being touched.
+    ///
+    /// Hands `runtime` back when this one is shared by clones, which would be moved as well,
+    /// when it was returned by [`parent`], or when `runtime` lies under this one. States then
+    /// have to set it on themselves and nested runtimes on their children,
+    /// as [`State::set_runtime`] falls back to.
+    ///
+    /// [`parent`]: #method.parent
+    /// [`State::set_runtime`]: ../state/trait.State.html#tymethod.set_runtime
+    pub fn relocate(&mut self, runtime: Runtime) -> Result<(), Runtime> {
+        if !self.owned || self.position.owners.load(Ordering::Relaxed) > 1 {
+            return Err(runtime);
+        }
+
+        let position = &self.position;
+
+        if Shared::ptr_eq(&runtime.position, position) || runtime.position.is_under(position) {
+            return Err(runtime);
         }
+
+        let link = runtime.position.link.lock().unwrap().clone();
+        *position.link.lock().unwrap() = link;
+        position.moves.fetch_add(1, Ordering::Release);
+        Ok(())
     }
 
-    pub fn path(&self) -> &Arc<Node<u32>> {
-        &self.path
+    /// Resolves the field numbers leading from the root to this runtime.
+    ///
+    /// Returns them as an owned `Vec` rather than a shared [`Node`], which runtimes no longer
+    /// keep, as the path changes whenever a parent is relocated.
+    ///
+    /// [`Node`]: enum.Node.html
+    pub fn path(&self) -> Vec<u32> {
+        self.position
+            .with_resolved(false, |resolved| resolved.path.clone())
     }
 
     pub fn is_root(&self) -> bool {
-        match &*self.path {
-            Node::Root { .. } => true,
-            Node::Child { .. } => false,
-        }
+        matches!(&*self.position.link.lock().unwrap(), Link::Root { .. })
     }
 
     pub fn is_child(&self) -> bool {
@@ -95,52 +289,129 @@ impl Runtime {
     }
 
     pub fn get_field_number(&self) -> Option<u32> {
-        self.path.get_value().copied()
+        match &*self.position.link.lock().unwrap() {
+            Link::Root { .. } => None,
+            Link::Child { field_number, .. } => Some(*field_number),
+        }
     }
 
     pub fn field_number(&self) -> u32 {
-        *self.path.value()
+        self.get_field_number()
+            .expect("root node doesn't have any value")
+    }
+
+    /// Resolves the logger of the root this runtime is currently under.
+    ///
+    /// The logger is returned owned rather than borrowed, since relocating a parent
+    /// may put this runtime under another root.
+    pub fn logger(&self) -> Shared<Lock<dyn PausableLogger>> {
+        self.position
+            .with_resolved(false, |resolved| resolved.logger.clone())
+    }
+
+    /// Stamp of the latest change logged at or under this runtime,
+    /// which encoded bytes are kept against, see [`SizeCache`]. Stamps are drawn
+    /// for each tree on its own, never twice in the whole process though.
+    ///
+    /// Always 0 without the `incremental` feature, as nothing else compares stamps.
+    ///
+    /// [`SizeCache`]: struct.SizeCache.html
+    pub fn change_stamp(&self) -> u64 {
+        self.position.stamp.get()
+    }
+
+    /// Renews the stamp of this runtime alone, for a change whose parents are stamped
+    /// on their own, e.g. when merging or replaying, which pass through every parent.
+    pub fn touch(&self) {
+        self.position.stamp.renew();
     }
 
-    pub fn logger(&self) -> &Arc<Mutex<dyn PausableLogger>> {
-        &self.logger
+    /// Renews the stamps of this runtime and every parent, as logging does. Changes made
+    /// without logging, e.g. by assigning fields, need this to be picked up by
+    /// [`Serialize::serialize_incremental`].
+    ///
+    /// [`Serialize::serialize_incremental`]: ../ser/trait.Serialize.html#method.serialize_incremental
+    pub fn mark_changed(&self) {
+        self.position.with_resolved(true, |_| ());
+    }
+
+    /// Goes through the logger without cloning it out of the resolved position,
+    /// which is most of what logging costs for shallow states.
+    fn through_logger<R>(
+        &self,
+        renew: bool,
+        f: impl FnOnce(&[u32], &mut dyn PausableLogger) -> R,
+    ) -> R {
+        self.position.with_resolved(renew, |resolved| {
+            f(&resolved.path, &mut *resolved.logger.lock().unwrap())
+        })
+    }
+
+    fn log_at(&self, renew: bool, entry: impl FnOnce(&[u32]) -> LogEntry) -> io::Result<()> {
+        self.through_logger(renew, |path, logger| logger.log(entry(path)))
     }
 
     pub fn pause_logger(&self) -> u32 {
-        self.logger.lock().unwrap().pause()
+        self.through_logger(false, |_, logger| logger.pause())
     }
 
     pub fn unpause_logger(&self) -> u32 {
-        self.logger.lock().unwrap().unpause()
+        self.through_logger(false, |_, logger| logger.unpause())
     }
 
     pub fn log(&self, entry: LogEntry) -> io::Result<()> {
-        self.logger.lock().unwrap().log(entry)
+        self.through_logger(true, |_, logger| logger.log(entry))
     }
 
     pub fn log_multi(&self, entries: Vec<LogEntry>) -> io::Result<()> {
-        self.logger.lock().unwrap().log_multi(entries)
+        self.through_logger(true, |_, logger| logger.log_multi(entries))
     }
 
     impl_entry!(entry_update, new_update, value: &impl Serialize);
     impl_entry!(entry_list_push, new_list_push, item: &impl Serialize);
     impl_entry!(entry_list_pop, new_list_pop);
     impl_entry!(entry_map_remove, new_map_remove, key: u32);
+    impl_entry!(entry_checksum, new_checksum, hash: u64);
 
     pub fn entry_update_child(&self, field_number: u32, value: &impl Serialize) -> LogEntry {
-        LogEntry::new_update(&Node::child(&self.path, field_number), value)
+        let mut path = self.path();
+        path.push(field_number);
+        LogEntry::new_update(path, value)
     }
 
-    impl_log!(log_update, entry_update, value: &impl Serialize);
-    impl_log!(
-        log_update_child,
-        entry_update_child,
-        field_number: u32,
-        value: &impl Serialize,
-    );
-    impl_log!(log_list_push, entry_list_push, item: &impl Serialize);
-    impl_log!(log_list_pop, entry_list_pop);
-    impl_log!(log_map_remove, entry_map_remove, key: u32);
+    impl_log!(log_update, new_update, value: &impl Serialize);
+    impl_log!(log_list_push, new_list_push, item: &impl Serialize);
+    impl_log!(log_list_pop, new_list_pop);
+    impl_log!(log_map_remove, new_map_remove, key: u32);
+
+    pub fn log_update_child(&self, field_number: u32, value: &impl Serialize) -> io::Result<()> {
+        self.log_at(true, |path| {
+            let mut child_path = Vec::with_capacity(path.len() + 1);
+            child_path.extend_from_slice(path);
+            child_path.push(field_number);
+            LogEntry::new_update(child_path, value)
+        })
+    }
+
+    /// Logs `hash` for the state at this runtime. Unlike other entries it changes nothing,
+    /// so stamps are left as they are.
+    pub fn log_checksum(&self, hash: u64) -> io::Result<()> {
+        self.log_at(false, |path| LogEntry::new_checksum(path.to_vec(), hash))
+    }
+}
+
+impl Clone for Runtime {
+    fn clone(&self) -> Self {
+        Self::sharing(self.position.clone(), self.owned)
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        if self.owned {
+            self.position.owners.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
 }
 
 impl PartialEq for Runtime {
@@ -165,7 +436,101 @@ impl fmt::Debug for Runtime {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Runtime")
             .field("logger", &"<logger>")
-            .field("path", &*self.path)
+            .field("path", &self.path())
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::log::loggers::BufferLogger;
+
+    use super::Runtime;
+
+    #[test]
+    fn path() {
+        let runtime = Runtime::new().nested(1).nested(2);
+        assert_eq!(runtime.path(), vec![1, 2]);
+        assert_eq!(runtime.parent().path(), vec![1]);
+        assert_eq!(runtime.sibling(3).path(), vec![1, 3]);
+        assert!(runtime.parent().parent().is_root());
+    }
+
+    #[test]
+    fn relocate_moves_nested() {
+        let root = Runtime::new();
+        let mut moved = root.nested(0).nested(1);
+        let nested = moved.nested(2).nested(3);
+        assert_eq!(nested.path(), vec![0, 1, 2, 3]);
+
+        assert!(moved.relocate(root.nested(5)).is_ok());
+        assert_eq!(moved.path(), vec![5]);
+        assert_eq!(nested.path(), vec![5, 2, 3]);
+    }
+
+    #[test]
+    fn relocate_to_another_root() {
+        let mut runtime = Runtime::with_logger(BufferLogger::new()).nested(0);
+        let nested = runtime.nested(1);
+        nested.log_update(&5).unwrap();
+
+        let (root, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        assert!(runtime.relocate(root.nested(4)).is_ok());
+
+        nested.log_update(&7).unwrap();
+        assert_eq!(
+            logger.lock().unwrap().bytes(),
+            &[8, 0, 2, 2, 4, 1, 10, 1, 14]
+        );
+    }
+
+    #[test]
+    fn relocate_shared() {
+        let root = Runtime::new();
+        let mut runtime = root.nested(0);
+        let clone = runtime.clone();
+
+        assert!(runtime.relocate(root.nested(1)).is_err());
+        assert_eq!(clone.path(), vec![0]);
+
+        drop(clone);
+        assert!(runtime.relocate(root.nested(1)).is_ok());
+    }
+
+    #[test]
+    fn relocate_parent() {
+        let root = Runtime::new();
+        let parent = root.nested(0);
+        let child = parent.nested(1);
+
+        assert!(child.parent().relocate(root.nested(9)).is_err());
+        assert_eq!(parent.path(), vec![0]);
+        assert_eq!(child.path(), vec![0, 1]);
+    }
+
+    #[test]
+    #[cfg(feature = "incremental")]
+    fn stamps_after_relocate() {
+        let mut runtime = Runtime::with_logger(BufferLogger::new()).nested(0);
+        let nested = runtime.nested(1);
+        let kept = nested.change_stamp();
+
+        let root = Runtime::with_logger(BufferLogger::new());
+        assert!(runtime.relocate(root.nested(4)).is_ok());
+        nested.log_update(&7).unwrap();
+
+        // Drawn in another tree, yet not the stamp of before
+        assert_ne!(nested.change_stamp(), kept);
+        assert_eq!(runtime.change_stamp(), nested.change_stamp());
+        assert_eq!(root.change_stamp(), nested.change_stamp());
+    }
+
+    #[test]
+    fn relocate_under_itself() {
+        let mut runtime = Runtime::new().nested(0);
+        let nested = runtime.nested(1);
+
+        assert!(runtime.relocate(nested.nested(2)).is_err());
+        assert_eq!(nested.path(), vec![0, 1]);
+    }
+}
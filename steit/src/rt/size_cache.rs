@@ -1,22 +1,51 @@
+use std::hash::{Hash, Hasher};
+
+#[cfg(not(feature = "streaming"))]
 use std::{
-    hash::{Hash, Hasher},
+    fmt,
     sync::atomic::{AtomicU32, Ordering},
 };
 
+#[cfg(feature = "incremental")]
+use std::{io, sync::Arc};
+
+#[cfg(feature = "incremental")]
+use crate::ser::{Recording, Writer};
+
+#[cfg(feature = "incremental")]
+use super::sync::Lock;
+
 /// Caches serialization size to prevent duplicate calculation.
 ///
+/// With the `incremental` feature, states [serialized incrementally] also keep their encoded
+/// bytes here, along with the [change stamp] of their runtime at the time. While the stamp
+/// stays the same, nothing at or under the state has been logged since, and the bytes are
+/// written again as they are instead of encoding the whole subtree. Kept bytes refer to those
+/// of children instead of copying them, so a tree keeps about as many bytes as it encodes to,
+/// however deep it is. Bytes kept in one [encoding] are never reused in the other.
+///
 /// A [`SizeCache`] is always equal to itself so its containing object can use `#[derive(Eq)]`.
 ///
 /// This references [`CachedSize`] from [rust-protobuf].
 ///
+/// [serialized incrementally]: ../ser/struct.Writer.html#method.incremental
+/// [change stamp]: struct.Runtime.html#method.change_stamp
+/// [encoding]: ../ser/index.html
 /// [`SizeCache`]: struct.SizeCache.html
 /// [rust-protobuf]: https://github.com/stepancheg/rust-protobuf
 /// [`CachedSize`]: https://github.com/stepancheg/rust-protobuf/blob/68c7a5a/protobuf/src/cached_size.rs
-#[derive(Default, Debug)]
+#[cfg(not(feature = "streaming"))]
+#[derive(Default)]
 pub struct SizeCache {
     size: AtomicU32,
+    #[cfg(feature = "incremental")]
+    kept: Lock<Option<Arc<KeptBytes>>>,
 }
 
+#[cfg(feature = "streaming")]
+pub use self::streaming::SizeCache;
+
+#[cfg(not(feature = "streaming"))]
 impl SizeCache {
     /// Creates a new [`SizeCache`] and initializes it to 0.
     ///
@@ -49,11 +78,74 @@ impl SizeCache {
     }
 }
 
+#[cfg(feature = "incremental")]
+impl SizeCache {
+    /// Gets the size of the kept bytes if they were encoded at `stamp`.
+    pub fn reusable_size(&self, stamp: u64) -> Option<u32> {
+        match &*self.kept.lock().unwrap() {
+            // Both encodings take the same size
+            Some(kept) if kept.stamp == stamp => Some(kept.len()),
+            _ => None,
+        }
+    }
+
+    /// Writes the kept bytes if they were encoded at `stamp`, in the encoding of `writer`.
+    /// Otherwise encodes them anew with `serialize`, then keeps and writes them.
+    pub fn reuse_bytes(
+        &self,
+        stamp: u64,
+        writer: &mut Writer<impl io::Write>,
+        serialize: impl FnOnce(&mut Writer<io::Sink>) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let canonical = writer.is_canonical();
+
+        let reusable = match &*self.kept.lock().unwrap() {
+            Some(kept) if kept.stamp == stamp && kept.canonical == canonical => Some(kept.clone()),
+            _ => None,
+        };
+
+        let kept = match reusable {
+            Some(kept) => kept,
+            None => {
+                let mut recording = writer.recording();
+                serialize(&mut recording)?;
+
+                let kept = Arc::new(KeptBytes {
+                    stamp,
+                    canonical,
+                    recording: recording.into_recording(),
+                });
+
+                *self.kept.lock().unwrap() = Some(kept.clone());
+                kept
+            }
+        };
+
+        writer.splice(&kept)
+    }
+
+    /// Drops the kept bytes, freeing their memory unless a parent still refers to them.
+    pub fn clear_bytes(&self) {
+        *self.kept.lock().unwrap() = None;
+    }
+}
+
+/// Clones start without kept bytes, which they'd only rarely get to reuse.
+#[cfg(not(feature = "streaming"))]
 impl Clone for SizeCache {
     fn clone(&self) -> Self {
-        Self {
-            size: AtomicU32::new(self.get()),
-        }
+        let size_cache = Self::default();
+        size_cache.set(self.get());
+        size_cache
+    }
+}
+
+#[cfg(not(feature = "streaming"))]
+impl fmt::Debug for SizeCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SizeCache")
+            .field("size", &self.get())
+            .finish()
     }
 }
 
@@ -69,6 +161,72 @@ impl Hash for SizeCache {
     fn hash<H: Hasher>(&self, _state: &mut H) {}
 }
 
+/// Bytes kept for a state, see [`SizeCache`]. They're shared through an `Arc` even with
+/// the `unsync` feature, which leaves log entries and their size caches `Send`.
+///
+/// [`SizeCache`]: struct.SizeCache.html
+#[cfg(feature = "incremental")]
+pub(crate) struct KeptBytes {
+    stamp: u64,
+    canonical: bool,
+    recording: Recording,
+}
+
+#[cfg(feature = "incremental")]
+impl KeptBytes {
+    pub fn len(&self) -> u32 {
+        self.recording.len()
+    }
+
+    pub fn write_to(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.recording.write_to(writer)
+    }
+}
+
+#[cfg(feature = "streaming")]
+mod streaming {
+    use std::fmt;
+
+    #[cfg(feature = "incremental")]
+    use std::sync::Arc;
+
+    #[cfg(feature = "incremental")]
+    use super::{KeptBytes, Lock};
+
+    /// Takes no space unless bytes are kept for incremental serialization,
+    /// since sizes aren't cached when serialization always streams.
+    #[derive(Default)]
+    pub struct SizeCache {
+        #[cfg(feature = "incremental")]
+        pub(super) kept: Lock<Option<Arc<KeptBytes>>>,
+    }
+
+    impl SizeCache {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Always 0, as sizes aren't kept.
+        pub fn get(&self) -> u32 {
+            0
+        }
+
+        pub fn set(&self, _size: u32) {}
+    }
+
+    impl Clone for SizeCache {
+        fn clone(&self) -> Self {
+            Self::default()
+        }
+    }
+
+    impl fmt::Debug for SizeCache {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("SizeCache").finish()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test_case;
@@ -78,7 +236,14 @@ mod tests {
     fn assert_back_and_forth(value: u32) {
         let size_cache = SizeCache::new();
         size_cache.set(value);
-        assert_eq!(size_cache.get(), value);
+
+        // Nothing is cached with the `streaming` feature
+        let expected = if cfg!(feature = "streaming") {
+            0
+        } else {
+            value
+        };
+        assert_eq!(size_cache.get(), expected);
     }
 
     test_case!(back_and_forth_01: assert_back_and_forth; 0);
@@ -86,3 +251,214 @@ mod tests {
     test_case!(back_and_forth_03: assert_back_and_forth; 1337);
     test_case!(back_and_forth_04: assert_back_and_forth; 1_000_000_007);
 }
+
+#[cfg(all(test, feature = "incremental"))]
+mod incremental_tests {
+    use std::{io::Write, sync::Arc};
+
+    use crate::{
+        de::{Deserialize, Reader},
+        log::loggers::{BufferLogger, NoopLogger},
+        rt::Runtime,
+        ser::{Part, Serialize, Writer},
+        state::State,
+        steit_derive,
+        test_util::Point,
+        types::List,
+    };
+
+    use super::{KeptBytes, SizeCache};
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned, transparent)]
+    struct Squad(List<Point>);
+
+    fn decode<T: Deserialize>(bytes: &[u8]) -> T {
+        T::deserialize(&mut Reader::new(bytes)).unwrap()
+    }
+
+    fn incremental(value: &impl Serialize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.serialize_incremental(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn is_kept(value: &impl Serialize) -> bool {
+        let stamp = value.change_stamp().unwrap();
+        value.size_cache().unwrap().reusable_size(stamp).is_some()
+    }
+
+    fn kept(value: &impl Serialize) -> Arc<KeptBytes> {
+        value
+            .size_cache()
+            .unwrap()
+            .kept
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap()
+    }
+
+    fn refers_to(parent: &KeptBytes, child: &Arc<KeptBytes>) -> bool {
+        parent.recording.parts.iter().any(|part| match part {
+            Part::Kept(kept) => Arc::ptr_eq(kept, child),
+            Part::Bytes(_) => false,
+        })
+    }
+
+    fn points(runtime: Runtime, xs: &[i32]) -> List<Point> {
+        List::from_iter(
+            runtime,
+            xs.iter().map(|&x| Point::new(Runtime::new(), x, 0, 0)),
+        )
+    }
+
+    #[test]
+    fn reuse_bytes() {
+        let size_cache = SizeCache::new();
+        let mut encoded = 0;
+
+        for stamp in &[1, 1, 2] {
+            let mut writer = Writer::new(Vec::new());
+
+            size_cache
+                .reuse_bytes(*stamp, &mut writer, |bytes| {
+                    encoded += 1;
+                    bytes.write_all(&[*stamp as u8])
+                })
+                .unwrap();
+
+            assert_eq!(writer.into_inner(), vec![*stamp as u8]);
+        }
+
+        assert_eq!(encoded, 2);
+        assert_eq!(size_cache.reusable_size(2), Some(1));
+        assert_eq!(size_cache.reusable_size(1), None);
+
+        size_cache.clear_bytes();
+        assert_eq!(size_cache.reusable_size(2), None);
+    }
+
+    #[test]
+    fn reuse_until_changed() {
+        let mut lists = List::new(Runtime::with_logger(NoopLogger::new()));
+        lists.push_with(|runtime| points(runtime, &[1, 2]));
+        lists.push_with(|runtime| points(runtime, &[3]));
+
+        let bytes = incremental(&lists);
+        assert!(is_kept(&lists));
+        assert_eq!(incremental(&lists), bytes);
+
+        lists.get_mut(0).unwrap().get_mut(1).unwrap().set_x(4);
+        assert!(!is_kept(&lists));
+        assert!(!is_kept(&lists[0]));
+        assert!(is_kept(&lists[0][0]));
+        assert!(is_kept(&lists[1]));
+
+        let decoded: List<List<Point>> = decode(&incremental(&lists));
+        assert_eq!(decoded, lists);
+        assert_eq!(decoded[0][1].x, 4);
+    }
+
+    #[test]
+    fn share_kept_bytes() {
+        let mut lists = List::new(Runtime::with_logger(NoopLogger::new()));
+        lists.push_with(|runtime| points(runtime, &[1, 2]));
+        incremental(&lists);
+
+        // Parents refer to what their children keep rather than copying it
+        assert!(refers_to(&kept(&lists), &kept(&lists[0])));
+        assert!(refers_to(&kept(&lists[0]), &kept(&lists[0][1])));
+
+        let unchanged = kept(&lists[0][0]);
+        lists.get_mut(0).unwrap().get_mut(1).unwrap().set_x(4);
+        incremental(&lists);
+        assert!(refers_to(&kept(&lists[0]), &unchanged));
+    }
+
+    #[test]
+    fn transparent_wrappers() {
+        let mut squads = List::new(Runtime::with_logger(NoopLogger::new()));
+        squads.push_with(|runtime| Squad(points(runtime, &[1, 2])));
+        squads.push_with(|runtime| Squad(points(runtime, &[3])));
+        incremental(&squads);
+
+        // Wrappers keep the bytes of what they wrap, with its stamp
+        assert!(is_kept(&squads[0]));
+        assert!(refers_to(&kept(&squads), &kept(&squads[0])));
+
+        let unchanged = kept(&squads[1]);
+        squads.get_mut(0).unwrap().0.get_mut(1).unwrap().set_x(4);
+        assert!(!is_kept(&squads[0]));
+        assert!(is_kept(&squads[1]));
+
+        let decoded: List<Squad> = decode(&incremental(&squads));
+        assert_eq!(decoded, squads);
+        assert!(refers_to(&kept(&squads), &unchanged));
+    }
+
+    #[test]
+    fn clones_keep_apart() {
+        let mut list = List::from_iter(Runtime::with_logger(NoopLogger::new()), vec![1]);
+        let clone = list.clone();
+        incremental(&list);
+
+        list.push(2);
+        assert_eq!(&*decode::<List<i32>>(&incremental(&clone)), &[1]);
+        assert_eq!(&*decode::<List<i32>>(&incremental(&list)), &[1, 2]);
+    }
+
+    #[test]
+    fn replay_changes() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut source = points(runtime, &[1, 2]);
+        let mut target = points(Runtime::new(), &[1, 2]);
+        incremental(&target);
+
+        source.get_mut(1).unwrap().set_y(5);
+        target
+            .replay(&mut Reader::new(&*logger.lock().unwrap().bytes()))
+            .unwrap();
+
+        assert!(!is_kept(&target));
+        assert_eq!(incremental(&target), source.to_bytes());
+    }
+
+    #[test]
+    fn unlogged_changes() {
+        let mut point = Point::new(Runtime::with_logger(NoopLogger::new()), 1, 2, 3);
+        point.to_bytes();
+        incremental(&point);
+
+        point.x = 42;
+        assert_eq!(decode::<Point>(&point.to_bytes()).x, 42);
+        assert_eq!(decode::<Point>(&incremental(&point)).x, 1);
+
+        point.runtime().mark_changed();
+        assert_eq!(decode::<Point>(&incremental(&point)).x, 42);
+    }
+
+    #[test]
+    fn unlogged_changes_of_items() {
+        let mut list = points(Runtime::with_logger(NoopLogger::new()), &[1, 2]);
+        list.to_bytes();
+        incremental(&list);
+
+        list[0].x = 7;
+        assert_eq!(decode::<List<Point>>(&list.to_bytes())[0].x, 7);
+        assert_eq!(
+            list.state_hash(),
+            points(Runtime::new(), &[7, 2]).state_hash()
+        );
+    }
+
+    #[test]
+    fn kept_only_when_incremental() {
+        let list = points(Runtime::with_logger(NoopLogger::new()), &[1]);
+        list.to_bytes();
+        assert!(!is_kept(&list));
+
+        incremental(&list);
+        assert!(is_kept(&list));
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(feature = "incremental")]
+pub(super) use self::tracked::Stamp;
+
+#[cfg(not(feature = "incremental"))]
+pub(super) use self::untracked::Stamp;
+
+#[cfg(feature = "incremental")]
+mod tracked {
+    use std::{
+        ops::Range,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+
+    #[cfg(feature = "unsync")]
+    use std::cell::Cell;
+
+    use super::super::sync::{Lock, Shared};
+
+    // Stamps each tree draws before reserving more
+    const BLOCK: u64 = 1 << 16;
+
+    /// Stamps of one tree, drawn without touching those of other trees.
+    ///
+    /// They're reserved a block at a time from a global counter, so that a stamp is still never
+    /// seen in two trees. States keep their bytes when moved to another tree, and a stamp
+    /// drawn there mustn't match one they were kept at.
+    #[derive(Default)]
+    struct Stamps {
+        reserved: Lock<Range<u64>>,
+    }
+
+    impl Stamps {
+        fn next(&self) -> u64 {
+            static BLOCKS: AtomicU64 = AtomicU64::new(0);
+
+            let mut reserved = self.reserved.lock().unwrap();
+
+            if reserved.is_empty() {
+                let start = BLOCKS.fetch_add(1, Ordering::Relaxed) * BLOCK;
+                *reserved = start..start + BLOCK;
+            }
+
+            let stamp = reserved.start;
+            reserved.start += 1;
+            stamp
+        }
+    }
+
+    /// Stamp of the latest change at a position or under it, along with the stamps of the tree
+    /// the position was made in, which new stamps are drawn from.
+    pub struct Stamp {
+        #[cfg(not(feature = "unsync"))]
+        value: AtomicU64,
+        #[cfg(feature = "unsync")]
+        value: Cell<u64>,
+        stamps: Shared<Stamps>,
+    }
+
+    impl Stamp {
+        pub fn root() -> Self {
+            Self::drawn_from(Shared::new(Stamps::default()))
+        }
+
+        /// Makes the stamp of a position nested in the one of `self`, in the same tree.
+        pub fn nested(&self) -> Self {
+            Self::drawn_from(self.stamps.clone())
+        }
+
+        fn drawn_from(stamps: Shared<Stamps>) -> Self {
+            let value = stamps.next();
+
+            Self {
+                value: value.into(),
+                stamps,
+            }
+        }
+
+        #[cfg(not(feature = "unsync"))]
+        pub fn get(&self) -> u64 {
+            self.value.load(Ordering::Relaxed)
+        }
+
+        #[cfg(not(feature = "unsync"))]
+        pub fn set(&self, value: u64) {
+            self.value.store(value, Ordering::Relaxed);
+        }
+
+        #[cfg(feature = "unsync")]
+        pub fn get(&self) -> u64 {
+            self.value.get()
+        }
+
+        #[cfg(feature = "unsync")]
+        pub fn set(&self, value: u64) {
+            self.value.set(value);
+        }
+
+        /// Draws a new stamp and sets it, then returns it for parents to be set to as well.
+        pub fn renew(&self) -> u64 {
+            let value = self.stamps.next();
+            self.set(value);
+            value
+        }
+    }
+}
+
+#[cfg(not(feature = "incremental"))]
+mod untracked {
+    /// Takes no space, since stamps are only compared when serializing incrementally.
+    pub struct Stamp;
+
+    impl Stamp {
+        pub fn root() -> Self {
+            Self
+        }
+
+        pub fn nested(&self) -> Self {
+            Self
+        }
+
+        /// Always 0, as stamps aren't kept.
+        pub fn get(&self) -> u64 {
+            0
+        }
+
+        pub fn set(&self, _value: u64) {}
+
+        pub fn renew(&self) -> u64 {
+            0
+        }
+    }
+}
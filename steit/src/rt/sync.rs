@@ -0,0 +1,51 @@
+//! Pointers and locks which runtimes are shared through.
+//!
+//! These are `Arc` and `Mutex` by default. With the `unsync` feature they become `Rc`
+//! and a `RefCell` wrapper, which skip atomic reference counting and locking
+//! for states that never leave their thread.
+//!
+//! **The feature is not additive.** Cargo turns it on for the whole build as soon as
+//! one crate asks for it, and every state then stops being `Send` and `Sync`,
+//! breaking any other crate which moves states or loggers across threads.
+//! Only the final binary should enable it, never a library depending on steit.
+
+#[cfg(not(feature = "unsync"))]
+pub use std::sync::{Arc as Shared, Mutex as Lock};
+
+#[cfg(feature = "unsync")]
+pub use std::rc::Rc as Shared;
+
+#[cfg(feature = "unsync")]
+pub use self::unsync::Lock;
+
+#[cfg(feature = "unsync")]
+mod unsync {
+    use std::cell::{BorrowMutError, RefCell, RefMut};
+
+    /// A `RefCell` taking the API of `Mutex`, so that loggers are reached the same way
+    /// whichever the feature, e.g. `logger.lock().unwrap()`.
+    #[derive(Default, Debug)]
+    pub struct Lock<T: ?Sized>(RefCell<T>);
+
+    impl<T> Lock<T> {
+        pub fn new(value: T) -> Self {
+            Lock(RefCell::new(value))
+        }
+
+        pub fn into_inner(self) -> Result<T, BorrowMutError> {
+            Ok(self.0.into_inner())
+        }
+    }
+
+    impl<T: ?Sized> Lock<T> {
+        /// Fails instead of blocking when the value is already borrowed,
+        /// which single-threaded code could only wait on forever.
+        pub fn lock(&self) -> Result<RefMut<'_, T>, BorrowMutError> {
+            self.0.try_borrow_mut()
+        }
+
+        pub fn get_mut(&mut self) -> Result<&mut T, BorrowMutError> {
+            Ok(self.0.get_mut())
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Serialization, in either of two encodings.
+//!
+//! The default one writes things in whatever order is at hand, e.g. fields in the order
+//! they're declared in and map entries in the order they're kept in. The canonical one
+//! gives a single byte representation per value, for signing, hashing or caching by content:
+//!
+//! * Fields come in ascending tag order, those of flattened structs included.
+//! * `Map` entries come in ascending key order, and `HashMap` entries in the order
+//!   of their encoded keys.
+//! * A field is omitted if and only if its value is omissible, i.e. a primitive
+//!   equal to its default or a message with nothing to write, unless the field
+//!   has a custom default, in which case it's always written.
+//! * Varints take as few bytes as possible, and there are no unknown fields.
+//!
+//! Both are read the same way, and [`Deserialize::from_canonical_bytes`] checks
+//! that its input is in the canonical encoding.
+//!
+//! [`Deserialize::from_canonical_bytes`]: ../de/trait.Deserialize.html#method.from_canonical_bytes
+//!
+//! The encoding is that of the [`Writer`] a value is serialized into, which hands it down
+//! to nested values. Codecs of fields, loggers and the like, which make writers of their own,
+//! start over from the default encoding, so a codec must write one representation per value
+//! for its field to be canonical.
+//!
+//! [`Writer`]: struct.Writer.html
+//!
+//! Either encoding can be written in two ways. By default, sizes of the whole value are
+//! computed and cached first, so that length prefixes can be written right before what
+//! they measure. Streaming, asked for with [`Writer::streaming`], instead writes every nested message into a scratch buffer
+//! and prefixes it once its length is known, which takes a single pass and no cache,
+//! and suits values written only once, e.g. log entries and snapshots sent over a socket.
+//! With the `streaming` feature, it's the only way, and size caches take no space.
+//!
+//! With the `incremental` feature, [`Writer::incremental`] also keeps the bytes of each state
+//! in its size cache, and writes them again as they are while the state hasn't logged
+//! any change since. Changes which aren't logged, e.g. assigning a public field or writing
+//! through `IndexMut`, go unnoticed until [`Runtime::mark_changed`] is called, so it's only
+//! for states changed through their setters. Other ways of serializing never reuse bytes.
+//! Without the feature, size caches and runtimes keep neither bytes nor stamps.
+//!
+//! [`Writer::streaming`]: struct.Writer.html#method.streaming
+//! [`Writer::incremental`]: struct.Writer.html#method.incremental
+//! [`Runtime::mark_changed`]: ../rt/struct.Runtime.html#method.mark_changed
+
+#[allow(clippy::module_inception)]
+mod ser;
+mod writer;
+
+pub use ser::*;
+pub use writer::*;
@@ -0,0 +1,317 @@
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+};
+
+use crate::{
+    rt::SizeCache,
+    wire_fmt::{HasWireType, WireType},
+};
+
+use super::writer::Writer;
+
+thread_local! {
+    static SCRATCH: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f` with an empty buffer, which is kept for later calls afterwards
+/// so that streaming doesn't allocate for every nested message.
+fn with_scratch<T>(f: impl FnOnce(&mut Vec<u8>) -> T) -> T {
+    let mut scratch = SCRATCH
+        .with(|scratches| scratches.borrow_mut().pop())
+        .unwrap_or_default();
+
+    let result = f(&mut scratch);
+    scratch.clear();
+    SCRATCH.with(|scratches| scratches.borrow_mut().push(scratch));
+    result
+}
+
+/// Writes streamed content of `len` bytes with `write`, after its tag if any and its length.
+/// Sized values are omissible exactly when they have nothing to write.
+fn write_streamed<W: io::Write>(
+    tag: Option<u32>,
+    is_omissible: bool,
+    len: u32,
+    writer: &mut Writer<W>,
+    write: impl FnOnce(&mut Writer<W>) -> io::Result<()>,
+) -> io::Result<()> {
+    if tag.is_some() && is_omissible && len == 0 {
+        return Ok(());
+    }
+
+    if let Some(tag) = tag {
+        tag.serialize_cached(writer)?;
+    }
+
+    len.serialize_cached(writer)?;
+    write(writer)
+}
+
+pub trait Serialize: HasWireType {
+    fn compute_size(&self) -> u32;
+    fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()>;
+
+    fn size_cache(&self) -> Option<&SizeCache>;
+
+    /// Stamp of the latest change of this object, for states, whose encoded bytes
+    /// are then kept in their size cache and reused until it changes
+    /// when serializing incrementally.
+    fn change_stamp(&self) -> Option<u64> {
+        None
+    }
+
+    fn cache_size(&self) -> u32 {
+        let size = self.compute_size();
+
+        if let Some(size_cache) = self.size_cache() {
+            size_cache.set(size);
+        }
+
+        size
+    }
+
+    fn cached_size(&self) -> u32 {
+        match self.size_cache() {
+            Some(size_cache) if !cfg!(feature = "streaming") => size_cache.get(),
+            _ => self.compute_size(),
+        }
+    }
+
+    /// Same as `serialize_cached`, but reuses the kept bytes if this hasn't changed since,
+    /// when serializing incrementally.
+    fn serialize_reusing(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
+        #[cfg(feature = "incremental")]
+        if writer.is_incremental() {
+            if let (Some(size_cache), Some(stamp)) = (self.size_cache(), self.change_stamp()) {
+                return size_cache.reuse_bytes(stamp, writer, |bytes| self.serialize_cached(bytes));
+            }
+        }
+
+        self.serialize_cached(writer)
+    }
+
+    fn serialize(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.serialize_to(&mut Writer::new(writer))
+    }
+
+    /// Same as `serialize`, but in the way `writer` serializes.
+    fn serialize_to(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
+        if !writer.is_streaming() {
+            self.cache_size();
+        }
+
+        self.serialize_reusing(writer)
+    }
+
+    fn is_omissible(&self) -> bool {
+        self.compute_size() == 0
+    }
+
+    fn compute_size_nested(
+        &self,
+        field_number: impl Into<Option<u32>>,
+        is_omissible: bool,
+    ) -> io::Result<u32> {
+        let field_number = field_number.into();
+
+        if field_number.is_some() && is_omissible && self.is_omissible() {
+            return Ok(0);
+        }
+
+        let mut size = self.cache_size();
+
+        match Self::WIRE_TYPE {
+            WireType::Varint => (),
+            WireType::Sized => size += size.cache_size(),
+        }
+
+        if let Some(field_number) = field_number {
+            size += self.tag(field_number)?.cache_size();
+        }
+
+        Ok(size)
+    }
+
+    fn serialize_nested(
+        &self,
+        field_number: impl Into<Option<u32>>,
+        is_omissible: bool,
+        writer: &mut Writer<impl io::Write>,
+    ) -> io::Result<()> {
+        let field_number = field_number.into();
+
+        if Self::WIRE_TYPE == WireType::Sized && writer.is_streaming() {
+            let tag = field_number
+                .map(|field_number| self.tag(field_number))
+                .transpose()?;
+
+            // Recorded rather than copied, so that bytes kept for nested states are only referred to
+            #[cfg(feature = "incremental")]
+            if writer.is_incremental() {
+                let mut content = writer.recording();
+                self.serialize_reusing(&mut content)?;
+
+                let content = content.into_recording();
+                let len = content.len();
+                return write_streamed(tag, is_omissible, len, writer, |writer| {
+                    writer.append(content)
+                });
+            }
+
+            return with_scratch(|content| {
+                self.serialize_reusing(&mut writer.nested(&mut *content))?;
+
+                write_streamed(tag, is_omissible, content.len() as u32, writer, |writer| {
+                    writer.write_all(content)
+                })
+            });
+        }
+
+        if field_number.is_some() && is_omissible && self.is_omissible() {
+            return Ok(());
+        }
+
+        if let Some(field_number) = field_number {
+            self.tag(field_number)?.serialize_cached(writer)?;
+        }
+
+        match Self::WIRE_TYPE {
+            WireType::Varint => (),
+            WireType::Sized => self.cached_size().serialize_cached(writer)?,
+        }
+
+        self.serialize_reusing(writer)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Same as `serialize`, but in the canonical encoding.
+    fn serialize_canonical(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.serialize_to(&mut Writer::new(writer).canonical())
+    }
+
+    /// Same as `serialize`, but streaming, i.e. in a single pass which leaves
+    /// size caches as they are.
+    fn serialize_streaming(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.serialize_to(&mut Writer::new(writer).streaming())
+    }
+
+    /// Same as `serialize`, but keeping the bytes of each state and reusing those of states
+    /// which haven't logged any change since, see [`Writer::incremental`].
+    ///
+    /// [`Writer::incremental`]: struct.Writer.html#method.incremental
+    #[cfg(feature = "incremental")]
+    fn serialize_incremental(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.serialize_to(&mut Writer::new(writer).incremental())
+    }
+
+    /// Same as `serialize_nested`, but streaming.
+    fn serialize_nested_streaming(
+        &self,
+        field_number: impl Into<Option<u32>>,
+        is_omissible: bool,
+        writer: &mut impl io::Write,
+    ) -> io::Result<()> {
+        self.serialize_nested(
+            field_number,
+            is_omissible,
+            &mut Writer::new(writer).streaming(),
+        )
+    }
+
+    fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.serialize_canonical(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Same as `serialize`, but appending to a `BufMut` such as `BytesMut`,
+    /// which saves collecting the bytes in a `Vec` first.
+    #[cfg(feature = "bytes")]
+    fn serialize_buf(&self, buf: &mut impl bytes::BufMut) -> io::Result<()> {
+        self.serialize(&mut bytes::BufMut::writer(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rt::Runtime,
+        test_util::{Foo, Point},
+        types::{List, Map},
+    };
+
+    use super::{Serialize, Writer};
+
+    fn streamed(value: &impl Serialize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.serialize_streaming(&mut bytes).unwrap();
+        bytes
+    }
+
+    fn lists() -> List<List<Point>> {
+        let mut lists = List::new(Runtime::new());
+
+        for len in [0, 1, 20] {
+            lists.push_with(|runtime| {
+                let mut points = List::new(runtime);
+
+                for x in 0..len {
+                    points.push_with(|runtime| Point::new(runtime, x, -x, 1000 * x));
+                }
+
+                points
+            });
+        }
+
+        lists
+    }
+
+    #[test]
+    fn streaming_same_bytes() {
+        let lists = lists();
+        assert!(lists[2].compute_size() >= 128);
+        assert_eq!(streamed(&lists), lists.to_bytes());
+
+        let mut map = Map::new(Runtime::new());
+        map.insert_with(7u32, |runtime| Point::new(runtime, 1, 2, 3));
+        map.insert_with(5u32, Point::empty);
+        assert_eq!(streamed(&map), map.to_bytes());
+    }
+
+    #[test]
+    fn streaming_nested() {
+        for (value, is_omissible) in [
+            (Foo::empty(), true),
+            (Foo::empty(), false),
+            (Foo::new(1, 2), true),
+        ] {
+            let mut expected = Writer::new(Vec::new());
+            value.cache_size();
+            value
+                .serialize_nested(10, is_omissible, &mut expected)
+                .unwrap();
+
+            let mut bytes = Vec::new();
+            value
+                .serialize_nested_streaming(10, is_omissible, &mut bytes)
+                .unwrap();
+
+            assert_eq!(bytes, expected.into_inner());
+        }
+    }
+
+    #[test]
+    fn streaming_no_sizes() {
+        let lists = lists();
+        streamed(&lists);
+
+        assert_eq!(lists.size_cache().unwrap().get(), 0);
+        assert_eq!(lists[2][0].size_cache().unwrap().get(), 0);
+    }
+}
@@ -0,0 +1,219 @@
+use std::io;
+
+#[cfg(feature = "incremental")]
+use std::sync::Arc;
+
+#[cfg(feature = "incremental")]
+use crate::rt::KeptBytes;
+
+/// Writes what's serialized into `inner`, along with how it's serialized, which nested values
+/// are handed down and can check, e.g. to put map entries in order for the canonical encoding.
+///
+/// The mode belongs to the writer rather than to the thread, so that whatever serializes
+/// on its own in the meantime, e.g. a logger, a hash or a codec of a field, starts over
+/// from the default one.
+pub struct Writer<W: io::Write> {
+    inner: W,
+    canonical: bool,
+    streaming: bool,
+    #[cfg(feature = "incremental")]
+    incremental: bool,
+    // Bytes encoded for a state to keep, which are recorded here instead of written to `inner`
+    #[cfg(feature = "incremental")]
+    recording: Option<Recording>,
+}
+
+impl<W: io::Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            canonical: false,
+            streaming: false,
+            #[cfg(feature = "incremental")]
+            incremental: false,
+            #[cfg(feature = "incremental")]
+            recording: None,
+        }
+    }
+
+    /// Writes in the [canonical encoding] instead.
+    ///
+    /// [canonical encoding]: index.html
+    pub fn canonical(mut self) -> Self {
+        self.canonical = true;
+        self
+    }
+
+    /// Streams instead, writing every nested message into a scratch buffer and prefixing it
+    /// once its length is known, with no sizes computed beforehand.
+    pub fn streaming(mut self) -> Self {
+        self.streaming = true;
+        self
+    }
+
+    /// Keeps the bytes of each state in its size cache and writes them again as they are
+    /// while the state hasn't logged any change since, which saves encoding unchanged
+    /// subtrees again. This streams as well, as sizes of what's kept are known already.
+    ///
+    /// Changes made without logging, e.g. by assigning a public field or through `IndexMut`,
+    /// aren't seen, and the bytes kept from before are written instead unless
+    /// [`Runtime::mark_changed`] is called for them.
+    ///
+    /// [`Runtime::mark_changed`]: ../rt/struct.Runtime.html#method.mark_changed
+    #[cfg(feature = "incremental")]
+    pub fn incremental(mut self) -> Self {
+        self.incremental = true;
+        self.streaming = true;
+        self
+    }
+
+    /// Whether this writes in the canonical encoding, which implementations
+    /// with a choice of order must check.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Whether this streams, as it always does with the `streaming` feature.
+    pub fn is_streaming(&self) -> bool {
+        cfg!(feature = "streaming") || self.streaming
+    }
+
+    #[cfg(feature = "incremental")]
+    pub fn is_incremental(&self) -> bool {
+        self.incremental
+    }
+
+    /// Makes a writer into `inner` which serializes the same way, e.g. into a buffer
+    /// whose bytes are then written here.
+    pub fn nested<V: io::Write>(&self, inner: V) -> Writer<V> {
+        Writer {
+            inner,
+            canonical: self.canonical,
+            streaming: self.streaming,
+            #[cfg(feature = "incremental")]
+            incremental: self.incremental,
+            #[cfg(feature = "incremental")]
+            recording: None,
+        }
+    }
+
+    /// Makes a writer which serializes the same way, recording what it's given
+    /// to be kept or written here later.
+    #[cfg(feature = "incremental")]
+    pub(crate) fn recording(&self) -> Writer<io::Sink> {
+        let mut writer = self.nested(io::sink());
+        writer.recording = Some(Recording::default());
+        writer
+    }
+
+    #[cfg(feature = "incremental")]
+    pub(crate) fn into_recording(self) -> Recording {
+        self.recording.unwrap_or_default()
+    }
+
+    /// Writes what `recording` holds, or refers to it if this is recording too.
+    #[cfg(feature = "incremental")]
+    pub(crate) fn append(&mut self, recording: Recording) -> io::Result<()> {
+        match &mut self.recording {
+            Some(own) => {
+                own.len += recording.len;
+                own.parts.extend(recording.parts);
+                Ok(())
+            }
+
+            None => recording.write_to(&mut self.inner),
+        }
+    }
+
+    /// Writes `kept`, or refers to it if this is recording too, so that kept bytes
+    /// are shared with those kept for parents rather than copied into them.
+    #[cfg(feature = "incremental")]
+    pub(crate) fn splice(&mut self, kept: &Arc<KeptBytes>) -> io::Result<()> {
+        match &mut self.recording {
+            Some(own) => {
+                own.len += kept.len();
+                own.parts.push(Part::Kept(kept.clone()));
+                Ok(())
+            }
+
+            None => kept.write_to(&mut self.inner),
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: io::Write> io::Write for Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "incremental")]
+        if let Some(recording) = &mut self.recording {
+            recording.push(buf);
+            return Ok(());
+        }
+
+        self.inner.write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: io::Write> From<W> for Writer<W> {
+    fn from(inner: W) -> Self {
+        Self::new(inner)
+    }
+}
+
+/// Bytes recorded while encoding a state to keep, in parts which are either written
+/// in between or kept for nested states already.
+#[cfg(feature = "incremental")]
+#[derive(Default)]
+pub(crate) struct Recording {
+    pub(crate) parts: Vec<Part>,
+    len: u32,
+}
+
+#[cfg(feature = "incremental")]
+pub(crate) enum Part {
+    Bytes(Vec<u8>),
+    Kept(Arc<KeptBytes>),
+}
+
+#[cfg(feature = "incremental")]
+impl Recording {
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    fn push(&mut self, buf: &[u8]) {
+        self.len += buf.len() as u32;
+
+        match self.parts.last_mut() {
+            Some(Part::Bytes(bytes)) => bytes.extend_from_slice(buf),
+            _ => self.parts.push(Part::Bytes(buf.to_vec())),
+        }
+    }
+
+    pub fn write_to(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        for part in &self.parts {
+            match part {
+                Part::Bytes(bytes) => writer.write_all(bytes)?,
+                Part::Kept(kept) => kept.write_to(writer)?,
+            }
+        }
+
+        Ok(())
+    }
+}
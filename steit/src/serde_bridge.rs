@@ -0,0 +1,257 @@
+//! Serde support, with the `serde` feature, for JSON APIs, admin panels or config files
+//! to use steit types directly.
+//!
+//! Derived types get serde impls as well, unless marked `#[steit(serde = false)]`, which
+//! types holding fields without serde impls need. Features are unified across the build,
+//! so a library deriving such types should opt them out even if it doesn't enable the feature:
+//!
+//! ```
+//! use steit::{rt::Runtime, steit_derive};
+//!
+//! #[steit_derive(Debug, State)]
+//! struct Hero {
+//!     #[steit(tag = 0)]
+//!     hp: i32,
+//! }
+//!
+//! let hero = Hero::new(Runtime::new());
+//! assert_eq!(serde_json::to_string(&hero).unwrap(), r#"{"hp":0}"#);
+//! ```
+//!
+//! Derived structs and variants become maps keyed by field names, or by field indices
+//! for tuple structs, leaving out runtimes, size caches and skipped fields. Flattened fields
+//! are inlined. Enums become single-entry maps from the variant name to its fields,
+//! and fieldless enums become variant names. `List`s are sequences, `Map`s are maps,
+//! `Maybe`s are options and `Bytes` are byte arrays.
+//!
+//! Deserialized states come back with a root `Runtime::new()`, their children
+//! placed under it as if they had been built there, and missing fields take their defaults.
+//! Another runtime can then be moved in with `set_runtime`.
+
+use std::{fmt, io};
+
+use serde::{
+    de::{self, DeserializeSeed, IgnoredAny, MapAccess, Visitor},
+    ser::{self, SerializeMap},
+    Deserializer, Serializer,
+};
+
+#[doc(hidden)]
+pub use serde;
+
+/// Writes the fields of a derived struct or enum as map entries.
+#[doc(hidden)]
+pub trait SerializeFields {
+    /// Writes the fields of the current variant instead of naming it, for enums.
+    fn serialize_fields<M: SerializeMap>(
+        &self,
+        in_variant: bool,
+        map: &mut M,
+    ) -> Result<(), M::Error>;
+}
+
+/// Reads the fields of a derived struct or enum from map entries.
+#[doc(hidden)]
+pub trait MergeFields {
+    const NAME: &'static str;
+
+    /// Reads the value of the field called `name`, returning whether there's one.
+    ///
+    /// Reads a field of the current variant instead of a variant name, for enums.
+    fn merge_field<'de, A: MapAccess<'de>>(
+        &mut self,
+        in_variant: bool,
+        name: &str,
+        map: &mut A,
+    ) -> Result<bool, A::Error>;
+
+    fn validate_fields(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn serialize_fields<S: Serializer>(
+    value: &impl SerializeFields,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(None)?;
+    value.serialize_fields(false, &mut map)?;
+    map.end()
+}
+
+#[doc(hidden)]
+pub fn merge_fields<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    value: &mut impl MergeFields,
+) -> Result<(), D::Error> {
+    Fields {
+        value,
+        in_variant: false,
+    }
+    .deserialize(deserializer)
+}
+
+/// Writes an entry from `name` to the fields of the current variant of `value`.
+#[doc(hidden)]
+pub fn serialize_variant<M: SerializeMap>(
+    map: &mut M,
+    name: &'static str,
+    value: &impl SerializeFields,
+) -> Result<(), M::Error> {
+    map.serialize_entry(name, &VariantFields(value))
+}
+
+/// Reads the fields of the current variant of `value`, which has just been switched to.
+#[doc(hidden)]
+pub fn merge_variant<'de, A: MapAccess<'de>>(
+    map: &mut A,
+    value: &mut impl MergeFields,
+) -> Result<(), A::Error> {
+    map.next_value_seed(Fields {
+        value,
+        in_variant: true,
+    })
+}
+
+struct VariantFields<'a, T>(&'a T);
+
+impl<'a, T: SerializeFields> ser::Serialize for VariantFields<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        self.0.serialize_fields(true, &mut map)?;
+        map.end()
+    }
+}
+
+struct Fields<'a, T> {
+    value: &'a mut T,
+    in_variant: bool,
+}
+
+impl<'de, 'a, T: MergeFields> DeserializeSeed<'de> for Fields<'a, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, 'a, T: MergeFields> Visitor<'de> for Fields<'a, T> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "a map of fields of `{}`", T::NAME)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(name) = map.next_key::<String>()? {
+            if !self.value.merge_field(self.in_variant, &name, &mut map)? {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+
+        if !self.in_variant {
+            self.value.validate_fields().map_err(de::Error::custom)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        log::loggers::NoopLogger,
+        rt::Runtime,
+        state::State,
+        test_util::Point,
+        types::{Bytes, List, Map, Maybe},
+    };
+
+    fn points() -> List<Point> {
+        let mut points = List::new(Runtime::with_logger(NoopLogger::new()));
+        points.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        points.push_with(|runtime| Point::new(runtime, -1, 0, 7));
+        points
+    }
+
+    #[test]
+    fn struct_to_json() {
+        let point = Point::new(Runtime::new(), 1, -2, 3);
+        let json = serde_json::to_string(&point).unwrap();
+        assert_eq!(json, r#"{"x":1,"y":-2,"z":3}"#);
+
+        let point: Point = serde_json::from_str(r#"{"y":5,"unknown":[1]}"#).unwrap();
+        assert_eq!((point.x, point.y, point.z), (0, 5, 0));
+        assert!(point.is_root());
+    }
+
+    #[test]
+    fn list_to_json() {
+        let points = points();
+        let json = serde_json::to_string(&points).unwrap();
+        assert_eq!(json, r#"[{"x":1,"y":2,"z":3},{"x":-1,"y":0,"z":7}]"#);
+
+        let back: List<Point> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, points);
+        assert!(back.is_root());
+        assert_eq!(back[1].runtime().path(), vec![1]);
+    }
+
+    #[test]
+    fn map_to_json() {
+        let mut map = Map::new(Runtime::with_logger(NoopLogger::new()));
+        map.insert_with(7u16, |runtime| {
+            let mut points = points();
+            points.set_runtime(runtime);
+            points
+        });
+        map.insert_with(2u16, List::new);
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(
+            json,
+            r#"{"7":[{"x":1,"y":2,"z":3},{"x":-1,"y":0,"z":7}],"2":[]}"#
+        );
+
+        let back: Map<u16, List<Point>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, map);
+        assert_eq!(back[&7][1].runtime().path(), vec![7, 1]);
+    }
+
+    #[test]
+    fn maybe_to_json() {
+        let mut maybe = Maybe::<Point>::new_none(Runtime::new());
+        assert_eq!(serde_json::to_string(&maybe).unwrap(), "null");
+
+        maybe = serde_json::from_str(r#"{"x":4}"#).unwrap();
+        assert!(maybe.is_some());
+        assert_eq!(
+            serde_json::to_string(&maybe).unwrap(),
+            r#"{"x":4,"y":0,"z":0}"#
+        );
+
+        if let Maybe::Some { value, .. } = &maybe {
+            assert_eq!(value.runtime().path(), vec![1, 0]);
+        }
+
+        let maybe: Maybe<Point> = serde_json::from_str("null").unwrap();
+        assert!(maybe.is_none());
+    }
+
+    #[test]
+    fn bytes_to_json() {
+        let bytes = Bytes::from_raw(vec![1, 2, 255]);
+        let json = serde_json::to_string(&bytes).unwrap();
+        assert_eq!(json, "[1,2,255]");
+        assert_eq!(serde_json::from_str::<Bytes>(&json).unwrap(), bytes);
+    }
+
+    #[test]
+    fn moved_to_another_runtime() {
+        let mut back: List<Point> = serde_json::from_str(r#"[{"x":1}]"#).unwrap();
+        back.set_runtime(Runtime::new().nested(3));
+        assert_eq!(back[0].runtime().path(), vec![3, 0]);
+    }
+}
@@ -0,0 +1,137 @@
+use std::{io, mem::MaybeUninit};
+
+use crate::{
+    de::Reader,
+    meta::{FieldMeta, MetaLink},
+    ser::Writer,
+    wire_fmt::WireType,
+};
+
+use super::State;
+
+/// Implemented by derived structs, so they can be inlined into others with `#[steit(flatten)]`.
+///
+/// A flattened struct puts its fields right into the tag space of its parent, on the wire
+/// as well as in paths, and shares the runtime of its parent instead of being nested in it.
+pub trait Flatten: State {
+    /// Tags of all fields, including those of structs flattened in turn.
+    const TAGS: &'static [u32];
+    const FIELDS: &'static [FieldMeta];
+    const LINKS: &'static [&'static MetaLink];
+
+    /// Merges the field numbered `field_number` if it is one of ours,
+    /// returning whether it was.
+    fn merge_field(
+        &mut self,
+        field_number: u32,
+        wire_type: WireType,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<bool>;
+
+    /// Serializes the field numbered `field_number` if it is one of ours, returning
+    /// whether it was, so that the canonical encoding can interleave our fields
+    /// with those of the parent in tag order.
+    fn serialize_field(
+        &self,
+        field_number: u32,
+        writer: &mut Writer<impl io::Write>,
+    ) -> io::Result<bool>;
+
+    /// Runs the checks of `#[steit(validate = "…")]`, which are left to the parent
+    /// since `merge_field` only sees one field at a time.
+    fn validate(&self) -> io::Result<()>;
+}
+
+/// Concatenates `parts`, which must add up to exactly `N` items.
+#[doc(hidden)]
+pub const fn concat<T: Copy, const N: usize>(parts: &[&[T]]) -> [T; N] {
+    let mut items = [MaybeUninit::<T>::uninit(); N];
+    let mut len = 0;
+    let mut part_index = 0;
+
+    while part_index < parts.len() {
+        let part = parts[part_index];
+        let mut index = 0;
+
+        while index < part.len() {
+            assert!(len < N, "too many items to concatenate");
+            items[len] = MaybeUninit::new(part[index]);
+            len += 1;
+            index += 1;
+        }
+
+        part_index += 1;
+    }
+
+    assert!(len == N, "too few items to concatenate");
+
+    // Safe since all `N` items have just been written
+    unsafe { (&items as *const [MaybeUninit<T>; N] as *const [T; N]).read() }
+}
+
+/// Fails to compile where flattened fields collide with other fields of their parent
+/// or take tags which their parent has reserved.
+#[doc(hidden)]
+pub const fn check_flattened_tags(tags: &[u32], reserved_tags: &[u32]) {
+    let mut index = 0;
+
+    while index < tags.len() {
+        let mut other_index = index + 1;
+
+        while other_index < tags.len() {
+            assert!(
+                tags[index] != tags[other_index],
+                "flattened fields collide with other fields of their parent",
+            );
+
+            other_index += 1;
+        }
+
+        let mut reserved_index = 0;
+
+        while reserved_index < reserved_tags.len() {
+            assert!(
+                tags[index] != reserved_tags[reserved_index],
+                "flattened fields take tags reserved by their parent",
+            );
+
+            reserved_index += 1;
+        }
+
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_flattened_tags, concat};
+
+    #[test]
+    fn concat_parts() {
+        const ITEMS: [u32; 4] = concat(&[&[1], &[], &[2, 3], &[4]]);
+        assert_eq!(ITEMS, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "too few items to concatenate")]
+    fn concat_too_few() {
+        let _: [u32; 3] = concat(&[&[1], &[2]]);
+    }
+
+    #[test]
+    fn distinct_tags() {
+        check_flattened_tags(&[0, 1, 5, 2], &[3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "flattened fields collide with other fields of their parent")]
+    fn colliding_tags() {
+        check_flattened_tags(&[0, 1, 5, 1], &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "flattened fields take tags reserved by their parent")]
+    fn reserved_tags() {
+        check_flattened_tags(&[0, 1], &[1]);
+    }
+}
@@ -0,0 +1,139 @@
+use std::{hash::Hasher, io};
+
+use twox_hash::XxHash64;
+
+use crate::{
+    de::{Deserialize, Reader},
+    ser::Serialize,
+};
+
+/// Hashes the [canonical encoding] of `value` with XXH64 and a zero seed, which gives
+/// the same result across processes and platforms, unlike `std::hash::Hash`.
+///
+/// [canonical encoding]: ../ser/index.html
+pub fn state_hash(value: &impl Serialize) -> u64 {
+    let mut writer = HashWriter(XxHash64::with_seed(0));
+    value.serialize_canonical(&mut writer).unwrap();
+    writer.0.finish()
+}
+
+/// Checks the hash carried by a `LogEntryKind::Checksum` entry against `actual`.
+pub fn check_hash(actual: u64, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+    let expected = u64::deserialize(reader)?;
+
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected hash {:#018x} but got {:#018x}", expected, actual),
+        ))
+    }
+}
+
+struct HashWriter(XxHash64);
+
+impl io::Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{hash::Hasher, io};
+
+    use twox_hash::XxHash64;
+
+    use crate::{
+        de::Reader,
+        log::loggers::BufferLogger,
+        rt::{LoggerHandle, Runtime},
+        ser::Serialize,
+        state::State,
+        test_util::Point,
+        types::List,
+    };
+
+    use super::state_hash;
+
+    fn points() -> (List<Point>, LoggerHandle<BufferLogger>) {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        (List::new(runtime), logger)
+    }
+
+    fn xxh64(bytes: &[u8]) -> u64 {
+        let mut hasher = XxHash64::with_seed(0);
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn known_values() {
+        assert_eq!(xxh64(b""), 0xef46_db37_51d8_e999);
+        assert_eq!(xxh64(b"abc"), 0x44bc_2cf5_ad77_0999);
+    }
+
+    #[test]
+    fn hash_encoding() {
+        let point = Point::new(Runtime::new(), 1, 2, 3);
+        assert_eq!(state_hash(&point), xxh64(&point.to_canonical_bytes()));
+        assert_eq!(state_hash(&0u8), xxh64(&[0]));
+    }
+
+    #[test]
+    fn hash_content_only() {
+        let mut list = List::new(Runtime::new());
+        list.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+
+        assert_eq!(
+            state_hash(&list[0]),
+            state_hash(&Point::new(Runtime::new(), 1, 2, 3))
+        );
+        assert_ne!(
+            state_hash(&list[0]),
+            state_hash(&Point::new(Runtime::new(), 3, 2, 1))
+        );
+    }
+
+    #[test]
+    fn replay_checksums() {
+        let (mut server, logger) = points();
+        server.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        server.log_checksum().unwrap();
+        server[0].log_checksum().unwrap();
+
+        let mut client = points().0;
+        let bytes = logger.lock().unwrap().bytes();
+        client.replay(&mut Reader::new(&*bytes)).unwrap();
+        assert_eq!(client, server);
+    }
+
+    #[test]
+    fn replay_diverged() {
+        let (mut server, logger) = points();
+        server.push_with(|runtime| Point::new(runtime, 1, 2, 3));
+        server.push_with(|runtime| Point::new(runtime, 4, 5, 6));
+        let bytes = logger.lock().unwrap().pluck_bytes();
+
+        let mut client = points().0;
+        client.replay(&mut Reader::new(&*bytes)).unwrap();
+        client[1].x = 0;
+
+        server[0].log_checksum().unwrap();
+        server[1].log_checksum().unwrap();
+        server.log_checksum().unwrap();
+        let bytes = logger.lock().unwrap().bytes();
+
+        let error = client.replay(&mut Reader::new(&*bytes)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error
+            .to_string()
+            .starts_with("state diverged at path [1]: "));
+    }
+}
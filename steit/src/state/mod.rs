@@ -0,0 +1,10 @@
+mod flatten;
+mod hash;
+mod r#mut;
+#[allow(clippy::module_inception)]
+mod state;
+
+pub use self::state::*;
+pub use flatten::*;
+pub use hash::*;
+pub use r#mut::*;
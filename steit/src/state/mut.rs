@@ -0,0 +1,136 @@
+use std::ops::Deref;
+
+use crate::rt::Runtime;
+
+use super::State;
+
+/// Implemented by states whose fields can only be changed through logged methods,
+/// e.g. those derived with `#[steit(accessors)]`.
+///
+/// Those can't flatten fields, which would stay public and be changed unlogged:
+///
+/// ```compile_fail
+/// use steit::steit_derive;
+///
+/// #[steit_derive(Debug, State)]
+/// struct Stats {
+///     #[steit(tag = 0)]
+///     hp: i32,
+/// }
+///
+/// #[steit_derive(Debug, State)]
+/// #[steit(accessors)]
+/// struct Soldier {
+///     #[steit(flatten)]
+///     pub stats: Stats,
+/// }
+/// ```
+pub trait Guarded: State {}
+
+/// A mutable borrow of a child state which only lets logged changes through.
+///
+/// It derefs to the child for reading, but never hands it out mutably, or it could be
+/// assigned or swapped unlogged. Whole values go through [`set`] instead. Collections
+/// expose their logged operations on the handle itself, while the setters and `*_mut`
+/// getters of [`Guarded`] states are forwarded by a trait derived along with them,
+/// e.g. `MemberMut` for `Member`.
+///
+/// [`set`]: #method.set
+/// [`Guarded`]: trait.Guarded.html
+pub struct Mut<'a, T: State> {
+    pub(crate) value: &'a mut T,
+    parent: &'a Runtime,
+    tag: u32,
+}
+
+impl<'a, T: State> Mut<'a, T> {
+    /// Wraps `value`, which lives under `tag` of the state owning `parent`.
+    pub fn new(value: &'a mut T, parent: &'a Runtime, tag: u32) -> Self {
+        Self { value, parent, tag }
+    }
+
+    /// Borrows the handle again for a shorter lifetime, e.g. to pass it down without moving it.
+    pub fn reborrow(&mut self) -> Mut<'_, T> {
+        Mut::new(self.value, self.parent, self.tag)
+    }
+
+    /// Replaces the whole child, giving the new value the runtime of the old one.
+    pub fn set(&mut self, mut value: T) {
+        value.set_runtime(self.parent.nested(self.tag));
+        self.parent.log_update_child(self.tag, &value).unwrap();
+        *self.value = value;
+    }
+
+    pub fn set_with(&mut self, get_value: impl FnOnce(Runtime) -> T) {
+        self.parent.pause_logger();
+        let value = get_value(self.parent.nested(self.tag));
+        self.parent.unpause_logger();
+
+        self.parent.log_update_child(self.tag, &value).unwrap();
+        *self.value = value;
+    }
+}
+
+impl<T: State> Deref for Mut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+/// Not part of the API. Only reachable through this module, so that handles of
+/// [`Guarded`] states can't hand them out mutably by mistake.
+///
+/// [`Guarded`]: ../trait.Guarded.html
+#[doc(hidden)]
+pub mod __private {
+    use super::{Guarded, Mut};
+
+    /// Lets derived handle traits call the logged methods of the child.
+    pub trait GuardedMut<T> {
+        fn guarded_mut(&mut self) -> &mut T;
+    }
+
+    impl<T: Guarded> GuardedMut<T> for Mut<'_, T> {
+        fn guarded_mut(&mut self) -> &mut T {
+            self.value
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        log::loggers::BufferLogger,
+        rt::Runtime,
+        state::State,
+        steit_derive,
+        test_util::{replay, Point},
+        types::List,
+    };
+
+    #[steit_derive(PartialEq, Debug, State)]
+    #[steit(steit_owned, accessors)]
+    struct Squad {
+        #[steit(tag = 0)]
+        points: List<Point>,
+    }
+
+    #[test]
+    fn set() {
+        let (runtime, logger) = Runtime::with_logger_returned(BufferLogger::new());
+        let mut squad = Squad::new(runtime);
+        squad.points_mut().push(Point::new(Runtime::new(), 1, 2, 3));
+
+        let mut point = squad.points_mut();
+        let mut point = point.get_mut(0).unwrap();
+        point.set(Point::new(Runtime::new(), 4, 5, 6));
+        assert_eq!(point.runtime().path(), vec![0, 0]);
+
+        let mut replayed = Squad::new(Runtime::new());
+        replay(&mut replayed, &logger.lock().unwrap().bytes());
+        assert_eq!(replayed.points()[0], squad.points()[0]);
+        assert_eq!(replayed.points()[0].x, 4);
+    }
+}
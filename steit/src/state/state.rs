@@ -0,0 +1,125 @@
+use std::io;
+
+use crate::{
+    de::{Deserialize, Reader},
+    log::{LogEntry, LogEntryKind},
+    rt::Runtime,
+    ser::Serialize,
+    wire_fmt::HasWireType,
+};
+
+use super::{check_hash, state_hash};
+
+pub trait State: Serialize + Deserialize {
+    fn with_runtime(runtime: Runtime) -> Self;
+    fn runtime(&self) -> &Runtime;
+    fn set_runtime(&mut self, runtime: Runtime);
+
+    fn handle(
+        &mut self,
+        path: impl Iterator<Item = u32>,
+        kind: LogEntryKind,
+        key: Option<u32>,
+        reader: &mut Reader<impl io::Read>,
+    ) -> io::Result<()>;
+
+    fn is_root(&self) -> bool {
+        self.runtime().is_root()
+    }
+
+    fn is_child(&self) -> bool {
+        !self.is_root()
+    }
+
+    fn handle_update(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        *self = Self::with_runtime(self.runtime().clone());
+        self.merge(reader)
+    }
+
+    /// Checks the hash carried by a `LogEntryKind::Checksum` entry against ours.
+    fn handle_checksum(&self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        check_hash(self.state_hash(), reader)
+    }
+
+    /// See [`state_hash`](fn.state_hash.html).
+    fn state_hash(&self) -> u64 {
+        state_hash(self)
+    }
+
+    /// Logs the hash of this state, so that replaying the log fails as soon as
+    /// a replica diverges here. Logging it for several subtrees as well as the root
+    /// tells which of them diverged.
+    ///
+    /// Only replicas in Rust check it. The C# replayer hands it to `StateReplayer.OnChecksum`
+    /// without checking, as it can't encode states to hash them.
+    fn log_checksum(&self) -> io::Result<()> {
+        self.runtime().log_checksum(self.state_hash())
+    }
+
+    fn replay(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        if !self.is_root() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "`replay` can only be called on the root `State` object",
+            ));
+        }
+
+        while !reader.eof()? {
+            let entry = LogEntry::deserialize_nested(LogEntry::WIRE_TYPE, reader)?;
+            self.replay_entry(entry)?;
+        }
+
+        Ok(())
+    }
+
+    /// Same as `replay`, but for a single entry which has been deserialized already,
+    /// e.g. by a [`Codec`].
+    ///
+    /// [`Codec`]: ../codec/struct.Codec.html
+    fn replay_entry(&mut self, entry: LogEntry) -> io::Result<()> {
+        if !self.is_root() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "`replay_entry` can only be called on the root `State` object",
+            ));
+        }
+
+        let (kind, path, key, bytes) = unpack_log_entry(entry);
+        let bytes = bytes.unwrap_or_default();
+        let reader = &mut Reader::new(&*bytes);
+
+        if kind == LogEntryKind::Checksum {
+            self.handle(path.iter().copied(), kind, key, reader)
+                .map_err(|error| diverged(&path, error))
+        } else {
+            self.handle(path.into_iter(), kind, key, reader)
+        }
+    }
+}
+
+fn unpack_log_entry(entry: LogEntry) -> (LogEntryKind, Vec<u32>, Option<u32>, Option<Vec<u8>>) {
+    match entry {
+        LogEntry::Update { path, value, .. } => {
+            (LogEntryKind::Update, path, None, Some(value.into_raw()))
+        }
+
+        LogEntry::ListPush { path, item, .. } => {
+            (LogEntryKind::ListPush, path, None, Some(item.into_raw()))
+        }
+
+        LogEntry::ListPop { path, .. } => (LogEntryKind::ListPop, path, None, None),
+        LogEntry::MapRemove { path, key, .. } => (LogEntryKind::MapRemove, path, Some(key), None),
+
+        LogEntry::Checksum { path, hash, .. } => {
+            (LogEntryKind::Checksum, path, None, Some(hash.to_bytes()))
+        }
+    }
+}
+
+/// Names the subtree a failed checksum was logged for.
+pub(crate) fn diverged(path: &[u32], error: io::Error) -> io::Error {
+    io::Error::new(
+        error.kind(),
+        format!("state diverged at path {:?}: {}", path, error),
+    )
+}
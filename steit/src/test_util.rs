@@ -3,7 +3,7 @@ use std::fmt;
 use super::{
     de::{Deserialize, Reader},
     rt::{Runtime, SizeCache},
-    ser::Serialize,
+    ser::{Serialize, Writer},
     state::State,
     steit_derive,
 };
@@ -28,11 +28,7 @@ pub struct Foo(#[steit(tag = 0)] pub i32, #[steit(tag = 1)] pub i32);
 
 impl Foo {
     pub fn new(f0: i32, f1: i32) -> Self {
-        Self {
-            0: f0,
-            1: f1,
-            2: SizeCache::new(),
-        }
+        Self(f0, f1, SizeCache::new())
     }
 }
 
@@ -74,10 +70,10 @@ pub fn assert_serialize(value: impl Serialize, bytes: &[u8]) {
 }
 
 pub fn serialize_nested(value: impl Serialize, tag: impl Into<Option<u32>>) -> Vec<u8> {
-    let mut bytes = Vec::new();
+    let mut writer = Writer::new(Vec::new());
     value.cache_size();
-    value.serialize_nested(tag, true, &mut bytes).unwrap();
-    bytes
+    value.serialize_nested(tag, true, &mut writer).unwrap();
+    writer.into_inner()
 }
 
 pub fn assert_serialize_nested(value: impl Serialize, tag: impl Into<Option<u32>>, bytes: &[u8]) {
@@ -106,7 +102,7 @@ pub fn assert_deserialize<T: PartialEq + fmt::Debug + Deserialize>(bytes: &[u8],
 }
 
 pub fn assert_ser_de<T: Clone + PartialEq + fmt::Debug + Serialize + Deserialize>(value: T) {
-    assert_eq!(deserialize::<T>(&*serialize(value.clone())), value);
+    assert_eq!(deserialize::<T>(&serialize(value.clone())), value);
 }
 
 pub fn replay<T: State>(value: &mut T, bytes: &[u8]) {
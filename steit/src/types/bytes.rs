@@ -1,13 +1,13 @@
 use std::{
     fmt,
-    io::{self, Read},
+    io::{self, Read, Write},
 };
 
 use crate::{
     de::{Deserialize, Reader},
     meta::{HasMeta, MetaLink, NameMeta, TypeMeta},
     rt::SizeCache,
-    ser::Serialize,
+    ser::{Serialize, Writer},
     wire_fmt::{HasWireType, WireType},
 };
 
@@ -25,6 +25,10 @@ impl Bytes {
         Self(bytes)
     }
 
+    pub fn as_raw(&self) -> &[u8] {
+        &self.0
+    }
+
     pub fn into_raw(self) -> Vec<u8> {
         self.0
     }
@@ -45,7 +49,7 @@ impl Serialize for Bytes {
         self.0.len() as u32
     }
 
-    fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+    fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
         writer.write_all(&self.0)
     }
 
@@ -61,6 +65,51 @@ impl Deserialize for Bytes {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Bytes {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Bytes {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+/// Takes byte arrays as well as sequences of bytes, which is what JSON has for them.
+#[cfg(feature = "serde")]
+struct BytesVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a byte array")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Bytes, E> {
+        Ok(Bytes(bytes.to_vec()))
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, bytes: Vec<u8>) -> Result<Bytes, E> {
+        Ok(Bytes(bytes))
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Bytes, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+
+        Ok(Bytes(bytes))
+    }
+}
+
 impl HasMeta for Bytes {
     const NAME: &'static NameMeta = &NameMeta::new("Bytes");
     const TYPE: &'static TypeMeta = &TypeMeta::Ref(Self::NAME, &[]);
@@ -10,8 +10,8 @@ use crate::{
     log::LogEntryKind,
     meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
     rt::{Runtime, SizeCache},
-    ser::Serialize,
-    state::State,
+    ser::{Serialize, Writer},
+    state::{Mut, State},
     wire_fmt::{HasWireType, WireType},
 };
 
@@ -108,11 +108,37 @@ impl<T: State> List<T> {
         Some(removed)
     }
 
-    pub fn iter_mut(&mut self) -> slice::IterMut<T> {
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
         self.items.iter_mut()
     }
 }
 
+impl<'a, T: State> Mut<'a, List<T>> {
+    pub fn get_mut(&mut self, index: usize) -> Option<Mut<'_, T>> {
+        let List { items, runtime, .. } = &mut *self.value;
+
+        items
+            .get_mut(index)
+            .map(move |item| Mut::new(item, runtime, index as u32))
+    }
+
+    pub fn push(&mut self, item: T) -> usize {
+        self.value.push(item)
+    }
+
+    pub fn push_with(&mut self, get_item: impl FnOnce(Runtime) -> T) -> usize {
+        self.value.push_with(get_item)
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.value.pop()
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+        self.value.swap_remove(index)
+    }
+}
+
 impl<T: State> ops::Index<usize> for List<T> {
     type Output = T;
 
@@ -129,9 +155,21 @@ impl<T: State> ops::IndexMut<usize> for List<T> {
 
 impl<T: State> FromIterator<T> for List<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let runtime = Runtime::default();
+
+        let items = iter
+            .into_iter()
+            .enumerate()
+            .map(|(field_number, mut item)| {
+                item.set_runtime(runtime.nested(field_number as u32));
+                item
+            })
+            .collect();
+
         Self {
-            items: iter.into_iter().collect(),
-            ..Default::default()
+            items,
+            size_cache: SizeCache::new(),
+            runtime,
         }
     }
 }
@@ -163,17 +201,23 @@ impl<T: State> Serialize for List<T> {
         self.items.compute_size()
     }
 
-    fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
+    fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
         self.items.serialize_cached(writer)
     }
 
     fn size_cache(&self) -> Option<&SizeCache> {
         Some(&self.size_cache)
     }
+
+    fn change_stamp(&self) -> Option<u64> {
+        Some(self.runtime.change_stamp())
+    }
 }
 
 impl<T: State> Deserialize for List<T> {
     fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        self.runtime.touch();
+
         let mut field_number = self.items.len() as u32;
 
         while !reader.eof()? {
@@ -197,11 +241,13 @@ impl<T: State> State for List<T> {
     }
 
     fn set_runtime(&mut self, runtime: Runtime) {
-        for (field_number, item) in self.items.iter_mut().enumerate() {
-            item.set_runtime(runtime.nested(field_number as u32))
-        }
+        if let Err(runtime) = self.runtime.relocate(runtime) {
+            for (field_number, item) in self.items.iter_mut().enumerate() {
+                item.set_runtime(runtime.nested(field_number as u32))
+            }
 
-        self.runtime = runtime;
+            self.runtime = runtime;
+        }
     }
 
     fn handle(
@@ -211,6 +257,8 @@ impl<T: State> State for List<T> {
         key: Option<u32>,
         reader: &mut Reader<impl io::Read>,
     ) -> io::Result<()> {
+        self.runtime.touch();
+
         if let Some(field_number) = path.next() {
             if let Some(item) = self.items.get_mut(field_number as usize) {
                 item.handle(path, kind, key, reader)
@@ -223,6 +271,7 @@ impl<T: State> State for List<T> {
         } else {
             match kind {
                 LogEntryKind::Update => self.handle_update(reader),
+                LogEntryKind::Checksum => self.handle_checksum(reader),
 
                 LogEntryKind::ListPush => {
                     let field_number = self.items.len() as u32;
@@ -256,10 +305,27 @@ impl<T: State> State for List<T> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<T: State + serde::Serialize> serde::Serialize for List<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.items, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: State + serde::Deserialize<'de>> serde::Deserialize<'de> for List<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let items: Vec<T> = serde::Deserialize::deserialize(deserializer)?;
+        Ok(items.into_iter().collect())
+    }
+}
+
 impl<T: State + HasMeta> HasMeta for List<T> {
     const NAME: &'static NameMeta = &NameMeta {
         rust: "List",
         csharp: Some("StateList"),
+        doc: None,
+        annotations: &[],
     };
 
     const TYPE: &'static TypeMeta = &TypeMeta::Ref(Self::NAME, &[FieldTypeMeta::Type(T::TYPE)]);
@@ -296,7 +362,7 @@ mod tests {
     fn push_and_get() {
         let mut list = list();
         list.push(1);
-        assert_eq!(list.get(0), Some(&1));
+        assert_eq!(list.first(), Some(&1));
     }
 
     #[test]
@@ -329,7 +395,7 @@ mod tests {
         list.push(0);
         list.push(1);
         list.swap_remove(0);
-        assert_eq!(list.get(0), Some(&1));
+        assert_eq!(list.first(), Some(&1));
     }
 
     #[test]
@@ -357,6 +423,39 @@ mod tests {
         assert_eq!(&list.iter().collect::<Vec<_>>(), &[&3, &2]);
     }
 
+    #[test]
+    fn swap_remove_and_log_nested() {
+        let (mut list, logger) = list_with_logger::<List<i32>>();
+
+        for item in 0..3 {
+            list.push_with(|runtime| List::from_iter(runtime, vec![item]));
+        }
+
+        list.swap_remove(0);
+        list.get_mut(0).unwrap().push(7);
+
+        let mut replayed = list_with_logger::<List<i32>>().0;
+        replay(&mut replayed, &logger.lock().unwrap().bytes());
+        assert_eq!(replayed, list);
+        assert_eq!(&*replayed[0], &[2, 7]);
+    }
+
+    #[test]
+    fn push_clone_and_log_nested() {
+        let (mut list, logger) = list_with_logger::<List<i32>>();
+        list.push_with(|runtime| List::from_iter(runtime, vec![1]));
+
+        let clone = list[0].clone();
+        list.push(clone);
+        list.get_mut(0).unwrap().push(2);
+        list.get_mut(1).unwrap().push(3);
+
+        let mut replayed = list_with_logger::<List<i32>>().0;
+        replay(&mut replayed, &logger.lock().unwrap().bytes());
+        assert_eq!(&*replayed[0], &[1, 2]);
+        assert_eq!(&*replayed[1], &[1, 3]);
+    }
+
     #[test]
     fn iter_mut_update_and_check_log() {
         let (mut list, logger) = list_with_logger();
@@ -407,7 +506,7 @@ mod tests {
         logger.lock().unwrap().clear();
         merge(&mut list, &[40, 60]);
         assert_eq!(list.get(3), Some(&30));
-        assert_eq!(logger.lock().unwrap().bytes(), &[]);
+        assert_eq!(logger.lock().unwrap().bytes(), &[] as &[u8]);
     }
 
     #[test]
@@ -422,8 +521,8 @@ mod tests {
     fn replay_push_no_log() {
         let (mut list, logger) = list_with_logger();
         replay(&mut list, &[4, 8, 10, 1, 1]);
-        assert_eq!(list.get(0), Some(&-1));
-        assert_eq!(logger.lock().unwrap().bytes(), &[]);
+        assert_eq!(list.first(), Some(&-1));
+        assert_eq!(logger.lock().unwrap().bytes(), &[] as &[u8]);
     }
 
     #[test]
@@ -445,7 +544,7 @@ mod tests {
         let mut list = list();
         list.push(0);
         replay(&mut list, &[7, 0, 2, 1, 0, 10, 1, 1]);
-        assert_eq!(list.get(0), Some(&-1));
+        assert_eq!(list.first(), Some(&-1));
     }
 
     #[test]
@@ -453,7 +552,7 @@ mod tests {
         let mut list = list();
         list.push_with(|runtime| Point::new(runtime, -1, -1, -1));
         replay(&mut list, &[8, 0, 2, 2, 0, 2, 10, 1, 100]);
-        assert_eq!(list.get(0), Some(&Point::new(Runtime::new(), -1, -1, 50)));
+        assert_eq!(list.first(), Some(&Point::new(Runtime::new(), -1, -1, 50)));
     }
 
     #[test]
@@ -467,7 +566,7 @@ mod tests {
             &[1, 9, /**/ 12, 0, 2, 1, 0, 10, 6, 0, 4, 8, 4, 16, 4],
         );
 
-        assert_eq!(list.get(0), Some(&Point::new(Runtime::new(), 2, 2, 2)));
+        assert_eq!(list.first(), Some(&Point::new(Runtime::new(), 2, 2, 2)));
         assert_eq!(list.get(1), None);
     }
 }
@@ -7,9 +7,9 @@ use crate::{
     log::LogEntryKind,
     meta::{FieldTypeMeta, HasMeta, MetaLink, NameMeta, TypeMeta},
     rt::{Runtime, SizeCache},
-    ser::Serialize,
-    state::State,
-    wire_fmt::{self, HasWireType, WireType},
+    ser::{Serialize, Writer},
+    state::{Mut, State},
+    wire_fmt::{HasWireType, WireType},
 };
 
 use super::{
@@ -56,18 +56,21 @@ impl<K: MapKey, V: State> Map<K, V> {
         self.entries.get_mut(&key.as_field_number())
     }
 
-    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
-        let field_number = key.as_field_number();
-        self.runtime.log_update_child(field_number, &value).unwrap();
-        self.entries.insert(field_number, value)
+    pub fn insert(&mut self, key: K, mut value: V) -> Option<V> {
+        self.insert_with(key, |runtime| {
+            value.set_runtime(runtime);
+            value
+        })
     }
 
     pub fn insert_with(&mut self, key: K, get_value: impl FnOnce(Runtime) -> V) -> Option<V> {
-        let tag = key.as_field_number();
+        let field_number = key.as_field_number();
         self.runtime.pause_logger();
-        let value = get_value(self.runtime.nested(tag));
+        let value = get_value(self.runtime.nested(field_number));
         self.runtime.unpause_logger();
-        self.insert(key, value)
+
+        self.runtime.log_update_child(field_number, &value).unwrap();
+        self.entries.insert(field_number, value)
     }
 
     pub fn remove(&mut self, key: &K) -> Option<V> {
@@ -76,15 +79,40 @@ impl<K: MapKey, V: State> Map<K, V> {
         self.entries.remove(&field_number)
     }
 
-    pub fn iter(&self) -> MapIter<K, V> {
+    pub fn iter(&self) -> MapIter<'_, K, V> {
         MapIter::new(self.entries.iter())
     }
 
-    pub fn iter_mut(&mut self) -> MapIterMut<K, V> {
+    pub fn iter_mut(&mut self) -> MapIterMut<'_, K, V> {
         MapIterMut::new(self.entries.iter_mut())
     }
 }
 
+impl<'a, K: MapKey, V: State> Mut<'a, Map<K, V>> {
+    pub fn get_mut(&mut self, key: &K) -> Option<Mut<'_, V>> {
+        let field_number = key.as_field_number();
+        let Map {
+            entries, runtime, ..
+        } = &mut *self.value;
+
+        entries
+            .get_mut(&field_number)
+            .map(move |value| Mut::new(value, runtime, field_number))
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.value.insert(key, value)
+    }
+
+    pub fn insert_with(&mut self, key: K, get_value: impl FnOnce(Runtime) -> V) -> Option<V> {
+        self.value.insert_with(key, get_value)
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.value.remove(key)
+    }
+}
+
 impl<K: MapKey, V: State> ops::Index<&K> for Map<K, V> {
     type Output = V;
 
@@ -115,15 +143,20 @@ impl<K: MapKey, V: State> Default for Map<K, V> {
 
 impl<K: MapKey, V: State> FromIterator<(K, V)> for Map<K, V> {
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let runtime = Runtime::default();
+
+        let entries = iter
+            .into_iter()
+            .map(|(key, mut value)| {
+                let key = key.as_field_number();
+                value.set_runtime(runtime.nested(key));
+                (key, value)
+            })
+            .collect();
+
         Self {
-            entries: iter
-                .into_iter()
-                .map(|(key, value)| {
-                    let key = key.as_field_number();
-                    (key, value)
-                })
-                .collect(),
-            ..Default::default()
+            entries,
+            ..Self::new(runtime)
         }
     }
 }
@@ -161,9 +194,18 @@ impl<K: MapKey, V: State> Serialize for Map<K, V> {
         size
     }
 
-    fn serialize_cached(&self, writer: &mut impl io::Write) -> io::Result<()> {
-        for (&field_number, value) in &self.entries {
-            value.serialize_nested(field_number, false, writer)?;
+    fn serialize_cached(&self, writer: &mut Writer<impl io::Write>) -> io::Result<()> {
+        if writer.is_canonical() {
+            let mut entries: Vec<_> = self.entries.iter().collect();
+            entries.sort_unstable_by_key(|&(&field_number, _)| field_number);
+
+            for (&field_number, value) in entries {
+                value.serialize_nested(field_number, false, writer)?;
+            }
+        } else {
+            for (&field_number, value) in &self.entries {
+                value.serialize_nested(field_number, false, writer)?;
+            }
         }
 
         Ok(())
@@ -172,21 +214,28 @@ impl<K: MapKey, V: State> Serialize for Map<K, V> {
     fn size_cache(&self) -> Option<&SizeCache> {
         Some(&self.size_cache)
     }
+
+    fn change_stamp(&self) -> Option<u64> {
+        Some(self.runtime.change_stamp())
+    }
 }
 
 impl<K: MapKey, V: State> Deserialize for Map<K, V> {
     fn merge(&mut self, reader: &mut Reader<impl io::Read>) -> io::Result<()> {
+        self.runtime.touch();
+
         while !reader.eof()? {
-            let field_number = u32::deserialize(reader)?;
-            wire_fmt::validate_field_number(field_number)?;
+            let (field_number, wire_type) = reader.read_tag()?;
             K::try_from_field_number(field_number)?;
 
             if let Some(value) = self.entries.get_mut(&field_number) {
-                value.merge_nested(V::WIRE_TYPE, reader)?;
-            } else {
+                value.merge_nested(wire_type, reader)?;
+            } else if wire_type == V::WIRE_TYPE {
                 let mut value = V::with_runtime(self.runtime.nested(field_number));
-                value.merge_nested(V::WIRE_TYPE, reader)?;
+                value.merge_nested(wire_type, reader)?;
                 self.entries.insert(field_number, value);
+            } else {
+                reader.skip_field(wire_type)?;
             }
         }
 
@@ -204,11 +253,13 @@ impl<K: MapKey, V: State> State for Map<K, V> {
     }
 
     fn set_runtime(&mut self, runtime: Runtime) {
-        for (&field_number, value) in self.entries.iter_mut() {
-            value.set_runtime(runtime.nested(field_number));
-        }
+        if let Err(runtime) = self.runtime.relocate(runtime) {
+            for (&field_number, value) in self.entries.iter_mut() {
+                value.set_runtime(runtime.nested(field_number));
+            }
 
-        self.runtime = runtime;
+            self.runtime = runtime;
+        }
     }
 
     fn handle(
@@ -218,12 +269,15 @@ impl<K: MapKey, V: State> State for Map<K, V> {
         key: Option<u32>,
         reader: &mut Reader<impl io::Read>,
     ) -> io::Result<()> {
+        self.runtime.touch();
+
         if let Some(field_number) = path.next() {
             if let Some(value) = self.entries.get_mut(&field_number) {
                 value.handle(path, kind, key, reader)
             } else if kind == LogEntryKind::Update && path.next().is_none() {
+                // Updates carry values as they are, not nested
                 let mut value = V::with_runtime(self.runtime.nested(field_number));
-                value.merge_nested(V::WIRE_TYPE, reader)?;
+                value.merge(reader)?;
                 self.entries.insert(field_number, value);
                 Ok(())
             } else {
@@ -235,6 +289,7 @@ impl<K: MapKey, V: State> State for Map<K, V> {
         } else {
             match kind {
                 LogEntryKind::Update => self.handle_update(reader),
+                LogEntryKind::Checksum => self.handle_checksum(reader),
 
                 LogEntryKind::MapRemove => {
                     let key = key.ok_or_else(|| {
@@ -263,10 +318,69 @@ impl<K: MapKey, V: State> State for Map<K, V> {
     }
 }
 
+/// Keys are put as themselves, e.g. as strings for JSON objects.
+#[cfg(feature = "serde")]
+impl<K: MapKey + serde::Serialize, V: State + serde::Serialize> serde::Serialize for Map<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeMap};
+
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+
+        for (&field_number, value) in &self.entries {
+            let key = K::try_from_field_number(field_number).map_err(S::Error::custom)?;
+            map.serialize_entry(&key, value)?;
+        }
+
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for Map<K, V>
+where
+    K: MapKey + serde::Deserialize<'de>,
+    V: State + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "serde")]
+struct MapVisitor<K, V>(PhantomData<fn() -> (K, V)>);
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::de::Visitor<'de> for MapVisitor<K, V>
+where
+    K: MapKey + serde::Deserialize<'de>,
+    V: State + serde::Deserialize<'de>,
+{
+    type Value = Map<K, V>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(
+        self,
+        mut access: A,
+    ) -> Result<Self::Value, A::Error> {
+        let mut entries = Vec::with_capacity(access.size_hint().unwrap_or_default());
+
+        while let Some(entry) = access.next_entry()? {
+            entries.push(entry);
+        }
+
+        Ok(entries.into_iter().collect())
+    }
+}
+
 impl<K: MapKey, V: State + HasMeta> HasMeta for Map<K, V> {
     const NAME: &'static NameMeta = &NameMeta {
         rust: "Map",
         csharp: Some("StateMap"),
+        doc: None,
+        annotations: &[],
     };
 
     const TYPE: &'static TypeMeta = &TypeMeta::Ref(Self::NAME, &[FieldTypeMeta::Type(V::TYPE)]);
@@ -283,10 +397,12 @@ mod tests {
     use std::io;
 
     use crate::{
+        de::Deserialize,
         log::loggers::BufferLogger,
         rt::{LoggerHandle, Runtime},
+        ser::Serialize,
         state::State,
-        test_util::{assert_serialize, merge, replay, Point},
+        test_util::{assert_serialize, deserialize, merge, replay, Point},
     };
 
     use super::{Map, MapKey};
@@ -447,15 +563,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serialize_canonical() {
+        let mut map = map();
+        map.insert(7u8, 70);
+        map.insert(3, 30);
+        map.insert(6, 60);
+        assert_eq!(map.to_canonical_bytes(), &[24, 60, 48, 120, 56, 140, 1]);
+    }
+
+    #[test]
+    fn back_and_forth() {
+        let mut map = map();
+        map.insert_with(2u32, |runtime| Point::new(runtime, -1, -1, -1));
+        map.insert_with(1, |runtime| Point::new(runtime, 3, 3, 3));
+        assert_eq!(deserialize::<Map<u32, Point>>(&map.to_bytes()), map);
+    }
+
+    #[test]
+    fn from_canonical_bytes() {
+        let bytes = &[24, 60, 48, 120];
+        let map = Map::<u8, i32>::from_canonical_bytes(bytes).unwrap();
+        assert_eq!(map.get(&6), Some(&60));
+
+        assert!(Map::<u8, i32>::from_canonical_bytes(&[48, 120, 24, 60]).is_err());
+        assert!(Map::<u8, i32>::from_canonical_bytes(&[24, 0]).is_ok());
+        assert!(Map::<u8, i32>::from_canonical_bytes(&[24, 188, 0]).is_err());
+    }
+
     #[test]
     fn merge_no_log() {
         let (mut map, logger) = map_with_logger();
         map.insert(1u16, 10);
         map.insert(2, 20);
         logger.lock().unwrap().clear();
-        merge(&mut map, &[3, 60]);
+        merge(&mut map, &[24, 60]);
         assert_eq!(map.get(&3), Some(&30));
-        assert_eq!(logger.lock().unwrap().bytes(), &[]);
+        assert_eq!(logger.lock().unwrap().bytes(), &[] as &[u8]);
     }
 
     #[test]
@@ -463,14 +607,14 @@ mod tests {
         let mut map = map();
         map.insert_with(1u8, |runtime| Point::new(runtime, -1, -1, -1));
         map.insert_with(10, |runtime| Point::new(runtime, 2, 2, 2));
-        merge(&mut map, &[10, 2, 8, 5]);
+        merge(&mut map, &[82, 2, 8, 5]);
         assert_eq!(map.get(&10), Some(&Point::new(Runtime::new(), 2, -3, 2)));
     }
 
     #[test]
     fn merge_push_new() {
         let mut map = map();
-        merge(&mut map, &[2, 2, 16, 7]);
+        merge(&mut map, &[18, 2, 16, 7]);
         assert_eq!(map.get(&2u8), Some(&Point::new(Runtime::new(), 0, 0, -4)));
     }
 
@@ -479,7 +623,7 @@ mod tests {
         let (mut map, logger) = map_with_logger();
         replay(&mut map, &[7, 0, 2, 1, 7, 10, 1, 1]);
         assert_eq!(map.get(&7u8), Some(&-1));
-        assert_eq!(logger.lock().unwrap().bytes(), &[]);
+        assert_eq!(logger.lock().unwrap().bytes(), &[] as &[u8]);
     }
 
     #[test]
@@ -490,6 +634,16 @@ mod tests {
         assert_eq!(map.get(&1), Some(&-1));
     }
 
+    #[test]
+    fn replay_insert_nested() {
+        let mut map = map();
+        replay(&mut map, &[12, 0, 2, 1, 3, 10, 6, 0, 1, 8, 1, 16, 1]);
+        assert_eq!(
+            map.get(&3u32),
+            Some(&Point::new(Runtime::new(), -1, -1, -1))
+        );
+    }
+
     #[test]
     fn replay_update_nested() {
         let mut map = map();
@@ -498,6 +652,23 @@ mod tests {
         assert_eq!(map.get(&3), Some(&Point::new(Runtime::new(), -1, -1, 50)));
     }
 
+    #[test]
+    fn insert_and_log_nested() {
+        let (mut map, logger) = map_with_logger();
+        map.insert_with(3u32, |runtime| Point::new(runtime, 0, 0, 0));
+        logger.lock().unwrap().clear();
+
+        let mut replayed = map_with_logger().0;
+        replayed.insert_with(3, |runtime| Point::new(runtime, 0, 0, 0));
+
+        map.insert(3, Point::new(Runtime::new(), 1, 2, 3));
+        map.get_mut(&3).unwrap().set_z(4);
+
+        replay(&mut replayed, &logger.lock().unwrap().bytes());
+        assert_eq!(replayed, map);
+        assert_eq!(replayed.get(&3).unwrap().z, 4);
+    }
+
     #[test]
     fn replay_remove() {
         let mut map = map();
@@ -6,7 +6,7 @@ use crate::{
 };
 
 #[steit_derive(Clone, PartialEq, Eq, Hash, Debug, State)]
-#[steit(steit_owned)]
+#[steit(steit_owned, serde = false)]
 pub enum Maybe<T: State> {
     #[steit(tag = 0)]
     #[steit(no_size_cache)]
@@ -68,3 +68,29 @@ impl<T: PartialEq + State> PartialEq<Option<T>> for Maybe<T> {
         }
     }
 }
+
+/// As an option of the value rather than as the enum it is.
+#[cfg(feature = "serde")]
+impl<T: State + serde::Serialize> serde::Serialize for Maybe<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Maybe::Some { value, .. } => serializer.serialize_some(value),
+            Maybe::None { .. } => serializer.serialize_none(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: State + serde::Deserialize<'de>> serde::Deserialize<'de> for Maybe<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(mut value) => {
+                let runtime = Runtime::new().nested(1);
+                value.set_runtime(runtime.nested(0));
+                Self::some(runtime, value)
+            }
+
+            None => Self::new_none(Runtime::new()),
+        })
+    }
+}